@@ -296,11 +296,26 @@ extern "C" {
 extern "C" {
     pub fn bitset_flip_list(words: *mut u64, list: *const u16, length: u64);
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct array_container_s {
+    pub cardinality: i32,
+    pub capacity: i32,
+    pub array: *mut u16,
+}
+pub type array_container_t = array_container_s;
 pub const BITSET_CONTAINER_SIZE_IN_WORDS: _bindgen_ty_4 = 1024;
 pub const BITSET_UNKNOWN_CARDINALITY: _bindgen_ty_4 = -1;
 pub type _bindgen_ty_4 = ::core::ffi::c_int;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub struct bitset_container_s {
+    pub cardinality: i32,
+    pub words: *mut u64,
+}
+pub type bitset_container_t = bitset_container_s;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct rle16_s {
     pub value: u16,
     pub length: u16,
@@ -308,6 +323,18 @@ pub struct rle16_s {
 pub type rle16_t = rle16_s;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub struct run_container_s {
+    pub n_runs: i32,
+    pub capacity: i32,
+    pub runs: *mut rle16_t,
+}
+pub type run_container_t = run_container_s;
+pub const BITSET_CONTAINER_TYPE: u32 = 1;
+pub const ARRAY_CONTAINER_TYPE: u32 = 2;
+pub const RUN_CONTAINER_TYPE: u32 = 3;
+pub const SHARED_CONTAINER_TYPE: u32 = 4;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct roaring_bitmap_s {
     pub high_low_container: roaring_array_t,
 }
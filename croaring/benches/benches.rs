@@ -1,6 +1,5 @@
-use criterion::{
-    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
-};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
 use std::ops::ControlFlow;
 
 use croaring::{Bitmap, Bitmap64, Portable};
@@ -122,7 +121,7 @@ fn binops(c: &mut Criterion) {
         }};
     }
 
-    bench_op!(and, and_inplace);
+    bench_op!(and, and_inplace, fast_and);
     bench_op!(or, or_inplace, fast_or, fast_or_heap);
     bench_op!(xor, xor_inplace, fast_xor);
     bench_op!(andnot, andnot_inplace);
@@ -159,7 +158,7 @@ fn to_vec(c: &mut Criterion) {
     g.bench_function("foreach", |b| {
         b.iter(|| {
             let mut vec = Vec::with_capacity(bitmap.cardinality() as usize);
-            bitmap.for_each(|item| -> ControlFlow<()> {
+            let _ = bitmap.for_each(|item| -> ControlFlow<()> {
                 vec.push(item);
                 ControlFlow::Continue(())
             });
@@ -221,7 +220,7 @@ fn deserialize(c: &mut Criterion) {
             .unwrap();
         group.throughput(Throughput::Elements(size.into()));
         group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
-            b.iter(|| Bitmap::deserialize::<Portable>(&serialized_buffer));
+            b.iter(|| Bitmap::deserialize::<Portable>(serialized_buffer));
         });
     }
 }
@@ -277,8 +276,6 @@ fn random_iter() -> RandomIter {
 
 fn create_random(c: &mut Criterion) {
     const N: u32 = 5_000;
-    // Clamp values so we get some re-use of containers
-    const MAX: u32 = 8 * (u16::MAX as u32 + 1);
 
     let mut group = c.benchmark_group("random_iter");
     group.throughput(Throughput::Elements(N.into()));
@@ -309,7 +306,7 @@ fn collect_bitmap64_to_vec(c: &mut Criterion) {
     const N: u64 = 1_000_000;
 
     let mut group = c.benchmark_group("collect_bitmap64_to_vec");
-    group.throughput(Throughput::Elements(N.into()));
+    group.throughput(Throughput::Elements(N));
     let bitmap = Bitmap64::from_range(0..N);
 
     #[cfg(feature = "alloc")]
@@ -321,7 +318,7 @@ fn collect_bitmap64_to_vec(c: &mut Criterion) {
             || (),
             |()| {
                 let mut vec = Vec::with_capacity(bitmap.cardinality() as usize);
-                bitmap.for_each(|item| -> ControlFlow<()> {
+                let _ = bitmap.for_each(|item| -> ControlFlow<()> {
                     vec.push(item);
                     ControlFlow::Continue(())
                 });
@@ -362,7 +359,7 @@ fn iterate_bitmap64(c: &mut Criterion) {
     const END_ITER: u64 = N - 100;
 
     let mut group = c.benchmark_group("bitmap64_iterate");
-    group.throughput(Throughput::Elements(N.into()));
+    group.throughput(Throughput::Elements(N));
     let bitmap = Bitmap64::from_range(0..N);
     group.bench_function("iter", |b| {
         b.iter(|| {
@@ -3,8 +3,9 @@ use roaring::RoaringBitmap;
 
 use criterion::measurement::Measurement;
 use criterion::{
-    black_box, criterion_group, criterion_main, BatchSize, BenchmarkGroup, BenchmarkId, Criterion,
+    criterion_group, criterion_main, BatchSize, BenchmarkGroup, BenchmarkId, Criterion,
 };
+use std::hint::black_box;
 
 fn compare<Prep1, Prep2, Bench1, Bench2, In1, In2, Out1, Out2, M>(
     group: &mut BenchmarkGroup<'_, M>,
@@ -20,10 +21,10 @@ fn compare<Prep1, Prep2, Bench1, Bench2, In1, In2, Out1, Out2, M>(
     M: Measurement,
 {
     group.bench_function("croaring", |b| {
-        b.iter_batched(|| prep1(), |x| bench1(x), BatchSize::SmallInput);
+        b.iter_batched(&mut prep1, &mut bench1, BatchSize::SmallInput);
     });
     group.bench_function("roaring-rs", |b| {
-        b.iter_batched(|| prep2(), |x| bench2(x), BatchSize::SmallInput);
+        b.iter_batched(&mut prep2, &mut bench2, BatchSize::SmallInput);
     });
 }
 
@@ -194,8 +195,8 @@ fn iter(c: &mut Criterion) {
         &mut c.benchmark_group("iter"),
         || Bitmap::from_iter(1..10_000),
         || RoaringBitmap::from_iter(1..10_000),
-        |bitmap: Bitmap| assert_eq!(bitmap.iter().fold(0, |a, b| a + b), 49995000),
-        |bitmap: RoaringBitmap| assert_eq!(bitmap.iter().fold(0, |a, b| a + b), 49995000),
+        |bitmap: Bitmap| assert_eq!(bitmap.iter().sum::<u32>(), 49995000),
+        |bitmap: RoaringBitmap| assert_eq!(bitmap.iter().sum::<u32>(), 49995000),
     );
 }
 
@@ -0,0 +1,168 @@
+use super::Bitmap;
+use core::mem::MaybeUninit;
+use core::ops::Deref;
+
+/// A context for repeated membership tests and insertions on a single bitmap
+///
+/// The context remembers the container used by the last operation, so consecutive operations on
+/// values which share their high 16 bits avoid searching the bitmap for the container again.
+///
+/// A context borrows the bitmap it was created for: created with
+/// [`Bitmap::bulk_context`], it can only test membership, while one created with
+/// [`Bitmap::bulk_context_mut`] can also add values.
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap;
+///
+/// let mut bitmap = Bitmap::new();
+/// let mut context = bitmap.bulk_context_mut();
+/// context.add(1);
+/// context.add(2);
+/// assert!(context.contains(1));
+/// assert!(!context.contains(3));
+///
+/// let mut context = bitmap.bulk_context();
+/// let found = [1, 2, 3].iter().filter(|&&x| context.contains(x)).count();
+/// assert_eq!(found, 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BulkContext<B> {
+    bitmap: B,
+    context: ffi::roaring_bulk_context_t,
+}
+
+// SAFETY: the context caches a pointer to the container used by the last operation, owned by
+// the bitmap in `bitmap`. CRoaring only dereferences it when the context is passed along with
+// that same bitmap, so the context may be sent between threads along with `B`.
+unsafe impl<B: Send> Send for BulkContext<B> {}
+
+// SAFETY: every method which passes the context to CRoaring takes `&mut self`, so `&BulkContext`
+// only grants access to `B`, which must itself be `Sync`.
+unsafe impl<B: Sync> Sync for BulkContext<B> {}
+
+impl<B> BulkContext<B> {
+    #[inline]
+    fn new(bitmap: B) -> Self {
+        let context = unsafe { MaybeUninit::<ffi::roaring_bulk_context_t>::zeroed().assume_init() };
+        Self { bitmap, context }
+    }
+}
+
+impl<B: Deref<Target = Bitmap>> BulkContext<B> {
+    /// Returns the bitmap this context operates on
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap = Bitmap::of(&[1, 2, 3]);
+    /// let context = bitmap.bulk_context();
+    /// assert_eq!(context.bitmap().cardinality(), 3);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.bitmap
+    }
+
+    /// Returns true if the bitmap contains `value`
+    ///
+    /// This is faster than [`Bitmap::contains`] when consecutive calls are made with values which
+    /// share their high 16 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap = Bitmap::of(&[1, 2, 3, 0x1_0000]);
+    /// let mut context = bitmap.bulk_context();
+    /// assert!(context.contains(1));
+    /// assert!(!context.contains(4));
+    /// assert!(context.contains(0x1_0000));
+    /// assert!(context.contains(3));
+    /// ```
+    #[inline]
+    #[doc(alias = "roaring_bitmap_contains_bulk")]
+    #[must_use]
+    pub fn contains(&mut self, value: u32) -> bool {
+        unsafe { ffi::roaring_bitmap_contains_bulk(&self.bitmap.bitmap, &mut self.context, value) }
+    }
+}
+
+impl BulkContext<&mut Bitmap> {
+    /// Add the integer `value` to the bitmap
+    ///
+    /// This is faster than [`Bitmap::add`] when consecutive calls are made with values which share
+    /// their high 16 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::new();
+    /// let mut context = bitmap.bulk_context_mut();
+    /// for i in 0..10 {
+    ///     context.add(i * 2);
+    /// }
+    /// assert_eq!(bitmap.cardinality(), 10);
+    /// assert!(bitmap.contains(18));
+    /// ```
+    #[inline]
+    #[doc(alias = "roaring_bitmap_add_bulk")]
+    pub fn add(&mut self, value: u32) {
+        unsafe { ffi::roaring_bitmap_add_bulk(&mut self.bitmap.bitmap, &mut self.context, value) }
+    }
+}
+
+impl Extend<u32> for BulkContext<&mut Bitmap> {
+    fn extend<T: IntoIterator<Item = u32>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|value| self.add(value));
+    }
+}
+
+impl Bitmap {
+    /// Returns a context for repeated membership tests on this bitmap
+    ///
+    /// See [`BulkContext`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap = Bitmap::from_range(100..200);
+    /// let mut context = bitmap.bulk_context();
+    /// assert_eq!((0..1000).filter(|&x| context.contains(x)).count(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn bulk_context(&self) -> BulkContext<&Self> {
+        BulkContext::new(self)
+    }
+
+    /// Returns a context for repeated insertions and membership tests on this bitmap
+    ///
+    /// See [`BulkContext`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::new();
+    /// let mut context = bitmap.bulk_context_mut();
+    /// context.extend([1, 5, 3]);
+    /// assert!(context.contains(5));
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [1, 3, 5]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn bulk_context_mut(&mut self) -> BulkContext<&mut Self> {
+        BulkContext::new(self)
+    }
+}
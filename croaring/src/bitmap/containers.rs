@@ -0,0 +1,290 @@
+use super::Bitmap;
+use core::ffi::c_void;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+
+const BITSET_CONTAINER_TYPE: u8 = ffi::BITSET_CONTAINER_TYPE as u8;
+const ARRAY_CONTAINER_TYPE: u8 = ffi::ARRAY_CONTAINER_TYPE as u8;
+const RUN_CONTAINER_TYPE: u8 = ffi::RUN_CONTAINER_TYPE as u8;
+const SHARED_CONTAINER_TYPE: u8 = ffi::SHARED_CONTAINER_TYPE as u8;
+
+/// The representation used by a container
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ContainerType {
+    /// A sorted array of 16-bit values, used for sparse containers
+    Array,
+    /// A bitset of 2^16 bits, used for dense containers
+    Bitset,
+    /// A sorted list of runs of consecutive values
+    Run,
+}
+
+/// Metadata about a single container of a [`Bitmap`]
+///
+/// See [`Bitmap::containers`] for more information
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ContainerInfo {
+    /// The high 16 bits shared by all values in the container
+    pub key: u16,
+    /// The representation used by the container
+    pub container_type: ContainerType,
+    /// Number of values in the container
+    pub cardinality: u32,
+    /// Number of bytes used by the container's data, as counted by [`Bitmap::statistics`]
+    pub size_in_bytes: u32,
+    /// Number of runs of consecutive values in the container
+    ///
+    /// This is computed for every container type, so it can be used to judge whether
+    /// [`Bitmap::run_optimize`] would help.
+    pub n_runs: u32,
+}
+
+impl ContainerInfo {
+    /// Reads the metadata of a container
+    ///
+    /// # Safety
+    ///
+    /// `container` and `typecode` must describe a valid, unshared container
+    ///
+    /// The container need not be aligned: the containers of a [`BitmapView`][super::BitmapView]
+    /// are stored in, or alongside, the serialized data.
+    unsafe fn from_raw(key: u16, container: *const c_void, typecode: u8) -> Self {
+        match typecode {
+            ARRAY_CONTAINER_TYPE => {
+                let array = container.cast::<ffi::array_container_t>().read_unaligned();
+                let cardinality = array.cardinality as u32;
+                let mut n_runs = 0;
+                let mut prev = None;
+                for value in read_unaligned(array.array, cardinality as usize) {
+                    if prev.map(|prev: u16| prev + 1) != Some(value) {
+                        n_runs += 1;
+                    }
+                    prev = Some(value);
+                }
+                Self {
+                    key,
+                    container_type: ContainerType::Array,
+                    cardinality,
+                    size_in_bytes: cardinality * 2,
+                    n_runs,
+                }
+            }
+            BITSET_CONTAINER_TYPE => {
+                let bitset = container.cast::<ffi::bitset_container_t>().read_unaligned();
+                let n_words = ffi::BITSET_CONTAINER_SIZE_IN_WORDS as usize;
+                let mut cardinality = 0;
+                let mut n_runs = 0;
+                let mut carry = 0;
+                for word in read_unaligned(bitset.words, n_words) {
+                    cardinality += word.count_ones();
+                    // Count the set bits which do not follow another set bit
+                    n_runs += (word & !((word << 1) | carry)).count_ones();
+                    carry = word >> 63;
+                }
+                Self {
+                    key,
+                    container_type: ContainerType::Bitset,
+                    cardinality,
+                    size_in_bytes: n_words as u32 * 8,
+                    n_runs,
+                }
+            }
+            RUN_CONTAINER_TYPE => {
+                let run = container.cast::<ffi::run_container_t>().read_unaligned();
+                let n_runs = run.n_runs as u32;
+                Self {
+                    key,
+                    container_type: ContainerType::Run,
+                    cardinality: read_unaligned(run.runs, n_runs as usize)
+                        .map(|r| u32::from(r.length) + 1)
+                        .sum(),
+                    size_in_bytes: 2 + n_runs * 4,
+                    n_runs,
+                }
+            }
+            _ => unreachable!("invalid container typecode {typecode}"),
+        }
+    }
+}
+
+/// Returns the container at index `i` of `bitmap`, and its typecode
+///
+/// Shared containers (created by copy on write) are resolved to the container they wrap, through
+/// a value iterator, which CRoaring positions on the unwrapped container.
+///
+/// # Safety
+///
+/// `i` must be less than the number of containers in `bitmap`
+#[inline]
+unsafe fn container_at(bitmap: &ffi::roaring_bitmap_t, i: usize) -> (u16, *const c_void, u8) {
+    let array = &bitmap.high_low_container;
+    let key = array.keys.add(i).read_unaligned();
+    let typecode = *array.typecodes.add(i);
+    if typecode != SHARED_CONTAINER_TYPE {
+        return (key, array.containers.add(i).read_unaligned(), typecode);
+    }
+    let mut it = MaybeUninit::<ffi::roaring_uint32_iterator_t>::uninit();
+    ffi::roaring_iterator_init(bitmap, it.as_mut_ptr());
+    let mut it = it.assume_init();
+    // Containers are never empty, so this lands on the container with this key
+    let found = ffi::roaring_uint32_iterator_move_equalorlarger(&mut it, u32::from(key) << 16);
+    debug_assert!(found && it.highbits == u32::from(key) << 16);
+    (key, it.container, it.typecode)
+}
+
+/// Writes the data of every container of `bitmap`, as laid out in the portable format
+///
+/// The data of array and bitset containers is written as-is, run containers are prefixed by
+/// their number of runs.
+#[cfg(feature = "std")]
+pub(super) fn write_portable_data<W: std::io::Write + ?Sized>(
+    bitmap: &Bitmap,
+    dst: &mut W,
+) -> std::io::Result<()> {
+    // Only ever viewed as bytes, so the data need not be aligned
+    let bytes = |data: *const c_void, len: usize| unsafe {
+        core::slice::from_raw_parts(data.cast::<u8>(), len)
+    };
+    for i in 0..usize::try_from(bitmap.bitmap.high_low_container.size).unwrap_or(0) {
+        unsafe {
+            let (_, container, typecode) = container_at(&bitmap.bitmap, i);
+            match typecode {
+                ARRAY_CONTAINER_TYPE => {
+                    let array = container.cast::<ffi::array_container_t>().read_unaligned();
+                    dst.write_all(bytes(array.array.cast(), array.cardinality as usize * 2))?;
+                }
+                BITSET_CONTAINER_TYPE => {
+                    let bitset = container.cast::<ffi::bitset_container_t>().read_unaligned();
+                    let n_words = ffi::BITSET_CONTAINER_SIZE_IN_WORDS as usize;
+                    dst.write_all(bytes(bitset.words.cast(), n_words * 8))?;
+                }
+                RUN_CONTAINER_TYPE => {
+                    let run = container.cast::<ffi::run_container_t>().read_unaligned();
+                    dst.write_all(&(run.n_runs as u16).to_ne_bytes())?;
+                    dst.write_all(bytes(run.runs.cast(), run.n_runs as usize * 4))?;
+                }
+                _ => unreachable!("invalid container typecode {typecode}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads `len` values starting at `data`
+#[inline]
+unsafe fn read_unaligned<T: Copy>(data: *const T, len: usize) -> impl Iterator<Item = T> {
+    (0..len).map(move |i| unsafe { data.add(i).read_unaligned() })
+}
+
+/// An iterator over the containers of a [`Bitmap`]
+///
+/// See [`Bitmap::containers`] for more information
+#[derive(Debug, Clone)]
+pub struct Containers<'a> {
+    bitmap: &'a ffi::roaring_bitmap_t,
+    index: usize,
+    end: usize,
+    _bitmap: PhantomData<&'a Bitmap>,
+}
+
+impl Containers<'_> {
+    #[inline]
+    fn info(&self, i: usize) -> ContainerInfo {
+        unsafe {
+            let (key, container, typecode) = container_at(self.bitmap, i);
+            ContainerInfo::from_raw(key, container, typecode)
+        }
+    }
+}
+
+// SAFETY: Containers only reads the bitmap through a shared reference, like `&Bitmap`, which is
+// Send and Sync. The iterator used to resolve shared containers is local to each call.
+unsafe impl Send for Containers<'_> {}
+
+// SAFETY: see above, no method mutates any state reachable through `&Containers`
+unsafe impl Sync for Containers<'_> {}
+
+impl Iterator for Containers<'_> {
+    type Item = ContainerInfo;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.end {
+            return None;
+        }
+        let info = self.info(self.index);
+        self.index += 1;
+        Some(info)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.index;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Containers<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(self.info(self.end))
+    }
+}
+
+impl ExactSizeIterator for Containers<'_> {}
+
+impl FusedIterator for Containers<'_> {}
+
+impl Bitmap {
+    /// Returns an iterator over metadata about each container in the bitmap
+    ///
+    /// A bitmap stores its values in containers of up to 2^16 values, grouped by their high
+    /// 16 bits. Containers are returned in ascending order of key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    /// use croaring::bitmap::ContainerType;
+    ///
+    /// let mut bitmap = Bitmap::of(&[1, 2, 3, 10]);
+    /// bitmap.add_range(0x1_0000..0x1_1000);
+    /// bitmap.add_range(0x2_0000..0x3_0000);
+    /// bitmap.remove(0x2_8000);
+    /// bitmap.run_optimize();
+    ///
+    /// let containers: Vec<_> = bitmap.containers().collect();
+    /// assert_eq!(containers.len(), 3);
+    ///
+    /// assert_eq!(containers[0].key, 0);
+    /// assert_eq!(containers[0].container_type, ContainerType::Array);
+    /// assert_eq!(containers[0].cardinality, 4);
+    /// assert_eq!(containers[0].size_in_bytes, 8);
+    /// assert_eq!(containers[0].n_runs, 2);
+    ///
+    /// assert_eq!(containers[1].key, 1);
+    /// assert_eq!(containers[1].container_type, ContainerType::Run);
+    /// assert_eq!(containers[1].cardinality, 0x1000);
+    /// assert_eq!(containers[1].n_runs, 1);
+    ///
+    /// assert_eq!(containers[2].key, 2);
+    /// assert_eq!(containers[2].container_type, ContainerType::Run);
+    /// assert_eq!(containers[2].cardinality, 0xFFFF);
+    /// assert_eq!(containers[2].n_runs, 2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn containers(&self) -> Containers<'_> {
+        Containers {
+            bitmap: &self.bitmap,
+            index: 0,
+            end: usize::try_from(self.bitmap.high_low_container.size).unwrap_or(0),
+            _bitmap: PhantomData,
+        }
+    }
+}
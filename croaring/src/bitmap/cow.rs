@@ -0,0 +1,462 @@
+use super::{Bitmap, BitmapIterator};
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::RangeBounds;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// A copy-on-write bitmap
+///
+/// Clones of a `CowBitmap` share their containers with the original, and only copy a container
+/// when either of them modifies it. This makes cloning cheap, and greatly reduces memory usage
+/// when keeping many slightly different versions of a large bitmap.
+///
+/// A `CowBitmap` intentionally exposes only a subset of the [`Bitmap`] API, and never hands out a
+/// `&Bitmap`: shared containers must never leak into a regular bitmap. Use
+/// [`CowBitmap::into_bitmap`] or [`CowBitmap::to_bitmap`] to get a regular bitmap.
+///
+/// # Thread safety
+///
+/// `CowBitmap` is [`Send`], so clones may be moved to (and modified on) other threads. All
+/// bitmaps cloned from the same original share a lock, which is held while any of them is
+/// modified or dropped, because releasing a shared container is not thread safe in `CRoaring`.
+/// Reading a `CowBitmap` never takes the lock.
+///
+/// `CowBitmap` is not [`Sync`]: even cloning through a shared reference converts the containers
+/// of the original into shared containers.
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<croaring::CowBitmap>();
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// use croaring::{Bitmap, CowBitmap};
+///
+/// let original = CowBitmap::from(Bitmap::from_range(0..100_000));
+/// let mut snapshot = original.clone();
+/// assert!(original.contains_shared());
+///
+/// snapshot.remove(5);
+/// assert!(original.contains(5));
+/// assert!(!snapshot.contains(5));
+///
+/// let thread_snapshot = snapshot.clone();
+/// let modified = std::thread::spawn(move || {
+///     let mut thread_snapshot = thread_snapshot;
+///     thread_snapshot.add(200_000);
+///     thread_snapshot
+/// })
+/// .join()
+/// .unwrap();
+///
+/// assert!(modified.contains(200_000));
+/// assert!(!snapshot.contains(200_000));
+/// assert_eq!(modified.into_bitmap().cardinality(), 100_000);
+/// ```
+pub struct CowBitmap {
+    // Always has the copy on write flag set
+    bitmap: Bitmap,
+    // Shared by all bitmaps which may share containers with this one
+    family: Arc<Mutex<()>>,
+    // Not Sync: cloning modifies the source bitmap through a shared reference
+    not_sync: PhantomData<Cell<()>>,
+}
+
+#[inline]
+fn lock(family: &Mutex<()>) -> MutexGuard<'_, ()> {
+    // The lock protects no data, a panic while holding it can't leave anything inconsistent
+    family.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Sets the copy on write flag again, after an operation which may have cleared it
+///
+/// When a bitmap is empty, CRoaring implements some in-place operations by overwriting it with a
+/// copy of the other bitmap, which also copies the other bitmap's flag.
+#[inline]
+fn restore_copy_on_write(bitmap: &mut Bitmap) {
+    // No containers are shared while the flag is unset, so setting it only marks them
+    unsafe { ffi::roaring_bitmap_set_copy_on_write(&mut bitmap.bitmap, true) };
+}
+
+impl CowBitmap {
+    /// Creates a new, empty copy-on-write bitmap
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::CowBitmap;
+    ///
+    /// let bitmap = CowBitmap::new();
+    /// assert!(bitmap.is_empty());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from(Bitmap::new())
+    }
+
+    /// Converts this bitmap into a regular [`Bitmap`]
+    ///
+    /// Any containers still shared with other bitmaps are copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, CowBitmap};
+    ///
+    /// let original = CowBitmap::from(Bitmap::of(&[1, 2, 3]));
+    /// let copy = original.clone();
+    ///
+    /// let bitmap: Bitmap = copy.into_bitmap();
+    /// assert_eq!(bitmap, Bitmap::of(&[1, 2, 3]));
+    /// assert!(original.contains(1));
+    /// ```
+    #[doc(alias = "roaring_unshare_all")]
+    #[must_use]
+    pub fn into_bitmap(mut self) -> Bitmap {
+        let _guard = lock(&self.family);
+        // Unshares all containers, and clears the flag
+        unsafe { ffi::roaring_bitmap_set_copy_on_write(&mut self.bitmap.bitmap, false) };
+        mem::take(&mut self.bitmap)
+    }
+
+    /// Returns a regular [`Bitmap`] with the same contents as this bitmap
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, CowBitmap};
+    ///
+    /// let bitmap = CowBitmap::from(Bitmap::of(&[1, 2, 3]));
+    /// assert_eq!(bitmap.to_bitmap(), Bitmap::of(&[1, 2, 3]));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn to_bitmap(&self) -> Bitmap {
+        self.clone().into_bitmap()
+    }
+
+    /// Returns true if this bitmap contains any shared containers
+    ///
+    /// Containers stay marked as shared after all other bitmaps sharing them have been dropped,
+    /// until they are modified, or [`Self::unshare`] is called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, CowBitmap};
+    ///
+    /// let mut bitmap = CowBitmap::from(Bitmap::of(&[1, 2, 3]));
+    /// assert!(!bitmap.contains_shared());
+    ///
+    /// let clone = bitmap.clone();
+    /// assert!(bitmap.contains_shared());
+    /// assert!(clone.contains_shared());
+    ///
+    /// bitmap.add(4);
+    /// assert!(!bitmap.contains_shared());
+    /// ```
+    #[inline]
+    #[doc(alias = "roaring_contains_shared")]
+    #[must_use]
+    pub fn contains_shared(&self) -> bool {
+        unsafe { ffi::roaring_contains_shared(&self.bitmap.bitmap) }
+    }
+
+    /// Copies all containers shared with other bitmaps
+    ///
+    /// Returns true if any containers were shared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, CowBitmap};
+    ///
+    /// let mut bitmap = CowBitmap::from(Bitmap::of(&[1, 2, 3]));
+    /// let clone = bitmap.clone();
+    ///
+    /// assert!(bitmap.unshare());
+    /// assert!(!bitmap.contains_shared());
+    /// assert!(!bitmap.unshare());
+    /// ```
+    #[inline]
+    #[doc(alias = "roaring_unshare_all")]
+    pub fn unshare(&mut self) -> bool {
+        let _guard = lock(&self.family);
+        unsafe { ffi::roaring_unshare_all(&mut self.bitmap.bitmap) }
+    }
+
+    /// Add the integer element to the bitmap
+    ///
+    /// See [`Bitmap::add`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_add")]
+    pub fn add(&mut self, element: u32) {
+        let _guard = lock(&self.family);
+        self.bitmap.add(element);
+    }
+
+    /// Add all values in `elements` to the bitmap
+    ///
+    /// See [`Bitmap::add_many`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_add_many")]
+    pub fn add_many(&mut self, elements: &[u32]) {
+        let _guard = lock(&self.family);
+        self.bitmap.add_many(elements);
+    }
+
+    /// Add all values in range
+    ///
+    /// See [`Bitmap::add_range`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_add_range_closed")]
+    pub fn add_range<R: RangeBounds<u32>>(&mut self, range: R) {
+        let _guard = lock(&self.family);
+        self.bitmap.add_range(range);
+    }
+
+    /// Remove the integer element from the bitmap
+    ///
+    /// See [`Bitmap::remove`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_remove")]
+    pub fn remove(&mut self, element: u32) {
+        let _guard = lock(&self.family);
+        self.bitmap.remove(element);
+    }
+
+    /// Remove all values in range
+    ///
+    /// See [`Bitmap::remove_range`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_remove_range_closed")]
+    pub fn remove_range<R: RangeBounds<u32>>(&mut self, range: R) {
+        let _guard = lock(&self.family);
+        self.bitmap.remove_range(range);
+    }
+
+    /// Empties the bitmap
+    ///
+    /// See [`Bitmap::clear`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_clear")]
+    pub fn clear(&mut self) {
+        let _guard = lock(&self.family);
+        self.bitmap.clear();
+    }
+
+    /// Modifies the bitmap to be the intersection of itself and `other`
+    ///
+    /// See [`Bitmap::and_inplace`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_and_inplace")]
+    pub fn and_inplace(&mut self, other: &Bitmap) {
+        let _guard = lock(&self.family);
+        self.bitmap.and_inplace(other);
+    }
+
+    /// Modifies the bitmap to be the union of itself and `other`
+    ///
+    /// See [`Bitmap::or_inplace`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_or_inplace")]
+    pub fn or_inplace(&mut self, other: &Bitmap) {
+        let _guard = lock(&self.family);
+        self.bitmap.or_inplace(other);
+        restore_copy_on_write(&mut self.bitmap);
+    }
+
+    /// Modifies the bitmap to be the symmetric difference of itself and `other`
+    ///
+    /// See [`Bitmap::xor_inplace`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_xor_inplace")]
+    pub fn xor_inplace(&mut self, other: &Bitmap) {
+        let _guard = lock(&self.family);
+        self.bitmap.xor_inplace(other);
+        restore_copy_on_write(&mut self.bitmap);
+    }
+
+    /// Modifies the bitmap to be the difference of itself and `other`
+    ///
+    /// See [`Bitmap::andnot_inplace`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_andnot_inplace")]
+    pub fn andnot_inplace(&mut self, other: &Bitmap) {
+        let _guard = lock(&self.family);
+        self.bitmap.andnot_inplace(other);
+    }
+
+    /// Convert internal containers to run containers where it is more space efficient
+    ///
+    /// See [`Bitmap::run_optimize`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_run_optimize")]
+    pub fn run_optimize(&mut self) -> bool {
+        let _guard = lock(&self.family);
+        self.bitmap.run_optimize()
+    }
+
+    /// Returns true if the integer element is contained in the bitmap
+    ///
+    /// See [`Bitmap::contains`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_contains")]
+    #[must_use]
+    pub fn contains(&self, element: u32) -> bool {
+        self.bitmap.contains(element)
+    }
+
+    /// Returns the number of integers contained in the bitmap
+    ///
+    /// See [`Bitmap::cardinality`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_get_cardinality")]
+    #[must_use]
+    pub fn cardinality(&self) -> u64 {
+        self.bitmap.cardinality()
+    }
+
+    /// Returns true if the bitmap is empty
+    ///
+    /// See [`Bitmap::is_empty`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_is_empty")]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bitmap.is_empty()
+    }
+
+    /// Returns the smallest value in the bitmap, or None if the bitmap is empty
+    ///
+    /// See [`Bitmap::minimum`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_minimum")]
+    #[must_use]
+    pub fn minimum(&self) -> Option<u32> {
+        self.bitmap.minimum()
+    }
+
+    /// Returns the greatest value in the bitmap, or None if the bitmap is empty
+    ///
+    /// See [`Bitmap::maximum`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_maximum")]
+    #[must_use]
+    pub fn maximum(&self) -> Option<u32> {
+        self.bitmap.maximum()
+    }
+
+    /// Returns the number of integers that are smaller or equal to x
+    ///
+    /// See [`Bitmap::rank`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_rank")]
+    #[must_use]
+    pub fn rank(&self, x: u32) -> u64 {
+        self.bitmap.rank(x)
+    }
+
+    /// Selects the element at index `position` in the bitmap
+    ///
+    /// See [`Bitmap::select`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_select")]
+    #[must_use]
+    pub fn select(&self, position: u32) -> Option<u32> {
+        self.bitmap.select(position)
+    }
+
+    /// Returns an iterator over each value stored in the bitmap
+    ///
+    /// See [`Bitmap::iter`]
+    #[inline]
+    #[must_use]
+    pub fn iter(&self) -> BitmapIterator<'_> {
+        self.bitmap.iter()
+    }
+
+    /// Returns a vector containing all of the integers stored in the bitmap
+    ///
+    /// See [`Bitmap::to_vec`]
+    #[inline]
+    #[doc(alias = "roaring_bitmap_to_uint32_array")]
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<u32> {
+        self.bitmap.to_vec()
+    }
+}
+
+impl From<Bitmap> for CowBitmap {
+    /// Converts a bitmap into a copy-on-write bitmap without copying any containers
+    #[doc(alias = "roaring_bitmap_set_copy_on_write")]
+    fn from(mut bitmap: Bitmap) -> Self {
+        unsafe { ffi::roaring_bitmap_set_copy_on_write(&mut bitmap.bitmap, true) };
+        Self {
+            bitmap,
+            family: Arc::new(Mutex::new(())),
+            not_sync: PhantomData,
+        }
+    }
+}
+
+impl Default for CowBitmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for CowBitmap {
+    /// Creates a copy of the bitmap which shares all containers with this bitmap
+    #[inline]
+    #[doc(alias = "roaring_bitmap_copy")]
+    fn clone(&self) -> Self {
+        debug_assert!(unsafe { ffi::roaring_bitmap_get_copy_on_write(&self.bitmap.bitmap) });
+        // Only increments reference counts of already shared containers, and wraps the
+        // containers of `self` (which can only be referenced by this thread) in new shared
+        // containers, so the lock is not needed
+        Self {
+            bitmap: self.bitmap.clone(),
+            family: Arc::clone(&self.family),
+            not_sync: PhantomData,
+        }
+    }
+}
+
+impl Drop for CowBitmap {
+    fn drop(&mut self) {
+        let _guard = lock(&self.family);
+        // Release all containers while holding the lock, the inner bitmap is dropped empty
+        self.bitmap.clear();
+    }
+}
+
+impl PartialEq for CowBitmap {
+    #[inline]
+    #[doc(alias = "roaring_bitmap_equals")]
+    fn eq(&self, other: &Self) -> bool {
+        self.bitmap == other.bitmap
+    }
+}
+
+impl PartialEq<Bitmap> for CowBitmap {
+    #[inline]
+    #[doc(alias = "roaring_bitmap_equals")]
+    fn eq(&self, other: &Bitmap) -> bool {
+        self.bitmap == *other
+    }
+}
+
+impl Eq for CowBitmap {}
+
+impl fmt::Debug for CowBitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cow{:?}", self.bitmap)
+    }
+}
@@ -1,9 +1,10 @@
 use super::serialization::{Deserializer, Serializer};
 use super::{Bitmap, Statistics};
 use crate::Bitset;
+use crate::DeserializeError;
 use core::ffi::c_void;
 use core::mem;
-use core::ops::{Bound, ControlFlow, RangeBounds};
+use core::ops::{Bound, ControlFlow, RangeBounds, RangeInclusive};
 use ffi::roaring_bitmap_t;
 
 #[cfg(feature = "alloc")]
@@ -349,6 +350,76 @@ impl Bitmap {
         unsafe { Bitmap::take_heap(ffi::roaring_bitmap_add_offset(&self.bitmap, offset)) }
     }
 
+    /// Shifts all values in the bitmap by `offset`, in place
+    ///
+    /// Any values which would be `< 0`, or `> u32::MAX` are dropped.
+    ///
+    /// When `offset` is a multiple of 2^16, containers are relabelled rather than copied.
+    /// Otherwise, the shifted bitmap is built as a copy with [`Self::add_offset`], which then
+    /// replaces `self`.
+    ///
+    /// # Examples
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::of(&[0, 1, 1000, u32::MAX]);
+    /// bitmap.add_offset_inplace(-1);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [0, 999, u32::MAX - 1]);
+    /// bitmap.add_offset_inplace(0x1_0000);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [0x1_0000, 0x1_03E7]);
+    /// bitmap.add_offset_inplace(-0x2_0000);
+    /// assert!(bitmap.is_empty());
+    /// ```
+    pub fn add_offset_inplace(&mut self, offset: i64) {
+        if offset % 0x1_0000 != 0 {
+            *self = self.add_offset(offset);
+            return;
+        }
+        let Some(kept) = offset_kept_range(offset) else {
+            self.clear();
+            return;
+        };
+        self.retain_range(kept);
+
+        let array = &mut self.bitmap.high_low_container;
+        if array.size == 0 {
+            return;
+        }
+        // This relabels the containers in the bitmap's internal array, which is not part of the
+        // API. Ensure this is still valid every time we update the version of croaring.
+        const _: () = assert!(ffi::ROARING_VERSION_MAJOR == 4);
+        let keys = unsafe { core::slice::from_raw_parts_mut(array.keys, array.size as usize) };
+        // All remaining values stay in range, so the keys do too, and keep their order
+        let key_offset = (offset >> 16) as i32;
+        for key in keys {
+            *key = (i32::from(*key) + key_offset) as u16;
+        }
+    }
+
+    /// Compute a new bitmap, which contains all values from this bitmap, but shifted by `offset`
+    ///
+    /// Returns the new bitmap, and the number of values which were dropped because they would be
+    /// `< 0`, or `> u32::MAX`.
+    ///
+    /// # Examples
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap = Bitmap::of(&[0, 1, 1000, u32::MAX]);
+    /// let (shifted, dropped) = bitmap.add_offset_checked(-1);
+    /// assert_eq!(shifted.iter().collect::<Vec<_>>(), [0, 999, u32::MAX - 1]);
+    /// assert_eq!(dropped, 1);
+    ///
+    /// let (shifted, dropped) = bitmap.add_offset_checked(i64::from(u32::MAX) + 1);
+    /// assert!(shifted.is_empty());
+    /// assert_eq!(dropped, 4);
+    /// ```
+    #[must_use]
+    pub fn add_offset_checked(&self, offset: i64) -> (Self, u64) {
+        let kept = offset_kept_range(offset).map_or(0, |kept| self.range_cardinality(kept));
+        (self.add_offset(offset), self.cardinality() - kept)
+    }
+
     /// Returns number of elements in range
     ///
     /// # Examples
@@ -363,6 +434,10 @@ impl Bitmap {
     /// assert_eq!(bitmap.range_cardinality((2..5)), 2);
     /// assert_eq!(bitmap.range_cardinality((..5)), 3);
     /// assert_eq!(bitmap.range_cardinality((1..=4)), 3);
+    ///
+    /// let bitmap = Bitmap::of(&[1, u32::MAX - 1, u32::MAX]);
+    /// assert_eq!(bitmap.range_cardinality(u32::MAX - 1..), 2);
+    /// assert_eq!(bitmap.range_cardinality(..), 3);
     /// ```
     #[inline]
     #[doc(alias = "roaring_bitmap_range_cardinality")]
@@ -642,6 +717,185 @@ impl Bitmap {
         unsafe { Self::take_heap(ffi::roaring_bitmap_xor_many(bms.len(), bms.as_mut_ptr())) }
     }
 
+    /// Computes the intersection of many bitmaps, container by container
+    ///
+    /// Only the containers whose key is present in every bitmap are visited. The containers for
+    /// each key are intersected from the bitmap with the smallest cardinality to the largest, and
+    /// the remaining bitmaps are skipped as soon as the intersection of a container is empty.
+    /// Unlike calling [`Self::and_inplace`] repeatedly, no intermediate bitmap is built.
+    ///
+    /// An empty slice produces an empty bitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap1 = Bitmap::of(&[15, 25, 35]);
+    /// let bitmap2 = Bitmap::of(&[25, 35, 45]);
+    /// let bitmap3 = Bitmap::of(&[5, 35]);
+    ///
+    /// let bitmap4 = Bitmap::fast_and(&[&bitmap1, &bitmap2, &bitmap3]);
+    ///
+    /// assert_eq!(bitmap4.cardinality(), 1);
+    /// assert!(bitmap4.contains(35));
+    ///
+    /// assert!(Bitmap::fast_and(&[]).is_empty());
+    /// ```
+    #[must_use]
+    #[cfg(feature = "alloc")]
+    pub fn fast_and(bitmaps: &[&Bitmap]) -> Self {
+        let Some(bitmaps) = Self::fast_and_order(bitmaps) else {
+            return Self::new();
+        };
+        if let [only] = bitmaps[..] {
+            return only.clone();
+        }
+        let mut result = Self::with_container_capacity(bitmaps[0].keys().len() as u32);
+        Self::fast_and_for_each(&bitmaps, |containers| {
+            let mut container = Self::fast_and_containers(containers);
+            result.append_containers(&mut container);
+        });
+        result
+    }
+
+    /// Computes the cardinality of the intersection between many bitmaps
+    ///
+    /// This is equivalent to `Bitmap::fast_and(bitmaps).cardinality()`, but the intersection is
+    /// never collected into a bitmap: the containers for each key are intersected as in
+    /// [`Self::fast_and`], except for the last one, which is only counted. When intersecting two
+    /// bitmaps, nothing is allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap1 = Bitmap::of(&[15, 25, 35]);
+    /// let bitmap2 = Bitmap::of(&[25, 35, 45]);
+    /// let bitmap3 = Bitmap::of(&[5, 25, 35]);
+    ///
+    /// assert_eq!(Bitmap::fast_and_cardinality(&[&bitmap1, &bitmap2, &bitmap3]), 2);
+    /// assert_eq!(Bitmap::fast_and_cardinality(&[&bitmap1, &Bitmap::new()]), 0);
+    /// ```
+    #[must_use]
+    #[cfg(feature = "alloc")]
+    pub fn fast_and_cardinality(bitmaps: &[&Bitmap]) -> u64 {
+        let Some(bitmaps) = Self::fast_and_order(bitmaps) else {
+            return 0;
+        };
+        if let [only] = bitmaps[..] {
+            return only.cardinality();
+        }
+        let mut cardinality = 0;
+        Self::fast_and_for_each(&bitmaps, |containers| {
+            cardinality += match containers {
+                [first, second] => first.and_cardinality(second),
+                [init @ .., last] => Self::fast_and_containers(init).and_cardinality(last),
+                _ => unreachable!(),
+            };
+        });
+        cardinality
+    }
+
+    /// Computes the values present in at least `k` of the given bitmaps
+    ///
+    /// The number of bitmaps containing each value is counted in bit-sliced form, so this takes
+    /// `O(n log n)` bitmap operations for `n` bitmaps, regardless of `k`.
+    ///
+    /// A `k` of 0 or 1 gives the union of the bitmaps, a `k` equal to the number of bitmaps gives
+    /// their intersection, and a larger `k` gives an empty bitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap1 = Bitmap::of(&[1, 2, 3, 4]);
+    /// let bitmap2 = Bitmap::of(&[2, 3, 4]);
+    /// let bitmap3 = Bitmap::of(&[3, 4]);
+    /// let bitmap4 = Bitmap::of(&[4, 5]);
+    /// let bitmaps = [&bitmap1, &bitmap2, &bitmap3, &bitmap4];
+    ///
+    /// assert_eq!(Bitmap::threshold(&bitmaps, 1), Bitmap::of(&[1, 2, 3, 4, 5]));
+    /// assert_eq!(Bitmap::threshold(&bitmaps, 2), Bitmap::of(&[2, 3, 4]));
+    /// assert_eq!(Bitmap::threshold(&bitmaps, 3), Bitmap::of(&[3, 4]));
+    /// assert_eq!(Bitmap::threshold(&bitmaps, 4), Bitmap::of(&[4]));
+    /// assert!(Bitmap::threshold(&bitmaps, 5).is_empty());
+    /// ```
+    #[must_use]
+    #[cfg(feature = "alloc")]
+    pub fn threshold(bitmaps: &[&Bitmap], k: usize) -> Self {
+        crate::threshold::threshold(bitmaps, k)
+    }
+
+    /// Calls `f` with the containers of every bitmap, for each key present in all bitmaps
+    ///
+    /// The keys of the first bitmap are walked, and each other bitmap is searched from the
+    /// position of the previous key. Each container is passed as a bitmap holding only that
+    /// container, in the order of `bitmaps`.
+    #[cfg(feature = "alloc")]
+    fn fast_and_for_each<F: FnMut(&[mem::ManuallyDrop<Bitmap>])>(bitmaps: &[&Bitmap], mut f: F) {
+        let Some((first, others)) = bitmaps.split_first() else {
+            return;
+        };
+        let mut positions = alloc::vec![0; others.len()];
+        let mut containers = Vec::with_capacity(bitmaps.len());
+        'keys: for (index, &key) in first.keys().iter().enumerate() {
+            containers.clear();
+            // SAFETY: the containers are only read by `f`, and are dropped before the bitmaps
+            containers.push(unsafe { first.container_view(index) });
+            for (bitmap, position) in others.iter().zip(&mut positions) {
+                let keys = bitmap.keys();
+                *position += keys[*position..].partition_point(|&k| k < key);
+                match keys.get(*position) {
+                    Some(&k) if k == key => {}
+                    Some(_) => continue 'keys,
+                    // No key of this bitmap is left to intersect with
+                    None => return,
+                }
+                containers.push(unsafe { bitmap.container_view(*position) });
+            }
+            f(&containers);
+        }
+    }
+
+    /// Intersects at least two single container bitmaps, stopping once the result is empty
+    #[cfg(feature = "alloc")]
+    fn fast_and_containers(containers: &[mem::ManuallyDrop<Bitmap>]) -> Bitmap {
+        let [first, second, rest @ ..] = containers else {
+            unreachable!()
+        };
+        let mut result = first.and(second);
+        for container in rest {
+            if result.is_empty() {
+                break;
+            }
+            result.and_inplace(container);
+        }
+        result
+    }
+
+    /// Sorts the bitmaps to intersect by cardinality
+    ///
+    /// Returns None if the intersection is trivially empty: there are no bitmaps, or the ranges
+    /// of values in the bitmaps do not overlap.
+    #[cfg(feature = "alloc")]
+    fn fast_and_order<'a>(bitmaps: &[&'a Bitmap]) -> Option<Vec<&'a Bitmap>> {
+        let mut start = 0;
+        let mut end = u32::MAX;
+        for bitmap in bitmaps {
+            start = start.max(bitmap.minimum()?);
+            end = end.min(bitmap.maximum()?);
+        }
+        if bitmaps.is_empty() || start > end {
+            return None;
+        }
+        let mut bitmaps = bitmaps.to_vec();
+        bitmaps.sort_by_cached_key(|bitmap| bitmap.cardinality());
+        Some(bitmaps)
+    }
+
     /// Computes the difference between two bitmaps and returns the result.
     ///
     /// # Examples
@@ -829,6 +1083,90 @@ impl Bitmap {
         buffer
     }
 
+    /// Returns a vector containing at most `limit` of the integers stored in the Bitmap, in sorted
+    /// order, starting with the value at position `offset`
+    ///
+    /// This is useful for paging through a bitmap by position. See also
+    /// [`read_range_into`][Self::read_range_into] to fill an existing buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap: Bitmap = (0..100).map(|x| x * 10).collect();
+    ///
+    /// assert_eq!(bitmap.to_vec_range(0, 3), [0, 10, 20]);
+    /// assert_eq!(bitmap.to_vec_range(98, 3), [980, 990]);
+    /// assert_eq!(bitmap.to_vec_range(100, 3), []);
+    /// ```
+    #[inline]
+    #[doc(alias = "roaring_bitmap_range_uint32_array")]
+    #[must_use]
+    #[cfg(feature = "alloc")]
+    pub fn to_vec_range(&self, offset: u32, limit: usize) -> Vec<u32> {
+        let len = self.range_len(offset, limit);
+
+        let mut buffer: Vec<u32> = Vec::with_capacity(len);
+        unsafe {
+            ffi::roaring_bitmap_range_uint32_array(
+                &self.bitmap,
+                offset as usize,
+                len,
+                buffer.as_mut_ptr(),
+            );
+            buffer.set_len(len);
+        }
+        buffer
+    }
+
+    /// Fills `dst` with the integers stored in the Bitmap, in sorted order, starting with the
+    /// value at position `offset`
+    ///
+    /// Returns the number of values written, which is less than `dst.len()` only if the end of
+    /// the bitmap was reached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap: Bitmap = (0..100).map(|x| x * 10).collect();
+    /// let mut page = [0; 4];
+    ///
+    /// assert_eq!(bitmap.read_range_into(10, &mut page), 4);
+    /// assert_eq!(page, [100, 110, 120, 130]);
+    ///
+    /// assert_eq!(bitmap.read_range_into(97, &mut page), 3);
+    /// assert_eq!(page[..3], [970, 980, 990]);
+    ///
+    /// assert_eq!(bitmap.read_range_into(1000, &mut page), 0);
+    /// ```
+    #[inline]
+    #[doc(alias = "roaring_bitmap_range_uint32_array")]
+    pub fn read_range_into(&self, offset: u32, dst: &mut [u32]) -> usize {
+        let len = self.range_len(offset, dst.len());
+        unsafe {
+            ffi::roaring_bitmap_range_uint32_array(
+                &self.bitmap,
+                offset as usize,
+                len,
+                dst.as_mut_ptr(),
+            );
+        }
+        len
+    }
+
+    /// The number of values which can be read starting at position `offset`, up to `limit`
+    #[inline]
+    fn range_len(&self, offset: u32, limit: usize) -> usize {
+        let remaining = self.cardinality().saturating_sub(u64::from(offset));
+        // CRoaring reads at most u32::MAX values in one call
+        limit
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX))
+            .min(u32::MAX as usize)
+    }
+
     /// Computes the serialized size in bytes of the Bitmap in format `S`.
     #[inline]
     #[must_use]
@@ -918,6 +1256,36 @@ impl Bitmap {
         S::try_serialize_into_aligned(self, dst)
     }
 
+    /// Serializes a bitmap to a writer in format `S`
+    ///
+    /// Returns the number of bytes written to the writer.
+    ///
+    /// The [`Portable`][crate::Portable] and [`Native`][crate::Native] formats are written
+    /// directly from the bitmap, without serializing the whole bitmap into memory first.
+    ///
+    /// Note that the [`Frozen`][crate::Frozen] format requires alignment to 32 bytes. This function
+    /// assumes the writer starts at an aligned position (and cannot check this).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, Portable};
+    ///
+    /// let bitmap: Bitmap = (1..5).collect();
+    ///
+    /// let mut data = Vec::new();
+    /// let written = bitmap.serialize_into_writer::<Portable, _>(&mut data).unwrap();
+    /// assert_eq!(written, data.len());
+    /// assert_eq!(data, bitmap.serialize::<Portable>());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn serialize_into_writer<S: Serializer, W: std::io::Write>(
+        &self,
+        writer: W,
+    ) -> std::io::Result<usize> {
+        S::serialize_into_writer(self, writer)
+    }
+
     /// Given a serialized bitmap as slice of bytes in format `S`, returns a `Bitmap` instance.
     /// See example of [`Self::serialize`] function.
     ///
@@ -947,6 +1315,69 @@ impl Bitmap {
         D::try_deserialize(buffer)
     }
 
+    /// Deserializes a bitmap in format `D` from the beginning of `buffer`, describing any failure
+    ///
+    /// Returns the bitmap, and the number of bytes it was deserialized from: `buffer` may continue
+    /// after the end of the bitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")]
+    /// # {
+    /// use croaring::{Bitmap, DeserializeError, Portable};
+    ///
+    /// let bitmap = Bitmap::of(&[1, 2, 3]);
+    /// let mut buffer = bitmap.serialize::<Portable>();
+    /// let len = buffer.len();
+    /// buffer.extend_from_slice(b"more data");
+    ///
+    /// let (deserialized, read) = Bitmap::try_deserialize_prefix::<Portable>(&buffer).unwrap();
+    /// assert_eq!(deserialized, bitmap);
+    /// assert_eq!(read, len);
+    ///
+    /// let err = Bitmap::try_deserialize_prefix::<Portable>(&buffer[..len - 1]).unwrap_err();
+    /// assert_eq!(err, DeserializeError::Truncated { needed: len, available: len - 1 });
+    /// # }
+    /// ```
+    pub fn try_deserialize_prefix<D: Deserializer>(
+        buffer: &[u8],
+    ) -> Result<(Self, usize), DeserializeError> {
+        D::try_deserialize_prefix(buffer)
+    }
+
+    /// Deserializes a bitmap in format `D` which fills the whole of `buffer`, describing any failure
+    ///
+    /// Unlike [`Self::try_deserialize_prefix`], it is an error for `buffer` to continue after
+    /// the end of the bitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")]
+    /// # {
+    /// use croaring::{Bitmap, DeserializeError, Native};
+    ///
+    /// let bitmap = Bitmap::of(&[1, 2, 3]);
+    /// let mut buffer = bitmap.serialize::<Native>();
+    /// assert_eq!(Bitmap::try_deserialize_exact::<Native>(&buffer).unwrap(), bitmap);
+    ///
+    /// buffer.push(0);
+    /// let err = Bitmap::try_deserialize_exact::<Native>(&buffer).unwrap_err();
+    /// assert_eq!(err, DeserializeError::TrailingBytes { extra: 1 });
+    ///
+    /// let err = Bitmap::try_deserialize_exact::<Native>(&[3]).unwrap_err();
+    /// assert_eq!(err, DeserializeError::UnknownCookie { cookie: 3 });
+    /// # }
+    /// ```
+    pub fn try_deserialize_exact<D: Deserializer>(buffer: &[u8]) -> Result<Self, DeserializeError> {
+        let (bitmap, len) = D::try_deserialize_prefix(buffer)?;
+        match buffer.len() - len {
+            0 => Ok(bitmap),
+            extra => Err(DeserializeError::TrailingBytes { extra }),
+        }
+    }
+
     /// Given a serialized bitmap as slice of bytes in format `S `, returns a bitmap instance.
     /// See example of [`Self::serialize`] function.
     ///
@@ -957,6 +1388,42 @@ impl Bitmap {
         Self::try_deserialize::<D>(buffer).unwrap_or_else(Bitmap::new)
     }
 
+    /// Reads a serialized bitmap in format `D` from a reader
+    ///
+    /// Only the bytes of the serialized bitmap are read, as determined by its header, so several
+    /// bitmaps can be read one after another from the same reader.
+    ///
+    /// Returns an error if reading fails, if the reader ends before the end of the bitmap (with
+    /// [`UnexpectedEof`][std::io::ErrorKind::UnexpectedEof]), or if the data is not a valid
+    /// bitmap (with [`InvalidData`][std::io::ErrorKind::InvalidData]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, Native, Portable};
+    /// use std::io::Cursor;
+    ///
+    /// let bitmap1 = Bitmap::of(&[1, 2, 3]);
+    /// let bitmap2 = Bitmap::from_range(0..100_000);
+    ///
+    /// let mut data = Vec::new();
+    /// bitmap1.serialize_into_writer::<Portable, _>(&mut data).unwrap();
+    /// bitmap2.serialize_into_writer::<Native, _>(&mut data).unwrap();
+    ///
+    /// let mut reader = Cursor::new(data);
+    /// assert_eq!(Bitmap::deserialize_from_reader::<Portable, _>(&mut reader).unwrap(), bitmap1);
+    /// assert_eq!(Bitmap::deserialize_from_reader::<Native, _>(&mut reader).unwrap(), bitmap2);
+    ///
+    /// let err = Bitmap::deserialize_from_reader::<Portable, _>(&mut reader).unwrap_err();
+    /// assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn deserialize_from_reader<D: Deserializer, R: std::io::Read>(
+        reader: R,
+    ) -> std::io::Result<Self> {
+        D::deserialize_from_reader(reader)
+    }
+
     /// Creates a new bitmap from a slice of u32 integers
     ///
     /// # Examples
@@ -1014,6 +1481,28 @@ impl Bitmap {
         result
     }
 
+    /// Create a new bitmap with all values in each of `ranges`
+    ///
+    /// This is the inverse of [`Bitmap::ranges`]. The ranges may overlap, and need not be sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap = Bitmap::from_ranges([10..=12, 1..=2, 11..=13, 20..=19]);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [1, 2, 10, 11, 12, 13]);
+    /// assert_eq!(bitmap.ranges().collect::<Vec<_>>(), [1..=2, 10..=13]);
+    /// ```
+    #[must_use]
+    pub fn from_ranges<I: IntoIterator<Item = RangeInclusive<u32>>>(ranges: I) -> Self {
+        let mut result = Self::new();
+        for range in ranges {
+            result.add_range(range);
+        }
+        result
+    }
+
     /// Create a new bitmap with all values in `range` which are a multiple of `step` away from the lower bound
     ///
     /// If `step` is zero or there are no values which are a multiple of `step` away from the lower bound
@@ -1446,6 +1935,59 @@ impl Bitmap {
         unsafe { ffi::roaring_bitmap_rank(&self.bitmap, x) }
     }
 
+    /// Computes the [rank][Self::rank] of each of the given values
+    ///
+    /// Writes the number of elements smaller or equal to `values[i]` into `ranks[i]`.
+    ///
+    /// This is equivalent to calling [`rank`][Self::rank] for each value, but is much faster
+    /// when `values` is sorted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ranks` is shorter than `values`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap: Bitmap = (5..10).collect();
+    ///
+    /// let mut ranks = [0; 4];
+    /// bitmap.rank_many(&[4, 5, 8, 100], &mut ranks);
+    /// assert_eq!(ranks, [0, 1, 4, 5]);
+    ///
+    /// // Unsorted values are allowed
+    /// bitmap.rank_many(&[100, 8, 4, 5], &mut ranks);
+    /// assert_eq!(ranks, [5, 4, 0, 1]);
+    /// ```
+    #[doc(alias = "roaring_bitmap_rank_many")]
+    pub fn rank_many(&self, values: &[u32], ranks: &mut [u64]) {
+        let ranks = &mut ranks[..values.len()];
+        if !values.windows(2).all(|w| w[0] <= w[1]) {
+            for (&value, rank) in values.iter().zip(ranks) {
+                *rank = self.rank(value);
+            }
+            return;
+        }
+        // CRoaring leaves the ranks of some values past the last element unwritten, so fill
+        // with a sentinel (no rank can reach u64::MAX), and fix up any which were skipped.
+        ranks.fill(u64::MAX);
+        unsafe {
+            ffi::roaring_bitmap_rank_many(
+                &self.bitmap,
+                values.as_ptr(),
+                values.as_ptr_range().end,
+                ranks.as_mut_ptr(),
+            );
+        }
+        for (&value, rank) in values.iter().zip(ranks) {
+            if *rank == u64::MAX {
+                *rank = self.rank(value);
+            }
+        }
+    }
+
     /// Returns the index of x in the given roaring bitmap.
     ///
     /// If the roaring bitmap doesn't contain x, this function will return None.
@@ -1530,6 +2072,60 @@ impl Bitmap {
         }
     }
 
+    /// Selects the elements at each of the given positions
+    ///
+    /// Writes the element at `positions[i]` into `dst[i]`, stopping at the first position which
+    /// is not less than the cardinality of the bitmap. Returns the number of elements written.
+    ///
+    /// This is equivalent to calling [`select`][Self::select] for each position, but when
+    /// `positions` is sorted and close together, the cursor skips over the elements between
+    /// them rather than searching the bitmap from the start for each position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is shorter than `positions`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap = Bitmap::of(&[1, 10, 100, 1000, 10000]);
+    ///
+    /// let mut dst = [0; 3];
+    /// assert_eq!(bitmap.select_many(&[0, 2, 4], &mut dst), 3);
+    /// assert_eq!(dst, [1, 100, 10000]);
+    ///
+    /// // Selection stops at the first out-of-range position
+    /// let mut dst = [0; 3];
+    /// assert_eq!(bitmap.select_many(&[3, 5, 1], &mut dst), 1);
+    /// assert_eq!(dst[0], 1000);
+    /// ```
+    pub fn select_many(&self, positions: &[u32], dst: &mut [u32]) -> usize {
+        // Past this distance, a fresh select is cheaper than skipping through the gap
+        const MAX_SKIP: u32 = 4096;
+
+        let dst = &mut dst[..positions.len()];
+        let mut cursor = self.cursor();
+        let mut cursor_position = 0;
+        for (i, (&position, out)) in positions.iter().zip(dst).enumerate() {
+            if position < cursor_position || position - cursor_position > MAX_SKIP {
+                let Some(value) = self.select(position) else {
+                    return i;
+                };
+                cursor.reset_at_or_after(value);
+            } else {
+                cursor.skip(position - cursor_position);
+            }
+            cursor_position = position;
+            match cursor.current() {
+                Some(value) => *out = value,
+                None => return i,
+            }
+        }
+        positions.len()
+    }
+
     /// Returns statistics about the composition of a roaring bitmap.
     ///
     /// # Examples
@@ -1674,7 +2270,21 @@ impl Bitmap {
     }
 }
 
-fn range_to_inclusive<R: RangeBounds<u32>>(range: R) -> (u32, u32) {
+/// The values which stay within `u32` when shifted by `offset`, if any
+fn offset_kept_range(offset: i64) -> Option<RangeInclusive<u32>> {
+    let max = i64::from(u32::MAX);
+    let (start, end) = if offset >= 0 {
+        (0, max - offset)
+    } else {
+        (offset.checked_neg()?, max)
+    };
+    if start > end {
+        return None;
+    }
+    Some(u32::try_from(start).ok()?..=u32::try_from(end).ok()?)
+}
+
+pub(super) fn range_to_inclusive<R: RangeBounds<u32>>(range: R) -> (u32, u32) {
     let start = match range.start_bound() {
         Bound::Included(&i) => i,
         Bound::Excluded(&i) => match i.checked_add(1) {
@@ -1703,7 +2313,7 @@ fn range_to_exclusive<R: RangeBounds<u32>>(range: R) -> (u64, u64) {
     let end = match range.end_bound() {
         Bound::Included(&i) => u64::from(i) + 1,
         Bound::Excluded(&i) => u64::from(i),
-        Bound::Unbounded => u64::MAX,
+        Bound::Unbounded => u64::from(u32::MAX) + 1,
     };
     (start, end)
 }
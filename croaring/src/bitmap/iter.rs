@@ -1,6 +1,9 @@
+use super::imp::range_to_inclusive;
 use super::Bitmap;
+use core::iter::FusedIterator;
 use core::marker::PhantomData;
 use core::mem::MaybeUninit;
+use core::ops::{RangeBounds, RangeInclusive};
 
 /// A cursor over the values of a bitmap
 ///
@@ -39,6 +42,13 @@ impl<'a> BitmapCursor<'a> {
         Self::from_raw(unsafe { raw.assume_init() })
     }
 
+    /// The bitmap this cursor was created from
+    #[inline]
+    fn bitmap(&self) -> &'a Bitmap {
+        // SAFETY: `Bitmap` is a transparent wrapper, and the cursor borrows the bitmap for 'a
+        unsafe { &*self.raw.parent.cast::<Bitmap>() }
+    }
+
     /// Returns true if the cursor is pointing at a value in the bitmap.
     ///
     /// If this returns false, then the cursor is pointing at a "ghost" position,
@@ -384,28 +394,111 @@ impl<'a> BitmapCursor<'a> {
 }
 
 /// Iterator over the values of a bitmap
+///
+/// Values can be taken from both ends, and the number of values left is known exactly. It is
+/// counted when the iterator is created, and kept up to date as values are taken. On 64-bit
+/// targets, where any count fits in a `usize`, this implements [`ExactSizeIterator`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap;
+///
+/// let bitmap = Bitmap::of(&[1, 2, 3, 4, 5]);
+/// let mut iter = bitmap.iter();
+/// assert_eq!(iter.size_hint(), (5, Some(5)));
+/// assert_eq!(iter.next(), Some(1));
+/// assert_eq!(iter.next_back(), Some(5));
+/// assert_eq!(iter.size_hint(), (3, Some(3)));
+/// assert_eq!(iter.rev().collect::<Vec<_>>(), [4, 3, 2]);
+///
+/// // The last 2 values
+/// let last: Vec<u32> = bitmap.iter().rev().take(2).collect();
+/// assert_eq!(last, [5, 4]);
+/// ```
 #[derive(Clone)]
 pub struct BitmapIterator<'a> {
     cursor: BitmapCursor<'a>,
+    /// Points at the next value to be returned from the back, only created once values are
+    /// taken from the back
+    back: Option<BitmapCursor<'a>>,
+    /// Number of values between the front and back cursors, inclusive
+    len: u64,
 }
 
 impl<'a> BitmapIterator<'a> {
     fn new(bitmap: &'a Bitmap) -> Self {
         Self {
             cursor: BitmapCursor::at_first(bitmap),
+            back: None,
+            len: bitmap.cardinality(),
+        }
+    }
+
+    fn with_range<R: RangeBounds<u32>>(bitmap: &'a Bitmap, range: R) -> Self {
+        let (start, end) = range_to_inclusive(range);
+        let mut cursor = BitmapCursor::at_first(bitmap);
+        cursor.reset_at_or_after(start);
+        let back_cursor = match end.checked_add(1) {
+            Some(after_end) => {
+                let mut back_cursor = cursor.clone();
+                back_cursor.reset_at_or_after(after_end);
+                back_cursor.move_prev();
+                back_cursor
+            }
+            None => BitmapCursor::at_last(bitmap),
+        };
+        let len = if start <= end {
+            bitmap.range_cardinality(start..=end)
+        } else {
+            0
+        };
+        Self {
+            cursor,
+            back: Some(back_cursor),
+            len,
+        }
+    }
+
+    /// Creates an iterator starting at the current position of `cursor`
+    fn from_cursor(cursor: BitmapCursor<'a>) -> Self {
+        let len = match cursor.current() {
+            Some(value) => cursor.bitmap().range_cardinality(value..),
+            None => 0,
+        };
+        Self {
+            cursor,
+            back: None,
+            len,
         }
     }
 
     #[inline]
     fn current_value(&self) -> Option<u32> {
+        if self.len == 0 {
+            return None;
+        }
         self.cursor.current()
     }
 
     #[inline]
     fn advance(&mut self) {
+        self.len -= 1;
         self.cursor.move_next();
     }
 
+    /// Number of values left to be returned
+    #[inline]
+    #[cfg(feature = "alloc")]
+    pub(crate) fn remaining(&self) -> u64 {
+        self.len
+    }
+
+    fn back_cursor(&mut self) -> &mut BitmapCursor<'a> {
+        let bitmap = self.cursor.bitmap();
+        self.back.get_or_insert_with(|| bitmap.cursor_to_last())
+    }
+
     /// Attempt to read many values from the iterator into `dst`
     ///
     /// Returns the number of items read from the iterator, may be `< dst.len()` iff
@@ -460,7 +553,10 @@ impl<'a> BitmapIterator<'a> {
     #[doc(alias = "roaring_uint32_iterator_read")]
     #[must_use]
     pub fn next_many(&mut self, dst: &mut [u32]) -> usize {
-        self.cursor.read_many(dst)
+        let len = usize::try_from(self.len).map_or(dst.len(), |len| len.min(dst.len()));
+        let n = self.cursor.read_many(&mut dst[..len]);
+        self.len -= n as u64;
+        n
     }
 
     /// Reset the iterator to the first value `>= val`
@@ -490,6 +586,15 @@ impl<'a> BitmapIterator<'a> {
     #[doc(alias = "roaring_uint32_iterator_move_equalorlarger")]
     pub fn reset_at_or_after(&mut self, val: u32) {
         self.cursor.reset_at_or_after(val);
+        let bitmap = self.cursor.bitmap();
+        self.len = match (self.cursor.current(), &self.back) {
+            (Some(front), None) => bitmap.range_cardinality(front..),
+            (Some(front), Some(back)) => match back.current() {
+                Some(last) if front <= last => bitmap.range_cardinality(front..=last),
+                _ => 0,
+            },
+            (None, _) => 0,
+        };
     }
 
     /// Peek at the next value to be returned by the iterator (if any), without consuming it
@@ -506,7 +611,7 @@ impl<'a> BitmapIterator<'a> {
     #[inline]
     #[must_use]
     pub fn peek(&self) -> Option<u32> {
-        self.cursor.current()
+        self.current_value()
     }
 }
 
@@ -525,6 +630,11 @@ impl<'a> Iterator for BitmapIterator<'a> {
     }
 
     fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n as u64 >= self.len {
+            self.len = 0;
+            return None;
+        }
+        self.len -= n as u64;
         if n > 0 {
             // `next` yeilds the current value from the cursor, so if we skip `n` values,
             // the next call to `next` will return the `n`th value.
@@ -534,8 +644,50 @@ impl<'a> Iterator for BitmapIterator<'a> {
         }
         self.next()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.len) {
+            Ok(len) => (len, Some(len)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+
+    fn count(self) -> usize {
+        usize::try_from(self.len).expect("count overflowed usize")
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
 }
 
+impl DoubleEndedIterator for BitmapIterator<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let back = self.back_cursor();
+        let value = back.current();
+        back.move_prev();
+        value
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        if n as u64 >= self.len {
+            self.len = 0;
+            return None;
+        }
+        self.len -= n as u64;
+        // `n < len <= 2^32`, so this can't truncate
+        self.back_cursor().skip_backward(n as u32);
+        self.next_back()
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+impl ExactSizeIterator for BitmapIterator<'_> {}
+
 impl Bitmap {
     /// Returns an iterator over each value stored in the bitmap.
     /// Returned values are ordered in ascending order.
@@ -563,6 +715,36 @@ impl Bitmap {
         BitmapIterator::new(self)
     }
 
+    /// Returns an iterator over the values stored in the bitmap which are in `range`
+    ///
+    /// The iterator supports everything [`Bitmap::iter`] does, including
+    /// [`next_many`](BitmapIterator::next_many) and reverse iteration, without ever returning a
+    /// value outside of `range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmap = Bitmap::of(&[1, 5, 10, 15, 20, u32::MAX]);
+    ///
+    /// assert_eq!(bitmap.iter_range(5..20).collect::<Vec<_>>(), [5, 10, 15]);
+    /// assert_eq!(bitmap.iter_range(5..=20).rev().collect::<Vec<_>>(), [20, 15, 10, 5]);
+    /// assert_eq!(bitmap.iter_range(11..).collect::<Vec<_>>(), [15, 20, u32::MAX]);
+    /// assert_eq!(bitmap.iter_range(..).count(), 6);
+    /// assert_eq!(bitmap.iter_range(2..5).next(), None);
+    ///
+    /// let mut buf = [0; 10];
+    /// let mut iter = bitmap.iter_range(2..=15);
+    /// assert_eq!(iter.next_many(&mut buf), 3);
+    /// assert_eq!(buf[..3], [5, 10, 15]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn iter_range<R: RangeBounds<u32>>(&self, range: R) -> BitmapIterator<'_> {
+        BitmapIterator::with_range(self, range)
+    }
+
     /// Returns a cursor pointing at the first value in the bitmap.
     ///
     /// See [`BitmapCursor`] for more details.
@@ -580,8 +762,104 @@ impl Bitmap {
     pub fn cursor_to_last(&self) -> BitmapCursor<'_> {
         BitmapCursor::at_last(self)
     }
+
+    /// Returns an iterator over the maximal runs of consecutive values in the bitmap
+    ///
+    /// Each run is returned as an inclusive range, in ascending order. Long runs are found without
+    /// visiting every value in them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::of(&[1, 2, 3, 7, u32::MAX]);
+    /// bitmap.add_range(100..=200_000);
+    ///
+    /// let ranges: Vec<_> = bitmap.ranges().collect();
+    /// assert_eq!(ranges, [1..=3, 7..=7, 100..=200_000, u32::MAX..=u32::MAX]);
+    ///
+    /// assert_eq!(Bitmap::from_ranges(ranges), bitmap);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn ranges(&self) -> BitmapRanges<'_> {
+        BitmapRanges::new(self)
+    }
 }
 
+/// Iterator over the maximal runs of consecutive values in a bitmap
+///
+/// Runs are returned in ascending order. See [`Bitmap::ranges`].
+#[derive(Debug, Clone)]
+pub struct BitmapRanges<'a> {
+    bitmap: &'a Bitmap,
+    cursor: BitmapCursor<'a>,
+}
+
+impl<'a> BitmapRanges<'a> {
+    /// Past this many values, the end of a run is found by searching, rather than stepping
+    const SCAN_LIMIT: usize = 16;
+
+    fn new(bitmap: &'a Bitmap) -> Self {
+        Self {
+            bitmap,
+            cursor: bitmap.cursor(),
+        }
+    }
+
+    /// Returns the last value of the run containing `end`
+    ///
+    /// Uses an exponential search, so long runs take a logarithmic number of steps
+    fn run_end(&self, mut end: u32) -> u32 {
+        let is_run = |end: u32, len: u32| match end.checked_add(len) {
+            Some(last) => self.bitmap.range_cardinality(end + 1..=last) == u64::from(len),
+            None => false,
+        };
+        let mut step = 1;
+        while is_run(end, step) {
+            end += step;
+            match step.checked_mul(2) {
+                Some(next) => step = next,
+                None => break,
+            }
+        }
+        while step > 1 {
+            step /= 2;
+            if is_run(end, step) {
+                end += step;
+            }
+        }
+        end
+    }
+}
+
+impl Iterator for BitmapRanges<'_> {
+    type Item = RangeInclusive<u32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.cursor.current()?;
+        let mut end = start;
+        for _ in 0..Self::SCAN_LIMIT {
+            match self.cursor.next() {
+                Some(value) if value == end + 1 => end = value,
+                _ => return Some(start..=end),
+            }
+        }
+        end = self.run_end(end);
+        match end.checked_add(1) {
+            Some(next) => self.cursor.reset_at_or_after(next),
+            None => {
+                self.cursor = self.bitmap.cursor_to_last();
+                self.cursor.move_next();
+            }
+        }
+        Some(start..=end)
+    }
+}
+
+impl FusedIterator for BitmapRanges<'_> {}
+
 /// Converts this iterator into a cursor
 ///
 /// The cursor's current value will be the the item which would have been returned by the next call to `next()`
@@ -624,7 +902,7 @@ impl<'a> From<BitmapIterator<'a>> for BitmapCursor<'a> {
 /// ```
 impl<'a> From<BitmapCursor<'a>> for BitmapIterator<'a> {
     fn from(cursor: BitmapCursor<'a>) -> Self {
-        BitmapIterator { cursor }
+        BitmapIterator::from_cursor(cursor)
     }
 }
 
@@ -652,10 +930,7 @@ impl FromIterator<u32> for Bitmap {
 
 impl Extend<u32> for Bitmap {
     fn extend<T: IntoIterator<Item = u32>>(&mut self, iter: T) {
-        let mut ctx = MaybeUninit::<ffi::roaring_bulk_context_t>::zeroed();
-        iter.into_iter().for_each(|item| unsafe {
-            ffi::roaring_bitmap_add_bulk(&mut self.bitmap, ctx.as_mut_ptr(), item);
-        });
+        self.bulk_context_mut().extend(iter);
     }
 }
 
@@ -664,6 +939,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::iter_nth_zero)]
     fn iter_nth() {
         let bitmap = Bitmap::of(&[1, 2, 3, 4, 5]);
         let mut iter = bitmap.iter();
@@ -122,13 +122,37 @@ pub struct Statistics {
     //       CRoaring 4.0.0
 }
 
+mod bulk;
+mod containers;
+#[cfg(feature = "std")]
+mod cow;
 mod imp;
 mod iter;
 mod lazy;
+#[cfg(feature = "mmap")]
+mod mmap;
 mod ops;
+#[cfg(feature = "alloc")]
+mod owned_view;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "serde")]
+mod serde;
 mod serialization;
+mod split;
 mod view;
 
-pub use self::iter::{BitmapCursor, BitmapIterator};
+pub use self::bulk::BulkContext;
+pub use self::containers::{ContainerInfo, ContainerType, Containers};
+#[cfg(feature = "std")]
+pub use self::cow::CowBitmap;
+pub use self::iter::{BitmapCursor, BitmapIterator, BitmapRanges};
 pub use self::lazy::LazyBitmap;
+#[cfg(feature = "mmap")]
+pub use self::mmap::MmapBitmapView;
+#[cfg(feature = "alloc")]
+pub use self::owned_view::OwnedBitmapView;
+#[cfg(feature = "rayon")]
+pub use self::par::BitmapParIter;
+pub(crate) use self::serialization::parse_portable;
 pub use self::serialization::{Deserializer, Serializer};
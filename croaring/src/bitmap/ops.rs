@@ -1,6 +1,11 @@
 use crate::BitmapView;
+use core::cmp::Ordering;
 use core::fmt;
-use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+use core::hash::{Hash, Hasher};
+use core::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Shl, ShlAssign, Shr, ShrAssign,
+    Sub, SubAssign,
+};
 
 use super::Bitmap;
 
@@ -102,6 +107,67 @@ impl PartialEq<Bitmap> for BitmapView<'_> {
 
 impl Eq for Bitmap {}
 
+impl Hash for Bitmap {
+    /// Hashes the values in the bitmap
+    ///
+    /// The hash does not depend on the internal representation of the bitmap (e.g. whether
+    /// [`Bitmap::run_optimize`] has been called), and a [`BitmapView`] hashes the same as an
+    /// equal `Bitmap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    /// use std::hash::BuildHasher;
+    ///
+    /// let hasher = std::collections::hash_map::RandomState::new();
+    /// let bitmap1 = Bitmap::from_range(0..10_000);
+    /// let mut bitmap2 = bitmap1.clone();
+    /// bitmap2.run_optimize();
+    ///
+    /// assert_eq!(hasher.hash_one(&bitmap1), hasher.hash_one(&bitmap2));
+    /// ```
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.cardinality().hash(state);
+        let mut iter = self.iter();
+        let mut buffer = [0; 256];
+        loop {
+            let count = iter.next_many(&mut buffer);
+            if count == 0 {
+                break;
+            }
+            u32::hash_slice(&buffer[..count], state);
+        }
+    }
+}
+
+impl PartialOrd for Bitmap {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Bitmap {
+    /// Compares bitmaps lexicographically by their values in sorted order
+    ///
+    /// This matches the ordering of a `BTreeSet` of the same values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// assert!(Bitmap::of(&[1, 2]) < Bitmap::of(&[1, 3]));
+    /// assert!(Bitmap::of(&[1, 2]) < Bitmap::of(&[1, 2, 3]));
+    /// assert!(Bitmap::of(&[2]) > Bitmap::of(&[1, 3]));
+    /// assert!(Bitmap::new() < Bitmap::of(&[0]));
+    /// ```
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
 impl Clone for Bitmap {
     /// Create a copy of a Bitmap
     /// # Examples
@@ -530,3 +596,107 @@ impl_binop_assign! {
         fn sub_assign as andnot_inplace
     }
 }
+
+/// Shifts all values up by `rhs`, dropping any which would be `> u32::MAX`
+///
+/// See [`Bitmap::add_offset`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap;
+///
+/// let bitmap = Bitmap::of(&[0, 1, u32::MAX]);
+/// assert_eq!((&bitmap << 2).iter().collect::<Vec<_>>(), [2, 3]);
+/// assert_eq!((bitmap << 0x1_0000).iter().collect::<Vec<_>>(), [0x1_0000, 0x1_0001]);
+/// ```
+impl Shl<u32> for &Bitmap {
+    type Output = Bitmap;
+
+    #[inline]
+    fn shl(self, rhs: u32) -> Bitmap {
+        self.add_offset(i64::from(rhs))
+    }
+}
+
+impl Shl<u32> for Bitmap {
+    type Output = Bitmap;
+
+    #[inline]
+    fn shl(mut self, rhs: u32) -> Bitmap {
+        self <<= rhs;
+        self
+    }
+}
+
+/// Shifts all values down by `rhs`, dropping any which would be `< 0`
+///
+/// See [`Bitmap::add_offset`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap;
+///
+/// let bitmap = Bitmap::of(&[0, 1, u32::MAX]);
+/// assert_eq!((&bitmap >> 1).iter().collect::<Vec<_>>(), [0, u32::MAX - 1]);
+/// assert_eq!((bitmap >> 0x1_0000).iter().collect::<Vec<_>>(), [0xFFFF_FFFF - 0x1_0000]);
+/// ```
+impl Shr<u32> for &Bitmap {
+    type Output = Bitmap;
+
+    #[inline]
+    fn shr(self, rhs: u32) -> Bitmap {
+        self.add_offset(-i64::from(rhs))
+    }
+}
+
+impl Shr<u32> for Bitmap {
+    type Output = Bitmap;
+
+    #[inline]
+    fn shr(mut self, rhs: u32) -> Bitmap {
+        self >>= rhs;
+        self
+    }
+}
+
+/// Shifts all values up by `rhs` in place, dropping any which would be `> u32::MAX`
+///
+/// See [`Bitmap::add_offset_inplace`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap;
+///
+/// let mut bitmap = Bitmap::of(&[0, 1, u32::MAX]);
+/// bitmap <<= 2;
+/// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [2, 3]);
+/// ```
+impl ShlAssign<u32> for Bitmap {
+    #[inline]
+    fn shl_assign(&mut self, rhs: u32) {
+        self.add_offset_inplace(i64::from(rhs));
+    }
+}
+
+/// Shifts all values down by `rhs` in place, dropping any which would be `< 0`
+///
+/// See [`Bitmap::add_offset_inplace`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap;
+///
+/// let mut bitmap = Bitmap::of(&[0, 1, u32::MAX]);
+/// bitmap >>= 1;
+/// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [0, u32::MAX - 1]);
+/// ```
+impl ShrAssign<u32> for Bitmap {
+    #[inline]
+    fn shr_assign(&mut self, rhs: u32) {
+        self.add_offset_inplace(-i64::from(rhs));
+    }
+}
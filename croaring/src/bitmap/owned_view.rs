@@ -0,0 +1,236 @@
+use super::serialization::ViewDeserializer;
+use super::{Bitmap, BitmapView};
+use crate::owned_buffer::{OwnedBuffer, SharedBuffer};
+use crate::DeserializeError;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+/// A frozen view of a bitmap, which owns the buffer backing it
+///
+/// Unlike a [`BitmapView`], which borrows its data, an `OwnedBitmapView` keeps its buffer alive
+/// itself, so it can be stored in long-lived structs, or returned from functions which read the
+/// buffer. Any buffer which can be viewed as bytes can be used, such as a `Vec<u8>`,
+/// `Box<[u8]>`, or a shared `Arc<[u8]>`.
+///
+/// All read-only methods for [`Bitmap`] are also usable on an `OwnedBitmapView`.
+///
+/// An `OwnedBitmapView` is [`Send`] and [`Sync`] if its buffer is. Views of a
+/// [`SharedBuffer`](crate::SharedBuffer), such as an `Arc<[u8]>`, are [`Clone`], sharing their
+/// data; views of other buffers can be cloned with [`try_clone`](Self::try_clone), which checks
+/// the data of the cloned buffer again. An `Arc<Vec<u8>>` is not `AsRef<[u8]>`, but it, or a
+/// shared buffer from another crate such as `bytes::Bytes`, can be used through a wrapper type
+/// implementing `SharedBuffer`.
+///
+/// # Examples
+///
+/// ```
+/// use croaring::{Bitmap, OwnedBitmapView, Portable};
+/// use std::sync::Arc;
+///
+/// struct Index {
+///     ids: OwnedBitmapView<Arc<[u8]>>,
+/// }
+///
+/// let bitmap = Bitmap::of(&[1, 2, 3]);
+/// let data: Arc<[u8]> = bitmap.serialize::<Portable>().into();
+/// let index = Index {
+///     ids: OwnedBitmapView::try_deserialize::<Portable>(data).unwrap(),
+/// };
+///
+/// let shared = index.ids.clone();
+/// std::thread::spawn(move || assert!(shared.contains(2)))
+///     .join()
+///     .unwrap();
+/// assert_eq!(index.ids, bitmap);
+/// ```
+pub struct OwnedBitmapView<B> {
+    // Declared before the buffer, so it is dropped before the data it borrows
+    view: BitmapView<'static>,
+    // Recreate the view for a clone of the buffer
+    deserialize: unsafe fn(&[u8]) -> BitmapView<'_>,
+    try_deserialize: fn(&[u8]) -> Result<BitmapView<'_>, DeserializeError>,
+    buffer: OwnedBuffer<B>,
+}
+
+impl<B: AsRef<[u8]>> OwnedBitmapView<B> {
+    /// Create a view of a bitmap serialized in `buffer`, checking that it is valid
+    ///
+    /// See [`BitmapView::try_deserialize`] for the checks performed. `buffer` is dropped if the
+    /// data is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, DeserializeError, OwnedBitmapView, Portable};
+    ///
+    /// let bitmap = Bitmap::of(&[1, 2, 3]);
+    /// let data: Box<[u8]> = bitmap.serialize::<Portable>().into();
+    /// let view = OwnedBitmapView::try_deserialize::<Portable>(data).unwrap();
+    /// assert_eq!(view, bitmap);
+    ///
+    /// let err = OwnedBitmapView::try_deserialize::<Portable>(vec![1, 2, 3]).unwrap_err();
+    /// assert!(matches!(err, DeserializeError::Truncated { .. }));
+    /// ```
+    pub fn try_deserialize<S: ViewDeserializer>(buffer: B) -> Result<Self, DeserializeError> {
+        let buffer = OwnedBuffer::new(buffer);
+        // SAFETY: the view is dropped before the buffer
+        let view = BitmapView::try_deserialize::<S>(unsafe { buffer.bytes_unbounded() })?;
+        Ok(Self {
+            view,
+            deserialize: S::deserialize_view,
+            try_deserialize: S::try_deserialize_view,
+            buffer,
+        })
+    }
+
+    /// Create a view of a bitmap serialized in `buffer` without checking it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, OwnedBitmapView, Portable};
+    ///
+    /// let bitmap = Bitmap::of(&[1, 2, 3]);
+    /// let data = bitmap.serialize::<Portable>();
+    /// let view = unsafe { OwnedBitmapView::deserialize::<Portable>(data) };
+    /// assert_eq!(view, bitmap);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The buffer must be the result of serializing a bitmap with the same serialization format,
+    /// as for [`BitmapView::deserialize`]
+    #[must_use]
+    pub unsafe fn deserialize<S: ViewDeserializer>(buffer: B) -> Self {
+        let buffer = OwnedBuffer::new(buffer);
+        Self {
+            view: BitmapView::deserialize::<S>(buffer.bytes_unbounded()),
+            deserialize: S::deserialize_view,
+            try_deserialize: S::try_deserialize_view,
+            buffer,
+        }
+    }
+
+    /// Clone the buffer, and view the bitmap in the clone, checking that it is valid
+    ///
+    /// The data of the cloned buffer is checked as by [`Self::try_deserialize`], so this fails
+    /// if the clone does not hold the same bitmap, or for formats which require alignment
+    /// ([`Frozen`](crate::Frozen)), if its data is not aligned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, OwnedBitmapView, Portable};
+    ///
+    /// let bitmap = Bitmap::of(&[1, 2, 3]);
+    /// let view = OwnedBitmapView::try_deserialize::<Portable>(bitmap.serialize::<Portable>())
+    ///     .unwrap();
+    /// let clone = view.try_clone().unwrap();
+    /// assert_eq!(clone, bitmap);
+    /// ```
+    pub fn try_clone(&self) -> Result<Self, DeserializeError>
+    where
+        B: Clone,
+    {
+        let buffer = OwnedBuffer::new(self.buffer.get().clone());
+        // SAFETY: the view is dropped before the buffer
+        let view = (self.try_deserialize)(unsafe { buffer.bytes_unbounded() })?;
+        Ok(Self {
+            view,
+            deserialize: self.deserialize,
+            try_deserialize: self.try_deserialize,
+            buffer,
+        })
+    }
+
+    /// The view of the bitmap
+    #[inline]
+    #[must_use]
+    pub fn as_view(&self) -> &BitmapView<'_> {
+        &self.view
+    }
+
+    /// The buffer backing the view
+    #[inline]
+    #[must_use]
+    pub fn buffer(&self) -> &B {
+        self.buffer.get()
+    }
+
+    /// Drop the view, returning the buffer which backed it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, OwnedBitmapView, Portable};
+    ///
+    /// let data = Bitmap::of(&[1, 2, 3]).serialize::<Portable>();
+    /// let view = OwnedBitmapView::try_deserialize::<Portable>(data.clone()).unwrap();
+    /// assert_eq!(view.into_buffer(), data);
+    /// ```
+    #[must_use]
+    pub fn into_buffer(self) -> B {
+        let Self { view, buffer, .. } = self;
+        drop(view);
+        buffer.into_inner()
+    }
+}
+
+impl<B> Deref for OwnedBitmapView<B> {
+    type Target = Bitmap;
+
+    #[inline]
+    fn deref(&self) -> &Bitmap {
+        &self.view
+    }
+}
+
+impl<B: SharedBuffer> Clone for OwnedBitmapView<B> {
+    /// Views the bitmap in a clone of the buffer, which shares the data of this view
+    fn clone(&self) -> Self {
+        let buffer = OwnedBuffer::new(self.buffer.get().clone());
+        // SAFETY: the clone shares the bytes which were deserialized into `self.view`, at the same
+        // address, and they cannot be modified while shared
+        let view = unsafe { (self.deserialize)(buffer.bytes_unbounded()) };
+        Self {
+            view,
+            deserialize: self.deserialize,
+            try_deserialize: self.try_deserialize,
+            buffer,
+        }
+    }
+}
+
+impl<B> PartialEq for OwnedBitmapView<B> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<B> PartialEq<Bitmap> for OwnedBitmapView<B> {
+    fn eq(&self, other: &Bitmap) -> bool {
+        **self == *other
+    }
+}
+
+impl<B> PartialEq<OwnedBitmapView<B>> for Bitmap {
+    fn eq(&self, other: &OwnedBitmapView<B>) -> bool {
+        *self == **other
+    }
+}
+
+impl<B> Eq for OwnedBitmapView<B> {}
+
+impl<B> Hash for OwnedBitmapView<B> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl<B> fmt::Debug for OwnedBitmapView<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
@@ -0,0 +1,207 @@
+use super::Bitmap;
+use crate::par::tree_reduce;
+use core::ops::Range;
+use rayon::iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer};
+use rayon::iter::{FromParallelIterator, IntoParallelIterator, ParallelIterator};
+
+impl Bitmap {
+    /// Computes the union of many bitmaps in parallel
+    ///
+    /// Groups of bitmaps are combined with [`Bitmap::fast_or`] on separate threads, and the
+    /// results are then merged pairwise, also in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmaps = (0..100).map(|i| Bitmap::of(&[i, i * 1000])).collect::<Vec<_>>();
+    /// let refs = bitmaps.iter().collect::<Vec<_>>();
+    ///
+    /// let union = Bitmap::par_fast_or(&refs);
+    /// assert_eq!(union, Bitmap::fast_or(&refs));
+    /// assert!(Bitmap::par_fast_or(&[]).is_empty());
+    /// ```
+    #[must_use]
+    #[doc(alias = "par_or")]
+    pub fn par_fast_or(bitmaps: &[&Bitmap]) -> Self {
+        if bitmaps.is_empty() {
+            return Self::new();
+        }
+        tree_reduce(bitmaps, &Self::fast_or, &|mut lhs, rhs| {
+            lhs.or_inplace(&rhs);
+            lhs
+        })
+    }
+
+    /// Computes the intersection of many bitmaps in parallel
+    ///
+    /// Groups of bitmaps are combined with [`Bitmap::fast_and`] on separate threads, and the
+    /// results are then merged pairwise, also in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmaps = (0..100).map(|i| Bitmap::of(&[i, 500, 1000])).collect::<Vec<_>>();
+    /// let refs = bitmaps.iter().collect::<Vec<_>>();
+    ///
+    /// let intersection = Bitmap::par_fast_and(&refs);
+    /// assert_eq!(intersection.iter().collect::<Vec<_>>(), [500, 1000]);
+    /// assert!(Bitmap::par_fast_and(&[]).is_empty());
+    /// ```
+    #[must_use]
+    #[doc(alias = "par_and")]
+    pub fn par_fast_and(bitmaps: &[&Bitmap]) -> Self {
+        if bitmaps.is_empty() {
+            return Self::new();
+        }
+        tree_reduce(bitmaps, &Self::fast_and, &|mut lhs, rhs| {
+            lhs.and_inplace(&rhs);
+            lhs
+        })
+    }
+
+    /// Computes the symmetric difference of many bitmaps in parallel
+    ///
+    /// Groups of bitmaps are combined with [`Bitmap::fast_xor`] on separate threads, and the
+    /// results are then merged pairwise, also in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let bitmaps = (0..100).map(|i| Bitmap::of(&[i, 1000])).collect::<Vec<_>>();
+    /// let refs = bitmaps.iter().collect::<Vec<_>>();
+    ///
+    /// let difference = Bitmap::par_fast_xor(&refs);
+    /// assert_eq!(difference, Bitmap::from_range(0..100));
+    /// assert!(Bitmap::par_fast_xor(&[]).is_empty());
+    /// ```
+    #[must_use]
+    #[doc(alias = "par_xor")]
+    pub fn par_fast_xor(bitmaps: &[&Bitmap]) -> Self {
+        if bitmaps.is_empty() {
+            return Self::new();
+        }
+        tree_reduce(bitmaps, &Self::fast_xor, &|mut lhs, rhs| {
+            lhs.xor_inplace(&rhs);
+            lhs
+        })
+    }
+}
+
+/// A parallel iterator over the values of a [`Bitmap`]
+///
+/// The bitmap is split between threads by container, so each container is iterated by a single
+/// thread.
+///
+/// Returned by `par_iter()` from [`rayon::iter::IntoParallelRefIterator`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap;
+/// use rayon::prelude::*;
+///
+/// let bitmap = Bitmap::from_range(0..0x10_0000);
+/// let sum: u64 = bitmap.par_iter().map(u64::from).sum();
+/// assert_eq!(sum, (0..0x10_0000u64).sum());
+/// ```
+#[derive(Clone)]
+pub struct BitmapParIter<'a> {
+    bitmap: &'a Bitmap,
+}
+
+impl ParallelIterator for BitmapParIter<'_> {
+    type Item = u32;
+
+    fn drive_unindexed<C: UnindexedConsumer<u32>>(self, consumer: C) -> C::Result {
+        let producer = ContainerProducer {
+            bitmap: self.bitmap,
+            containers: 0..self.bitmap.keys().len(),
+        };
+        bridge_unindexed(producer, consumer)
+    }
+}
+
+impl<'a> IntoParallelIterator for &'a Bitmap {
+    type Iter = BitmapParIter<'a>;
+    type Item = u32;
+
+    #[inline]
+    fn into_par_iter(self) -> Self::Iter {
+        BitmapParIter { bitmap: self }
+    }
+}
+
+/// Produces the values of a range of containers, splitting the range in half
+struct ContainerProducer<'a> {
+    bitmap: &'a Bitmap,
+    containers: Range<usize>,
+}
+
+impl UnindexedProducer for ContainerProducer<'_> {
+    type Item = u32;
+
+    fn split(self) -> (Self, Option<Self>) {
+        if self.containers.len() < 2 {
+            return (self, None);
+        }
+        let mid = self.containers.start + self.containers.len() / 2;
+        let right = ContainerProducer {
+            bitmap: self.bitmap,
+            containers: mid..self.containers.end,
+        };
+        let left = ContainerProducer {
+            bitmap: self.bitmap,
+            containers: self.containers.start..mid,
+        };
+        (left, Some(right))
+    }
+
+    fn fold_with<F: Folder<u32>>(self, folder: F) -> F {
+        let keys = &self.bitmap.keys()[self.containers];
+        let (Some(&first), Some(&last)) = (keys.first(), keys.last()) else {
+            return folder;
+        };
+        let start = u32::from(first) << 16;
+        let end = (u32::from(last) << 16) | 0xFFFF;
+        folder.consume_iter(self.bitmap.iter_range(start..=end))
+    }
+}
+
+/// Collects values from a parallel iterator
+///
+/// Each thread builds a separate bitmap, and these are then merged. When the values are
+/// ordered, as from `par_iter()` on another bitmap, the partial bitmaps are merged with
+/// [`Bitmap::append`] without copying containers.
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap;
+/// use rayon::prelude::*;
+///
+/// let bitmap: Bitmap = (0..100_000u32).into_par_iter().filter(|i| i % 3 == 0).collect();
+/// assert_eq!(bitmap.cardinality(), 33_334);
+///
+/// let doubled: Bitmap = bitmap.par_iter().map(|i| i * 2).collect();
+/// assert_eq!(doubled.maximum(), Some(199_998));
+/// ```
+impl FromParallelIterator<u32> for Bitmap {
+    fn from_par_iter<I: IntoParallelIterator<Item = u32>>(par_iter: I) -> Self {
+        par_iter
+            .into_par_iter()
+            .fold(Bitmap::new, |mut bitmap, value| {
+                bitmap.add(value);
+                bitmap
+            })
+            .reduce(Bitmap::new, |mut lhs, mut rhs| {
+                lhs.append(&mut rhs);
+                lhs
+            })
+    }
+}
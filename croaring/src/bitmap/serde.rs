@@ -0,0 +1,75 @@
+use super::Bitmap;
+use crate::Portable;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Unexpected, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// Human-readable formats (e.g. JSON) serialize a bitmap as a sorted sequence of values,
+/// other formats serialize it as a byte buffer in the [`Portable`] format.
+impl Serialize for Bitmap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_seq(self.iter())
+        } else {
+            serializer.serialize_bytes(&Bitmap::serialize::<Portable>(self))
+        }
+    }
+}
+
+/// Human-readable formats (e.g. JSON) expect a sequence of values, other formats expect a byte
+/// buffer in the [`Portable`] format.
+///
+/// Serialized data is validated: invalid bitmaps, or byte buffers with trailing data, are rejected.
+impl<'de> Deserialize<'de> for Bitmap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_seq(ValuesVisitor)
+        } else {
+            deserializer.deserialize_bytes(PortableVisitor)
+        }
+    }
+}
+
+struct ValuesVisitor;
+
+impl<'de> Visitor<'de> for ValuesVisitor {
+    type Value = Bitmap;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of u32 values")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Bitmap, A::Error> {
+        let mut bitmap = Bitmap::new();
+        while let Some(value) = seq.next_element::<u32>()? {
+            bitmap.add(value);
+        }
+        Ok(bitmap)
+    }
+}
+
+struct PortableVisitor;
+
+impl<'de> Visitor<'de> for PortableVisitor {
+    type Value = Bitmap;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a bitmap serialized in the portable format")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Bitmap, E> {
+        Bitmap::try_deserialize_exact::<Portable>(v)
+            .map_err(|err| E::invalid_value(Unexpected::Other(&err.to_string()), &self))
+    }
+
+    // Some formats represent bytes as a sequence of u8
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Bitmap, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
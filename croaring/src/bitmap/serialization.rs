@@ -1,7 +1,29 @@
 use super::{Bitmap, BitmapView};
-use crate::serialization::{Frozen, Native, Portable};
+use crate::serialization::{ClickHouse, Frozen, Native, Portable};
 
+use crate::serialization::{
+    clickhouse_size, parse_clickhouse, write_clickhouse_header, ByteSource, ClickHouseState,
+    DeserializeError, SliceSource, CLICKHOUSE_SMALL_SET_SIZE,
+};
 use core::ffi::{c_char, c_void};
+#[cfg(feature = "std")]
+use {
+    crate::serialization::ReaderSource,
+    alloc::vec::Vec,
+    std::io::{Read, Write},
+};
+
+// Constants of the portable and native formats from roaring.c, used to parse and write them
+// directly. Ensure these are still valid every time we update the version of croaring.
+const _: () = assert!(ffi::ROARING_VERSION_MAJOR == 4);
+
+const SERIAL_COOKIE_NO_RUNCONTAINER: u32 = 12346;
+const SERIAL_COOKIE: u32 = 12347;
+const NO_OFFSET_THRESHOLD: usize = 4;
+const DEFAULT_MAX_SIZE: usize = 4096;
+const SERIALIZATION_ARRAY_UINT32: u8 = 1;
+const SERIALIZATION_CONTAINER: u8 = 2;
+const FROZEN_COOKIE: u32 = 13766;
 
 /// Trait for different formats of bitmap serialization
 pub trait Serializer: crate::sealed::Sealed {
@@ -35,6 +57,21 @@ pub trait Serializer: crate::sealed::Sealed {
         &mut dst[data_start..]
     }
 
+    /// Serialize a bitmap into a writer
+    ///
+    /// Returns the number of bytes written. No padding is written before the serialized data, so
+    /// formats which require alignment ([Frozen]) must be aligned by the reader.
+    ///
+    /// By default, the bitmap is serialized into a temporary buffer, which is then written.
+    #[doc(hidden)]
+    #[cfg(feature = "std")]
+    fn serialize_into_writer<W: Write>(bitmap: &Bitmap, mut dst: W) -> std::io::Result<usize> {
+        let mut buf = Vec::new();
+        let serialized = Self::serialize_into_vec(bitmap, &mut buf);
+        dst.write_all(serialized)?;
+        Ok(serialized.len())
+    }
+
     #[doc(hidden)]
     fn try_serialize_into_aligned<'a>(bitmap: &Bitmap, dst: &'a mut [u8]) -> Option<&'a mut [u8]> {
         let offset = dst.as_ptr().align_offset(Self::REQUIRED_ALIGNMENT);
@@ -99,6 +136,22 @@ pub trait Deserializer: crate::sealed::Sealed {
     /// will result in undefined behavior.
     #[doc(hidden)]
     unsafe fn try_deserialize_unchecked(buffer: &[u8]) -> Bitmap;
+
+    /// Deserialize a bitmap from the beginning of the provided buffer, describing any failure
+    ///
+    /// The [`Bitmap::try_deserialize_prefix`] method should usually be used instead of this method
+    /// directly.
+    ///
+    /// Returns the bitmap, and the number of bytes it was deserialized from. Unlike
+    /// [`Self::try_deserialize`], the error describes why the buffer could not be deserialized.
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Bitmap, usize), DeserializeError>;
+
+    /// Deserialize a bitmap from a reader
+    ///
+    /// Only the bytes of the serialized bitmap are read from `src`, as determined by its header.
+    #[doc(hidden)]
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R: Read>(src: R) -> std::io::Result<Bitmap>;
 }
 
 /// Trait for different formats of bitmap deserialization into a view without copying
@@ -113,6 +166,12 @@ pub trait ViewDeserializer: crate::sealed::Sealed {
     /// See [`BitmapView::deserialize`] for examples.
     #[doc(hidden)]
     unsafe fn deserialize_view(data: &[u8]) -> BitmapView<'_>;
+
+    /// Create a bitmap view using the passed data, checking that it is a valid bitmap
+    ///
+    /// See [`BitmapView::try_deserialize`] for examples.
+    #[doc(hidden)]
+    fn try_deserialize_view(data: &[u8]) -> Result<BitmapView<'_>, DeserializeError>;
 }
 
 impl crate::sealed::Sealed for Portable {}
@@ -129,6 +188,12 @@ impl Serializer for Portable {
             ffi::roaring_bitmap_portable_serialize(&bitmap.bitmap, dst);
         }
     }
+
+    /// Writes the header, then the data of each container directly, without a temporary buffer
+    #[cfg(feature = "std")]
+    fn serialize_into_writer<W: Write>(bitmap: &Bitmap, mut dst: W) -> std::io::Result<usize> {
+        write_portable(bitmap, &mut dst)
+    }
 }
 
 impl Deserializer for Portable {
@@ -160,6 +225,23 @@ impl Deserializer for Portable {
         let bitmap = ffi::roaring_bitmap_portable_deserialize(buffer.as_ptr().cast::<c_char>());
         Bitmap::take_heap(bitmap)
     }
+
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Bitmap, usize), DeserializeError> {
+        let mut src = SliceSource::new(buffer);
+        parse_portable(&mut src)?;
+        let len = src.position();
+        let bitmap = unsafe {
+            ffi::roaring_bitmap_portable_deserialize_safe(buffer.as_ptr().cast::<c_char>(), len)
+        };
+        unsafe { validated(bitmap).map(|bitmap| (bitmap, len)) }
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R: Read>(mut src: R) -> std::io::Result<Bitmap> {
+        let mut buf = Vec::new();
+        parse_portable(&mut ReaderSource::new(&mut src, &mut buf))?;
+        Ok(Self::try_deserialize_prefix(&buf)?.0)
+    }
 }
 
 impl ViewDeserializer for Portable {
@@ -183,6 +265,23 @@ impl ViewDeserializer for Portable {
         let roaring = ffi::roaring_bitmap_portable_deserialize_frozen(data.as_ptr().cast());
         BitmapView::take_heap(roaring)
     }
+
+    fn try_deserialize_view(data: &[u8]) -> Result<BitmapView<'_>, DeserializeError> {
+        let mut src = SliceSource::new(data);
+        // The headers and containers are read in place, so must be aligned in memory
+        parse_portable_impl(&mut src, Some(data.as_ptr() as usize))?;
+        match data.len() - src.position() {
+            0 => {}
+            extra => return Err(DeserializeError::TrailingBytes { extra }),
+        }
+        // The headers have been checked, so every container lies within `data`
+        let view = unsafe {
+            BitmapView::take_heap(ffi::roaring_bitmap_portable_deserialize_frozen(
+                data.as_ptr().cast(),
+            ))
+        };
+        validated_view(view)
+    }
 }
 
 impl crate::sealed::Sealed for Native {}
@@ -199,6 +298,36 @@ impl Serializer for Native {
             ffi::roaring_bitmap_serialize(&bitmap.bitmap, dst);
         }
     }
+
+    /// Chooses between a portable bitmap and an array of values as `roaring_bitmap_serialize`
+    /// does, then writes either without a temporary buffer
+    #[cfg(feature = "std")]
+    fn serialize_into_writer<W: Write>(bitmap: &Bitmap, mut dst: W) -> std::io::Result<usize> {
+        let portable_size = Portable::get_serialized_size_in_bytes(bitmap);
+        let cardinality = bitmap.cardinality();
+        if (portable_size as u64) < cardinality * 4 + 4 {
+            dst.write_all(&[SERIALIZATION_CONTAINER])?;
+            return Ok(1 + write_portable(bitmap, &mut dst)?);
+        }
+        // The array is only smaller than the portable format when there are few values, so the
+        // cardinality always fits in a u32
+        dst.write_all(&[SERIALIZATION_ARRAY_UINT32])?;
+        dst.write_all(&(cardinality as u32).to_ne_bytes())?;
+        let mut values = [0; 1024];
+        let mut bytes = [0; 1024 * 4];
+        let mut iter = bitmap.iter();
+        loop {
+            let n = iter.next_many(&mut values);
+            if n == 0 {
+                break;
+            }
+            for (dst, value) in bytes.chunks_exact_mut(4).zip(&values[..n]) {
+                dst.copy_from_slice(&value.to_ne_bytes());
+            }
+            dst.write_all(&bytes[..n * 4])?;
+        }
+        Ok(1 + 4 + cardinality as usize * 4)
+    }
 }
 
 impl Deserializer for Native {
@@ -229,6 +358,87 @@ impl Deserializer for Native {
         let bitmap = ffi::roaring_bitmap_deserialize(buffer.as_ptr().cast::<c_void>());
         Bitmap::take_heap(bitmap)
     }
+
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Bitmap, usize), DeserializeError> {
+        let mut src = SliceSource::new(buffer);
+        parse_native(&mut src)?;
+        let len = src.position();
+        let bitmap =
+            unsafe { ffi::roaring_bitmap_deserialize_safe(buffer.as_ptr().cast::<c_void>(), len) };
+        unsafe { validated(bitmap).map(|bitmap| (bitmap, len)) }
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R: Read>(mut src: R) -> std::io::Result<Bitmap> {
+        let mut buf = Vec::new();
+        parse_native(&mut ReaderSource::new(&mut src, &mut buf))?;
+        Ok(Self::try_deserialize_prefix(&buf)?.0)
+    }
+}
+
+impl crate::sealed::Sealed for ClickHouse {}
+impl Serializer for ClickHouse {
+    /// Computes the serialized size in bytes of the Bitmap as a ClickHouse `groupBitmap` state.
+    /// See [`Bitmap::get_serialized_size_in_bytes`] for examples.
+    fn get_serialized_size_in_bytes(bitmap: &Bitmap) -> usize {
+        clickhouse_size(
+            bitmap.cardinality(),
+            4,
+            Portable::get_serialized_size_in_bytes(bitmap),
+        )
+    }
+
+    unsafe fn raw_serialize(bitmap: &Bitmap, dst: *mut c_char) {
+        let len = Self::get_serialized_size_in_bytes(bitmap);
+        let dst = unsafe { core::slice::from_raw_parts_mut(dst.cast::<u8>(), len) };
+        let cardinality = bitmap.cardinality();
+        let portable_len = Portable::get_serialized_size_in_bytes(bitmap);
+        let header_len = write_clickhouse_header(cardinality, portable_len, dst);
+        let data = &mut dst[header_len..];
+        if cardinality <= CLICKHOUSE_SMALL_SET_SIZE {
+            for (dst, value) in data.chunks_exact_mut(4).zip(bitmap.iter()) {
+                dst.copy_from_slice(&value.to_le_bytes());
+            }
+        } else {
+            unsafe { Portable::raw_serialize(bitmap, data.as_mut_ptr().cast::<c_char>()) };
+        }
+    }
+}
+
+impl Deserializer for ClickHouse {
+    /// Given a ClickHouse `groupBitmap` state as a slice of bytes, returns a `Bitmap` instance.
+    /// See [`Bitmap::try_deserialize`] for examples.
+    fn try_deserialize(buffer: &[u8]) -> Option<Bitmap> {
+        Self::try_deserialize_prefix(buffer)
+            .ok()
+            .map(|(bitmap, _)| bitmap)
+    }
+
+    unsafe fn try_deserialize_unchecked(buffer: &[u8]) -> Bitmap {
+        Self::try_deserialize(buffer).unwrap_unchecked()
+    }
+
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Bitmap, usize), DeserializeError> {
+        let mut src = SliceSource::new(buffer);
+        let bitmap = match parse_clickhouse(&mut src, 4)? {
+            ClickHouseState::Small { start, count } => buffer[start..start + count * 4]
+                .chunks_exact(4)
+                .map(|value| u32::from_le_bytes(value.try_into().unwrap()))
+                .collect(),
+            ClickHouseState::Large { start, len } => {
+                Bitmap::try_deserialize_exact::<Portable>(&buffer[start..start + len])
+                    .map_err(|e| e.offset_by(start))?
+            }
+        };
+        Ok((bitmap, src.position()))
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R: Read>(mut src: R) -> std::io::Result<Bitmap> {
+        let mut buf = Vec::new();
+        parse_clickhouse(&mut ReaderSource::new(&mut src, &mut buf), 4)?;
+        Ok(Self::try_deserialize_prefix(&buf)?.0)
+    }
 }
 
 impl crate::sealed::Sealed for Frozen {}
@@ -266,4 +476,233 @@ impl ViewDeserializer for Frozen {
         let roaring = ffi::roaring_bitmap_frozen_view(data.as_ptr().cast(), data.len());
         BitmapView::take_heap(roaring)
     }
+
+    fn try_deserialize_view(data: &[u8]) -> Result<BitmapView<'_>, DeserializeError> {
+        if data.as_ptr().align_offset(Self::REQUIRED_ALIGNMENT) != 0 {
+            return Err(DeserializeError::Misaligned {
+                required: Self::REQUIRED_ALIGNMENT,
+            });
+        }
+        // The header is at the end of the data, followed by the keys, counts and typecodes
+        let Some(header) = data.last_chunk::<4>().map(|h| u32::from_ne_bytes(*h)) else {
+            return Err(DeserializeError::Truncated {
+                needed: 4,
+                available: data.len(),
+            });
+        };
+        let cookie = header & 0x7FFF;
+        if cookie != FROZEN_COOKIE {
+            return Err(DeserializeError::UnknownCookie { cookie });
+        }
+        let needed = 4 + (header >> 15) as usize * 5;
+        if data.len() < needed {
+            return Err(DeserializeError::Truncated {
+                needed,
+                available: data.len(),
+            });
+        }
+        // roaring_bitmap_frozen_view checks the data is exactly the size given by the header
+        let roaring = unsafe { ffi::roaring_bitmap_frozen_view(data.as_ptr().cast(), data.len()) };
+        if roaring.is_null() {
+            return Err(DeserializeError::InvalidHeader {
+                reason: "container sizes do not match the length of the data",
+            });
+        }
+        validated_view(unsafe { BitmapView::take_heap(roaring) })
+    }
+}
+
+/// Checks the containers of a view are valid, before handing it out
+fn validated_view(view: BitmapView<'_>) -> Result<BitmapView<'_>, DeserializeError> {
+    view.internal_validate()
+        .map_err(|reason| DeserializeError::InvalidContainer { reason })?;
+    Ok(view)
+}
+
+/// Writes `bitmap` in the portable format, returning the number of bytes written
+///
+/// This matches `roaring_bitmap_portable_serialize`, but writes the data of each container
+/// directly from the bitmap.
+#[cfg(feature = "std")]
+fn write_portable<W: Write + ?Sized>(bitmap: &Bitmap, dst: &mut W) -> std::io::Result<usize> {
+    use super::ContainerType;
+
+    let containers = bitmap.containers().collect::<Vec<_>>();
+    let size = containers.len();
+    let has_run = containers
+        .iter()
+        .any(|c| c.container_type == ContainerType::Run);
+
+    let mut header = Vec::new();
+    if has_run {
+        let cookie = SERIAL_COOKIE | ((size as u32 - 1) << 16);
+        header.extend_from_slice(&cookie.to_ne_bytes());
+        let mut run_flags = alloc::vec![0u8; size.div_ceil(8)];
+        for (i, container) in containers.iter().enumerate() {
+            if container.container_type == ContainerType::Run {
+                run_flags[i / 8] |= 1 << (i % 8);
+            }
+        }
+        header.extend_from_slice(&run_flags);
+    } else {
+        header.extend_from_slice(&SERIAL_COOKIE_NO_RUNCONTAINER.to_ne_bytes());
+        header.extend_from_slice(&(size as u32).to_ne_bytes());
+    }
+    for container in &containers {
+        header.extend_from_slice(&container.key.to_ne_bytes());
+        header.extend_from_slice(&((container.cardinality - 1) as u16).to_ne_bytes());
+    }
+    let data_size = |container: &super::ContainerInfo| match container.container_type {
+        ContainerType::Array => container.cardinality as usize * 2,
+        ContainerType::Bitset => ffi::BITSET_CONTAINER_SIZE_IN_WORDS as usize * 8,
+        ContainerType::Run => 2 + container.n_runs as usize * 4,
+    };
+    if !has_run || size >= NO_OFFSET_THRESHOLD {
+        let mut offset = header.len() + size * 4;
+        for container in &containers {
+            header.extend_from_slice(&(offset as u32).to_ne_bytes());
+            offset += data_size(container);
+        }
+    }
+    dst.write_all(&header)?;
+    super::containers::write_portable_data(bitmap, dst)?;
+
+    Ok(header.len() + containers.iter().map(data_size).sum::<usize>())
+}
+
+/// Takes ownership of a bitmap deserialized by CRoaring, checking it is valid
+///
+/// `bitmap` must be null, or a valid pointer to a heap allocated bitmap
+unsafe fn validated(bitmap: *mut ffi::roaring_bitmap_t) -> Result<Bitmap, DeserializeError> {
+    if bitmap.is_null() {
+        // The headers have already been parsed, so CRoaring must have rejected a container
+        return Err(DeserializeError::InvalidContainer {
+            reason: "container data is inconsistent with its header",
+        });
+    }
+    let bitmap = Bitmap::take_heap(bitmap);
+    bitmap
+        .internal_validate()
+        .map_err(|reason| DeserializeError::InvalidContainer { reason })?;
+    Ok(bitmap)
+}
+
+/// Consumes a bitmap in the portable format from `src`
+///
+/// Only the headers are checked, which determine the length of the serialized bitmap: the data
+/// of its containers is not validated.
+pub(crate) fn parse_portable<S: ByteSource + ?Sized>(src: &mut S) -> Result<(), S::Error> {
+    parse_portable_impl(src, None)
+}
+
+/// Consumes a bitmap in the portable format from `src`, as with [`parse_portable`]
+///
+/// If `in_place` is the address of the data, it is checked for viewing without copying: the
+/// offset header (if present) must match the position of each container, as views locate
+/// containers through it rather than reading sequentially, and the headers and containers must be
+/// aligned in memory for the types they are read as.
+fn parse_portable_impl<S: ByteSource + ?Sized>(
+    src: &mut S,
+    in_place: Option<usize>,
+) -> Result<(), S::Error> {
+    let check_aligned = |offset: usize, required: usize| match in_place {
+        Some(address) if address.wrapping_add(offset) % required != 0 => {
+            Err(DeserializeError::Misaligned { required })
+        }
+        _ => Ok(()),
+    };
+
+    let start = src.take(4)?;
+    let cookie = read_u32(src.consumed(), start);
+    let (size, has_run) = if cookie & 0xFFFF == SERIAL_COOKIE {
+        ((cookie >> 16) as usize + 1, true)
+    } else if cookie == SERIAL_COOKIE_NO_RUNCONTAINER {
+        (u32::from_ne_bytes(src.take_array()?) as usize, false)
+    } else {
+        return Err(DeserializeError::UnknownCookie { cookie }.into());
+    };
+    if size > 1 << 16 {
+        return Err(DeserializeError::InvalidHeader {
+            reason: "more than 2^16 containers",
+        }
+        .into());
+    }
+
+    let run_flags_start = if has_run {
+        src.take(size.div_ceil(8))?
+    } else {
+        0
+    };
+    let header_start = src.take(size * 4)?;
+    check_aligned(header_start, 2)?;
+    let offsets_start = if !has_run || size >= NO_OFFSET_THRESHOLD {
+        let offsets_start = src.take(size * 4)?;
+        check_aligned(offsets_start, 4)?;
+        Some(offsets_start)
+    } else {
+        None
+    };
+
+    for i in 0..size {
+        let consumed = src.consumed();
+        let is_run = has_run && consumed[run_flags_start + i / 8] & (1 << (i % 8)) != 0;
+        let cardinality = usize::from(read_u16(consumed, header_start + i * 4 + 2)) + 1;
+        let container_start = if is_run {
+            let container_start = src.take(2)?;
+            check_aligned(container_start, 2)?;
+            let n_runs = read_u16(src.consumed(), container_start);
+            src.take(usize::from(n_runs) * 4)?;
+            container_start
+        } else if cardinality <= DEFAULT_MAX_SIZE {
+            let container_start = src.take(cardinality * 2)?;
+            check_aligned(container_start, 2)?;
+            container_start
+        } else {
+            let container_start = src.take(ffi::BITSET_CONTAINER_SIZE_IN_WORDS as usize * 8)?;
+            check_aligned(container_start, 8)?;
+            container_start
+        };
+        if let (Some(_), Some(offsets_start)) = (in_place, offsets_start) {
+            let offset = read_u32(src.consumed(), offsets_start + i * 4);
+            if offset as usize != container_start - start {
+                return Err(DeserializeError::InvalidHeader {
+                    reason: "container offset does not match its position",
+                }
+                .into());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_ne_bytes(bytes[offset..offset + 2].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// Consumes a bitmap in the native format from `src`
+///
+/// As with [`parse_portable`], only the headers are checked.
+fn parse_native<S: ByteSource + ?Sized>(src: &mut S) -> Result<(), S::Error> {
+    let [kind] = src.take_array()?;
+    match kind {
+        SERIALIZATION_ARRAY_UINT32 => {
+            let cardinality = u32::from_ne_bytes(src.take_array()?);
+            let len = usize::try_from(u64::from(cardinality) * 4).map_err(|_| {
+                DeserializeError::InvalidHeader {
+                    reason: "too many values",
+                }
+            })?;
+            src.take(len)?;
+            Ok(())
+        }
+        SERIALIZATION_CONTAINER => parse_portable(src),
+        _ => Err(DeserializeError::UnknownCookie {
+            cookie: u32::from(kind),
+        }
+        .into()),
+    }
 }
@@ -0,0 +1,245 @@
+use super::imp::range_to_inclusive;
+use super::Bitmap;
+use core::mem;
+use core::ops::RangeBounds;
+use core::ptr;
+use core::slice;
+
+// This moves containers between the internal arrays of bitmaps, which are not part of the API.
+// Ensure this is still valid every time we update the version of croaring.
+const _: () = assert!(ffi::ROARING_VERSION_MAJOR == 4);
+
+/// Moves the containers of `src` from `start` onwards to the end of `dst`
+///
+/// # Safety
+///
+/// `dst` must have room for the containers, and their keys must all be greater than the keys of
+/// `dst`
+unsafe fn move_containers(
+    src: &mut ffi::roaring_array_t,
+    start: usize,
+    dst: &mut ffi::roaring_array_t,
+) {
+    let src_size = src.size as usize;
+    let dst_size = dst.size as usize;
+    let count = src_size - start;
+    debug_assert!(dst_size + count <= dst.allocation_size as usize);
+    if count == 0 {
+        return;
+    }
+    ptr::copy_nonoverlapping(
+        src.containers.add(start),
+        dst.containers.add(dst_size),
+        count,
+    );
+    ptr::copy_nonoverlapping(src.keys.add(start), dst.keys.add(dst_size), count);
+    ptr::copy_nonoverlapping(src.typecodes.add(start), dst.typecodes.add(dst_size), count);
+    // The containers are now owned by `dst`
+    src.size = start as i32;
+    dst.size = (dst_size + count) as i32;
+}
+
+impl Bitmap {
+    /// The keys of the containers in the bitmap, in ascending order
+    #[inline]
+    pub(super) fn keys(&self) -> &[u16] {
+        let array = &self.bitmap.high_low_container;
+        if array.size == 0 {
+            return &[];
+        }
+        unsafe { slice::from_raw_parts(array.keys, array.size as usize) }
+    }
+
+    /// A bitmap holding only the container at `index`, borrowed from `self`
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than the number of containers. The returned bitmap must only be read,
+    /// and must not outlive `self`.
+    #[inline]
+    #[cfg(feature = "alloc")]
+    pub(super) unsafe fn container_view(&self, index: usize) -> mem::ManuallyDrop<Bitmap> {
+        let array = &self.bitmap.high_low_container;
+        debug_assert!(index < array.size as usize);
+        mem::ManuallyDrop::new(Bitmap {
+            bitmap: ffi::roaring_bitmap_t {
+                high_low_container: ffi::roaring_array_t {
+                    size: 1,
+                    allocation_size: 1,
+                    containers: array.containers.add(index),
+                    keys: array.keys.add(index),
+                    typecodes: array.typecodes.add(index),
+                    // Not copy on write, so results never share the borrowed container
+                    flags: 0,
+                },
+            },
+        })
+    }
+
+    /// An empty bitmap with room for `capacity` containers, sharing the copy-on-write setting of `self`
+    fn empty_like(&self, capacity: usize) -> Bitmap {
+        let mut result = Bitmap::with_container_capacity(capacity as u32);
+        unsafe {
+            let cow = ffi::roaring_bitmap_get_copy_on_write(&self.bitmap);
+            ffi::roaring_bitmap_set_copy_on_write(&mut result.bitmap, cow);
+        }
+        result
+    }
+
+    /// Moves the containers of `self` from `index` onwards into a new bitmap
+    fn split_off_containers(&mut self, index: usize) -> Bitmap {
+        let mut tail = self.empty_like(self.keys().len() - index);
+        unsafe {
+            move_containers(
+                &mut self.bitmap.high_low_container,
+                index,
+                &mut tail.bitmap.high_low_container,
+            );
+        }
+        tail
+    }
+
+    /// Moves all containers of `other` to the end of `self`
+    ///
+    /// The keys of `other` must all be greater than the keys of `self`
+    pub(super) fn append_containers(&mut self, other: &mut Bitmap) {
+        debug_assert!(match (self.keys().last(), other.keys().first()) {
+            (Some(last), Some(first)) => last < first,
+            _ => true,
+        });
+        let needed = self.keys().len() + other.keys().len();
+        if (self.bitmap.high_low_container.allocation_size as usize) < needed {
+            let mut grown = self.empty_like(needed);
+            unsafe {
+                move_containers(
+                    &mut self.bitmap.high_low_container,
+                    0,
+                    &mut grown.bitmap.high_low_container,
+                );
+            }
+            // The old allocation no longer owns any containers, and is freed on drop
+            mem::swap(self, &mut grown);
+        }
+        unsafe {
+            move_containers(
+                &mut other.bitmap.high_low_container,
+                0,
+                &mut self.bitmap.high_low_container,
+            );
+        }
+    }
+
+    /// Splits the bitmap in two at `at`
+    ///
+    /// Returns a bitmap containing all values `>= at`, leaving all values `< at` in `self`.
+    ///
+    /// Containers are moved to the new bitmap rather than copied: only the container holding
+    /// `at`, if any, is split by copying.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::of(&[1, 2, 3, 0x1_0000, 0x2_0001, 0x3_0000]);
+    /// let tail = bitmap.split_off(0x2_0000);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [1, 2, 3, 0x1_0000]);
+    /// assert_eq!(tail.iter().collect::<Vec<_>>(), [0x2_0001, 0x3_0000]);
+    ///
+    /// let tail = bitmap.split_off(3);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [1, 2]);
+    /// assert_eq!(tail.iter().collect::<Vec<_>>(), [3, 0x1_0000]);
+    ///
+    /// let tail = bitmap.split_off(0);
+    /// assert!(bitmap.is_empty());
+    /// assert_eq!(tail.iter().collect::<Vec<_>>(), [1, 2]);
+    /// ```
+    #[must_use]
+    pub fn split_off(&mut self, at: u32) -> Bitmap {
+        let high = (at >> 16) as u16;
+        let low = at as u16;
+        let index = self.keys().partition_point(|&key| key < high);
+        let mut tail = self.split_off_containers(index);
+        if low != 0 && tail.keys().first() == Some(&high) {
+            // The first container of the tail holds values on both sides of `at`
+            let container_start = u32::from(high) << 16;
+            let mut head = tail.and(&Bitmap::from_range(container_start..at));
+            tail.remove_range(container_start..at);
+            self.append_containers(&mut head);
+        }
+        tail
+    }
+
+    /// Moves all values of `other` into `self`, leaving `other` empty
+    ///
+    /// When every value in `other` is greater than every value in `self`, as when rejoining the
+    /// halves from [`Bitmap::split_off`], containers are moved rather than copied. Otherwise,
+    /// this is equivalent to [`Bitmap::or_inplace`] followed by clearing `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::of(&[1, 2, 3, 0x1_0000]);
+    /// let mut other = Bitmap::of(&[0x1_0001, 0x5_0000]);
+    /// bitmap.append(&mut other);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [1, 2, 3, 0x1_0000, 0x1_0001, 0x5_0000]);
+    /// assert!(other.is_empty());
+    ///
+    /// // Overlapping values are merged
+    /// let mut other = Bitmap::of(&[0, 2, 4]);
+    /// bitmap.append(&mut other);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 0x1_0000, 0x1_0001, 0x5_0000]);
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Bitmap) {
+        match (self.keys().last(), other.keys().first()) {
+            (_, None) => {}
+            (None, _) => self.append_containers(other),
+            (Some(last), Some(first)) if last < first => self.append_containers(other),
+            (Some(last), Some(first)) if last == first => {
+                // Only the boundary container needs to be merged
+                let mut rest = other.split_off_containers(1);
+                self.or_inplace(other);
+                other.clear();
+                self.append_containers(&mut rest);
+            }
+            _ => {
+                self.or_inplace(other);
+                other.clear();
+            }
+        }
+    }
+
+    /// Removes all values outside of `range`
+    ///
+    /// Containers entirely outside of `range` are dropped without being visited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::of(&[1, 2, 3, 0x1_0000, 0x2_0000, u32::MAX]);
+    /// bitmap.retain_range(2..=0x2_0000);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [2, 3, 0x1_0000, 0x2_0000]);
+    ///
+    /// bitmap.retain_range(3..3);
+    /// assert!(bitmap.is_empty());
+    /// ```
+    #[doc(alias = "truncate_range")]
+    pub fn retain_range<R: RangeBounds<u32>>(&mut self, range: R) {
+        let (start, end) = range_to_inclusive(range);
+        if start > end {
+            self.clear();
+            return;
+        }
+        if let Some(before) = start.checked_sub(1) {
+            self.remove_range(..=before);
+        }
+        if let Some(after) = end.checked_add(1) {
+            self.remove_range(after..);
+        }
+    }
+}
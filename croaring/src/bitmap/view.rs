@@ -1,5 +1,8 @@
 use super::serialization::ViewDeserializer;
 use super::{Bitmap, BitmapView};
+use crate::DeserializeError;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
 use core::marker::PhantomData;
 use core::ops::Deref;
 use core::{fmt, mem};
@@ -63,6 +66,54 @@ impl<'a> BitmapView<'a> {
         S::deserialize_view(data)
     }
 
+    /// Create a bitmap view of a slice of data without copying, checking that it is valid
+    ///
+    /// Unlike [`Self::deserialize`], this is safe to call with any data, such as data read from
+    /// disk or the network: the headers are bounds-checked, and the contents of every container
+    /// are validated as with [`Bitmap::internal_validate`] before the view is returned. `data`
+    /// must contain exactly one serialized bitmap, and for [`Frozen`](crate::Frozen) data, must
+    /// be aligned to [`Frozen::REQUIRED_ALIGNMENT`](crate::Frozen::REQUIRED_ALIGNMENT) bytes.
+    ///
+    /// [`Portable`](crate::Portable) data is read in place too, so its headers and containers
+    /// must be aligned in memory for the 16 and 64-bit values they hold. Where they fall depends
+    /// on the bitmap: data which is not aligned is rejected with
+    /// [`DeserializeError::Misaligned`], and can be copied with [`Bitmap::try_deserialize`]
+    /// instead.
+    ///
+    /// Validation visits every container, so this is slower than [`Self::deserialize`], though
+    /// still without copying the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, BitmapView, DeserializeError, Frozen, Portable};
+    ///
+    /// #[repr(align(8))]
+    /// struct AlignedBuf([u8; 1024]);
+    ///
+    /// let orig_bitmap = Bitmap::of(&[1, 2, 3, 4]);
+    /// let mut buf = AlignedBuf([0; 1024]);
+    /// let data: &[u8] = orig_bitmap.try_serialize_into::<Portable>(&mut buf.0).unwrap();
+    /// let view = BitmapView::try_deserialize::<Portable>(data).unwrap();
+    /// assert_eq!(orig_bitmap, view);
+    ///
+    /// let err = BitmapView::try_deserialize::<Portable>(&data[..data.len() - 1]).unwrap_err();
+    /// assert!(matches!(err, DeserializeError::Truncated { .. }));
+    ///
+    /// let mut buf = AlignedBuf([0; 1024]);
+    /// let data: &[u8] = orig_bitmap.try_serialize_into::<Portable>(&mut buf.0[1..]).unwrap();
+    /// let err = BitmapView::try_deserialize::<Portable>(data).unwrap_err();
+    /// assert!(matches!(err, DeserializeError::Misaligned { .. }));
+    ///
+    /// let mut buf = [0; 1024];
+    /// let data: &[u8] = orig_bitmap.try_serialize_into::<Frozen>(&mut buf).unwrap();
+    /// let view = BitmapView::try_deserialize::<Frozen>(data).unwrap();
+    /// assert_eq!(orig_bitmap, view);
+    /// ```
+    pub fn try_deserialize<S: ViewDeserializer>(data: &'a [u8]) -> Result<Self, DeserializeError> {
+        S::try_deserialize_view(data)
+    }
+
     /// Create an owned, mutable bitmap from this view
     ///
     /// # Examples
@@ -108,6 +159,27 @@ impl PartialEq for BitmapView<'_> {
 
 impl Eq for BitmapView<'_> {}
 
+impl Hash for BitmapView<'_> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Bitmap::hash(self, state);
+    }
+}
+
+impl PartialOrd for BitmapView<'_> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BitmapView<'_> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        Bitmap::cmp(self, other)
+    }
+}
+
 impl<'a> Drop for BitmapView<'a> {
     fn drop(&mut self) {
         // Based heavily on the c++ wrapper included in CRoaring
@@ -0,0 +1,174 @@
+use super::Bitmap64;
+use core::mem::MaybeUninit;
+use core::ops::Deref;
+
+/// A context for repeated membership tests and insertions on a single bitmap
+///
+/// The context remembers the container used by the last operation, so consecutive operations on
+/// values which share their high 48 bits avoid searching the bitmap for the container again.
+///
+/// A context borrows the bitmap it was created for: created with
+/// [`Bitmap64::bulk_context`], it can only test membership, while one created with
+/// [`Bitmap64::bulk_context_mut`] can also add values.
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap64;
+///
+/// let mut bitmap = Bitmap64::new();
+/// let mut context = bitmap.bulk_context_mut();
+/// context.add(1);
+/// context.add(2);
+/// assert!(context.contains(1));
+/// assert!(!context.contains(3));
+///
+/// let mut context = bitmap.bulk_context();
+/// let found = [1, 2, 3].iter().filter(|&&x| context.contains(x)).count();
+/// assert_eq!(found, 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bitmap64BulkContext<B> {
+    bitmap: B,
+    context: ffi::roaring64_bulk_context_t,
+}
+
+// SAFETY: the context caches a pointer to the ART leaf found by the last operation, which lives
+// in the bitmap borrowed through `bitmap`. The pointer is only followed by the bulk functions,
+// which are always given that bitmap, so the context can move to another thread whenever `B` can.
+unsafe impl<B: Send> Send for Bitmap64BulkContext<B> {}
+
+// SAFETY: `contains` and `add` take `&mut self`, as the bulk functions update the cached leaf.
+// Through a shared reference, only the bitmap itself can be reached, so sharing the context is
+// sound whenever sharing `B` is.
+unsafe impl<B: Sync> Sync for Bitmap64BulkContext<B> {}
+
+impl<B> Bitmap64BulkContext<B> {
+    #[inline]
+    fn new(bitmap: B) -> Self {
+        let context =
+            unsafe { MaybeUninit::<ffi::roaring64_bulk_context_t>::zeroed().assume_init() };
+        Self { bitmap, context }
+    }
+}
+
+impl<B: Deref<Target = Bitmap64>> Bitmap64BulkContext<B> {
+    /// Returns the bitmap this context operates on
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap = Bitmap64::of(&[1, 2, 3]);
+    /// let context = bitmap.bulk_context();
+    /// assert_eq!(context.bitmap().cardinality(), 3);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn bitmap(&self) -> &Bitmap64 {
+        &self.bitmap
+    }
+
+    /// Returns true if the bitmap contains `value`
+    ///
+    /// This is faster than [`Bitmap64::contains`] when consecutive calls are made with values which
+    /// share their high 48 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap = Bitmap64::of(&[1, 2, 3, 1 << 40]);
+    /// let mut context = bitmap.bulk_context();
+    /// assert!(context.contains(1));
+    /// assert!(!context.contains(4));
+    /// assert!(context.contains(1 << 40));
+    /// assert!(context.contains(3));
+    /// ```
+    #[inline]
+    #[doc(alias = "roaring64_bitmap_contains_bulk")]
+    #[must_use]
+    pub fn contains(&mut self, value: u64) -> bool {
+        unsafe {
+            ffi::roaring64_bitmap_contains_bulk(self.bitmap.raw.as_ptr(), &mut self.context, value)
+        }
+    }
+}
+
+impl Bitmap64BulkContext<&mut Bitmap64> {
+    /// Add the integer `value` to the bitmap
+    ///
+    /// This is faster than [`Bitmap64::add`] when consecutive calls are made with values which share
+    /// their high 48 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let mut bitmap = Bitmap64::new();
+    /// let mut context = bitmap.bulk_context_mut();
+    /// for i in 0..10 {
+    ///     context.add(i * 2);
+    /// }
+    /// assert_eq!(bitmap.cardinality(), 10);
+    /// assert!(bitmap.contains(18));
+    /// ```
+    #[inline]
+    #[doc(alias = "roaring64_bitmap_add_bulk")]
+    pub fn add(&mut self, value: u64) {
+        unsafe {
+            ffi::roaring64_bitmap_add_bulk(self.bitmap.raw.as_ptr(), &mut self.context, value)
+        }
+    }
+}
+
+impl Extend<u64> for Bitmap64BulkContext<&mut Bitmap64> {
+    fn extend<T: IntoIterator<Item = u64>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|value| self.add(value));
+    }
+}
+
+impl Bitmap64 {
+    /// Returns a context for repeated membership tests on this bitmap
+    ///
+    /// See [`Bitmap64BulkContext`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap = Bitmap64::from_range(100..200);
+    /// let mut context = bitmap.bulk_context();
+    /// assert_eq!((0..1000).filter(|&x| context.contains(x)).count(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn bulk_context(&self) -> Bitmap64BulkContext<&Self> {
+        Bitmap64BulkContext::new(self)
+    }
+
+    /// Returns a context for repeated insertions and membership tests on this bitmap
+    ///
+    /// See [`Bitmap64BulkContext`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let mut bitmap = Bitmap64::new();
+    /// let mut context = bitmap.bulk_context_mut();
+    /// context.extend([1, 5, 3]);
+    /// assert!(context.contains(5));
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [1, 3, 5]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn bulk_context_mut(&mut self) -> Bitmap64BulkContext<&mut Self> {
+        Bitmap64BulkContext::new(self)
+    }
+}
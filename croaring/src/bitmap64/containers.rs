@@ -0,0 +1,141 @@
+use super::Bitmap64;
+use crate::{BitmapView, Portable};
+use alloc::vec::{self, Vec};
+use core::iter::FusedIterator;
+
+pub use crate::bitmap::ContainerType;
+
+/// Metadata about a single container of a [`Bitmap64`]
+///
+/// See [`Bitmap64::containers`] for more information
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Bitmap64ContainerInfo {
+    /// The high 48 bits shared by all values in the container
+    pub key: u64,
+    /// The representation used by the container
+    pub container_type: ContainerType,
+    /// Number of values in the container
+    pub cardinality: u32,
+    /// Number of bytes used by the container's data, as counted by [`Bitmap64::statistics`]
+    pub size_in_bytes: u32,
+    /// Number of runs of consecutive values in the container
+    ///
+    /// This is computed for every container type, so it can be used to judge whether
+    /// [`Bitmap64::run_optimize`] would help.
+    pub n_runs: u32,
+}
+
+/// An iterator over the containers of a [`Bitmap64`]
+///
+/// See [`Bitmap64::containers`] for more information
+#[derive(Debug, Clone)]
+pub struct Bitmap64Containers {
+    /// The bitmap, serialized with [`Portable`]
+    data: Vec<u8>,
+    /// Offset in `data` of the next bucket to decode
+    offset: usize,
+    buckets_left: u64,
+    /// The remaining containers of the last decoded bucket
+    bucket: vec::IntoIter<Bitmap64ContainerInfo>,
+    remaining: usize,
+}
+
+impl Bitmap64Containers {
+    /// Decodes the containers of the next 32-bit bucket of the serialized bitmap
+    fn next_bucket(&mut self) -> Option<()> {
+        if self.buckets_left == 0 {
+            return None;
+        }
+        self.buckets_left -= 1;
+
+        let (high, data) = self.data[self.offset..].split_at(4);
+        let high = u32::from_le_bytes(high.try_into().unwrap());
+        // SAFETY: the data was serialized by CRoaring in Bitmap64::containers
+        let view = unsafe { BitmapView::deserialize::<Portable>(data) };
+        let containers: Vec<_> = view
+            .containers()
+            .map(|info| Bitmap64ContainerInfo {
+                key: (u64::from(high) << 16) | u64::from(info.key),
+                container_type: info.container_type,
+                cardinality: info.cardinality,
+                size_in_bytes: info.size_in_bytes,
+                n_runs: info.n_runs,
+            })
+            .collect();
+        self.offset += 4 + view.get_serialized_size_in_bytes::<Portable>();
+        self.bucket = containers.into_iter();
+        Some(())
+    }
+}
+
+impl Iterator for Bitmap64Containers {
+    type Item = Bitmap64ContainerInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(info) = self.bucket.next() {
+                self.remaining -= 1;
+                return Some(info);
+            }
+            self.next_bucket()?;
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Bitmap64Containers {}
+
+impl FusedIterator for Bitmap64Containers {}
+
+impl Bitmap64 {
+    /// Returns an iterator over metadata about each container in the bitmap
+    ///
+    /// A bitmap stores its values in containers of up to 2^16 values, grouped by their high
+    /// 48 bits. Containers are returned in ascending order of key.
+    ///
+    /// CRoaring offers no way to walk the containers of a 64-bit bitmap, so this serializes the
+    /// whole bitmap with [`Portable`] up front, which takes time and memory proportional to the
+    /// size of the bitmap. Containers are then decoded lazily, one group of containers sharing
+    /// their high 32 bits at a time. Prefer calling this once over calling it repeatedly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    /// use croaring::bitmap64::ContainerType;
+    ///
+    /// let mut bitmap = Bitmap64::of(&[1, 2, 3, 10]);
+    /// bitmap.add_range(0xFFFF_0000_0000..0xFFFF_0000_1000);
+    /// bitmap.run_optimize();
+    ///
+    /// let containers: Vec<_> = bitmap.containers().collect();
+    /// assert_eq!(containers.len(), 2);
+    ///
+    /// assert_eq!(containers[0].key, 0);
+    /// assert_eq!(containers[0].container_type, ContainerType::Array);
+    /// assert_eq!(containers[0].cardinality, 4);
+    /// assert_eq!(containers[0].n_runs, 2);
+    ///
+    /// assert_eq!(containers[1].key, 0xFFFF_0000);
+    /// assert_eq!(containers[1].container_type, ContainerType::Run);
+    /// assert_eq!(containers[1].cardinality, 0x1000);
+    /// assert_eq!(containers[1].n_runs, 1);
+    /// ```
+    #[must_use]
+    pub fn containers(&self) -> Bitmap64Containers {
+        let data = self.serialize::<Portable>();
+        let buckets_left = u64::from_le_bytes(data[..8].try_into().unwrap());
+        Bitmap64Containers {
+            data,
+            offset: 8,
+            buckets_left,
+            bucket: Vec::new().into_iter(),
+            // Every container is held in memory, so their number fits in a usize
+            remaining: self.statistics().n_containers as usize,
+        }
+    }
+}
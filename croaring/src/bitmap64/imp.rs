@@ -1,6 +1,7 @@
 use super::{Bitmap64, Deserializer, Serializer, Statistics};
+use crate::DeserializeError;
 use core::mem::MaybeUninit;
-use core::ops::{Bound, RangeBounds};
+use core::ops::{Bound, RangeBounds, RangeInclusive};
 use core::prelude::v1::*;
 use core::ptr::{self, NonNull};
 
@@ -54,6 +55,28 @@ impl Bitmap64 {
         Self::from_range_with_step(range, 1)
     }
 
+    /// Create a new bitmap containing all the values in each of `ranges`
+    ///
+    /// This is the inverse of [`Bitmap64::ranges`]. The ranges may overlap, and need not be sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap = Bitmap64::from_ranges([10..=12, 1..=2, 11..=13, u64::MAX..=u64::MAX]);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [1, 2, 10, 11, 12, 13, u64::MAX]);
+    /// assert_eq!(bitmap.ranges().collect::<Vec<_>>(), [1..=2, 10..=13, u64::MAX..=u64::MAX]);
+    /// ```
+    #[must_use]
+    pub fn from_ranges<I: IntoIterator<Item = RangeInclusive<u64>>>(ranges: I) -> Self {
+        let mut result = Self::new();
+        for range in ranges {
+            result.add_range(range);
+        }
+        result
+    }
+
     /// Create a new bitmap containing all the values in `range` which are a multiple of `step` away from the lower
     /// bound
     ///
@@ -639,6 +662,68 @@ impl Bitmap64 {
         has_elem.then_some(element)
     }
 
+    /// Selects the elements at each of the given positions
+    ///
+    /// Writes the element at `positions[i]` into `dst[i]`, stopping at the first position which
+    /// is not less than the cardinality of the bitmap. Returns the number of elements written.
+    ///
+    /// This is equivalent to calling [`select`][Self::select] for each position, but when
+    /// `positions` is sorted and close together, the elements between them are read in bulk
+    /// rather than searching the bitmap from the start for each position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is shorter than `positions`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap = Bitmap64::of(&[1, 10, 100, 1000, u64::MAX]);
+    ///
+    /// let mut dst = [0; 3];
+    /// assert_eq!(bitmap.select_many(&[0, 2, 4], &mut dst), 3);
+    /// assert_eq!(dst, [1, 100, u64::MAX]);
+    ///
+    /// // Selection stops at the first out-of-range position
+    /// let mut dst = [0; 3];
+    /// assert_eq!(bitmap.select_many(&[3, 5, 1], &mut dst), 1);
+    /// assert_eq!(dst[0], 1000);
+    /// ```
+    pub fn select_many(&self, positions: &[u64], dst: &mut [u64]) -> usize {
+        // Past this distance, a fresh select is cheaper than reading through the gap
+        const MAX_SCAN: u64 = 4096;
+
+        let dst = &mut dst[..positions.len()];
+        let mut cursor = self.cursor();
+        let mut cursor_position = 0;
+        let mut scratch = [0; 256];
+        for (i, (&position, out)) in positions.iter().zip(dst).enumerate() {
+            if position < cursor_position || position - cursor_position > MAX_SCAN {
+                let Some(value) = self.select(position) else {
+                    return i;
+                };
+                cursor.reset_at_or_after(value);
+            } else {
+                let mut remaining = position - cursor_position;
+                while remaining != 0 {
+                    let n = remaining.min(scratch.len() as u64) as usize;
+                    if cursor.read_many(&mut scratch[..n]) != n {
+                        return i;
+                    }
+                    remaining -= n as u64;
+                }
+            }
+            cursor_position = position;
+            match cursor.current() {
+                Some(value) => *out = value,
+                None => return i,
+            }
+        }
+        positions.len()
+    }
+
     /// Returns the number of integers that are smaller or equal to x
     ///
     /// If x is the first element, this function will return 1. If x is smaller than the smallest element, this
@@ -670,6 +755,50 @@ impl Bitmap64 {
         unsafe { ffi::roaring64_bitmap_rank(self.raw.as_ptr(), value) }
     }
 
+    /// Computes the [rank][Self::rank] of each of the given values
+    ///
+    /// Writes the number of elements smaller or equal to `values[i]` into `ranks[i]`.
+    ///
+    /// This is equivalent to calling [`rank`][Self::rank] for each value, but when `values`
+    /// is sorted, each rank is computed relative to the previous one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ranks` is shorter than `values`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap = Bitmap64::from_range(5..10);
+    ///
+    /// let mut ranks = [0; 4];
+    /// bitmap.rank_many(&[4, 5, 8, u64::MAX], &mut ranks);
+    /// assert_eq!(ranks, [0, 1, 4, 5]);
+    ///
+    /// // Unsorted values are allowed
+    /// bitmap.rank_many(&[u64::MAX, 8, 4, 5], &mut ranks);
+    /// assert_eq!(ranks, [5, 4, 0, 1]);
+    /// ```
+    pub fn rank_many(&self, values: &[u64], ranks: &mut [u64]) {
+        let ranks = &mut ranks[..values.len()];
+        let mut prev: Option<(u64, u64)> = None;
+        for (&value, rank) in values.iter().zip(ranks) {
+            *rank = match prev {
+                Some((prev_value, prev_rank)) if prev_value <= value => {
+                    prev_rank
+                        + self.range_cardinality((
+                            Bound::Excluded(prev_value),
+                            Bound::Included(value),
+                        ))
+                }
+                _ => self.rank(value),
+            };
+            prev = Some((value, *rank));
+        }
+    }
+
     /// Returns the index of x in the given roaring bitmap.
     ///
     /// If the roaring bitmap doesn't contain x, this function will return None.
@@ -812,6 +941,51 @@ impl Bitmap64 {
         }
     }
 
+    /// Shifts all values in the bitmap by `offset`
+    ///
+    /// Any values which would underflow or overflow `u64` are dropped.
+    ///
+    /// CRoaring cannot shift a 64-bit bitmap in place, so this is not cheaper than
+    /// [`Self::add_offset`]: the shifted bitmap is built as a copy, which then replaces `self`.
+    ///
+    /// # Examples
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let mut bitmap = Bitmap64::of(&[0, 1, 1000, u64::MAX]);
+    /// bitmap.add_offset_inplace(-1);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [0, 999, u64::MAX - 1]);
+    /// bitmap.add_offset_inplace(1 << 40);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [1 << 40, (1 << 40) + 999]);
+    /// ```
+    pub fn add_offset_inplace(&mut self, offset: i128) {
+        *self = self.add_offset(offset);
+    }
+
+    /// Returns a new bitmap with all values shifted by the given offset
+    ///
+    /// Returns the new bitmap, and the number of values which were dropped because they would
+    /// underflow or overflow `u64`.
+    ///
+    /// # Examples
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap = Bitmap64::of(&[0, 1, 1000, u64::MAX]);
+    /// let (shifted, dropped) = bitmap.add_offset_checked(-1);
+    /// assert_eq!(shifted.iter().collect::<Vec<_>>(), [0, 999, u64::MAX - 1]);
+    /// assert_eq!(dropped, 1);
+    ///
+    /// let (shifted, dropped) = bitmap.add_offset_checked(i128::from(u64::MAX) + 1);
+    /// assert!(shifted.is_empty());
+    /// assert_eq!(dropped, 4);
+    /// ```
+    #[must_use]
+    pub fn add_offset_checked(&self, offset: i128) -> (Self, u64) {
+        let kept = offset_kept_range(offset).map_or(0, |kept| self.range_cardinality(kept));
+        (self.add_offset(offset), self.cardinality() - kept)
+    }
+
     /// Returns a vector containing the values in the bitmap in sorted order
     ///
     /// # Examples
@@ -834,6 +1008,66 @@ impl Bitmap64 {
         vec
     }
 
+    /// Returns a vector containing at most `limit` of the values in the bitmap, in sorted order,
+    /// starting with the value at position `offset`
+    ///
+    /// This is useful for paging through a bitmap by position. See also
+    /// [`read_range_into`][Self::read_range_into] to fill an existing buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap: Bitmap64 = (0..100).map(|x| x << 32).collect();
+    ///
+    /// assert_eq!(bitmap.to_vec_range(1, 2), [1 << 32, 2 << 32]);
+    /// assert_eq!(bitmap.to_vec_range(99, 3), [99 << 32]);
+    /// assert_eq!(bitmap.to_vec_range(100, 3), []);
+    /// ```
+    #[must_use]
+    #[cfg(feature = "alloc")]
+    pub fn to_vec_range(&self, offset: u64, limit: usize) -> Vec<u64> {
+        let remaining = self.cardinality().saturating_sub(offset);
+        let len = limit.min(usize::try_from(remaining).unwrap_or(usize::MAX));
+
+        let mut vec = alloc::vec![0; len];
+        let read = self.read_range_into(offset, &mut vec);
+        debug_assert_eq!(read, len);
+        vec
+    }
+
+    /// Fills `dst` with the values in the bitmap, in sorted order, starting with the value at
+    /// position `offset`
+    ///
+    /// Returns the number of values written, which is less than `dst.len()` only if the end of
+    /// the bitmap was reached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap: Bitmap64 = (0..100).map(|x| x << 32).collect();
+    /// let mut page = [0; 4];
+    ///
+    /// assert_eq!(bitmap.read_range_into(10, &mut page), 4);
+    /// assert_eq!(page, [10 << 32, 11 << 32, 12 << 32, 13 << 32]);
+    ///
+    /// assert_eq!(bitmap.read_range_into(97, &mut page), 3);
+    /// assert_eq!(page[..3], [97 << 32, 98 << 32, 99 << 32]);
+    ///
+    /// assert_eq!(bitmap.read_range_into(1000, &mut page), 0);
+    /// ```
+    pub fn read_range_into(&self, offset: u64, dst: &mut [u64]) -> usize {
+        let Some(first) = self.select(offset) else {
+            return 0;
+        };
+        let mut cursor = self.cursor();
+        cursor.reset_at_or_after(first);
+        cursor.read_many(dst)
+    }
+
     /// Shrink the memory allocation of the bitmap if needed
     ///
     /// Returns the number of bytes saved
@@ -975,6 +1209,36 @@ impl Bitmap64 {
         S::try_serialize_into_aligned(self, dst)
     }
 
+    /// Serializes a bitmap to a writer in format `S`
+    ///
+    /// Returns the number of bytes written to the writer.
+    ///
+    /// The internal structure of a 64-bit bitmap is not directly accessible, so the bitmap is
+    /// serialized into a temporary buffer before being written.
+    ///
+    /// Note that the [`Frozen`][crate::Frozen] format requires alignment to 64 bytes. This function
+    /// assumes the writer starts at an aligned position (and cannot check this).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap64, Portable};
+    ///
+    /// let bitmap = Bitmap64::of(&[1, 2, 3, 1 << 40]);
+    ///
+    /// let mut data = Vec::new();
+    /// let written = bitmap.serialize_into_writer::<Portable, _>(&mut data).unwrap();
+    /// assert_eq!(written, data.len());
+    /// assert_eq!(data, bitmap.serialize::<Portable>());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn serialize_into_writer<S: Serializer, W: std::io::Write>(
+        &self,
+        writer: W,
+    ) -> std::io::Result<usize> {
+        S::serialize_into_writer(self, writer)
+    }
+
     /// Given a serialized bitmap as slice of bytes in format `S`, returns a `Bitmap64` instance.
     /// See example of [`Self::serialize`] function.
     ///
@@ -1002,6 +1266,69 @@ impl Bitmap64 {
         D::try_deserialize(buffer)
     }
 
+    /// Deserializes a bitmap in format `D` from the beginning of `buffer`, describing any failure
+    ///
+    /// Returns the bitmap, and the number of bytes it was deserialized from: `buffer` may continue
+    /// after the end of the bitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")]
+    /// # {
+    /// use croaring::{Bitmap64, DeserializeError, Portable};
+    ///
+    /// let bitmap = Bitmap64::of(&[1, 2, 3, 1 << 40]);
+    /// let mut buffer = bitmap.serialize::<Portable>();
+    /// let len = buffer.len();
+    /// buffer.extend_from_slice(b"more data");
+    ///
+    /// let (deserialized, read) = Bitmap64::try_deserialize_prefix::<Portable>(&buffer).unwrap();
+    /// assert_eq!(deserialized, bitmap);
+    /// assert_eq!(read, len);
+    ///
+    /// let err = Bitmap64::try_deserialize_prefix::<Portable>(&buffer[..len - 1]).unwrap_err();
+    /// assert_eq!(err, DeserializeError::Truncated { needed: len, available: len - 1 });
+    /// # }
+    /// ```
+    pub fn try_deserialize_prefix<D: Deserializer>(
+        buffer: &[u8],
+    ) -> Result<(Self, usize), DeserializeError> {
+        D::try_deserialize_prefix(buffer)
+    }
+
+    /// Deserializes a bitmap in format `D` which fills the whole of `buffer`, describing any failure
+    ///
+    /// Unlike [`Self::try_deserialize_prefix`], it is an error for `buffer` to continue after
+    /// the end of the bitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")]
+    /// # {
+    /// use croaring::{Bitmap64, DeserializeError, Portable};
+    ///
+    /// let bitmap = Bitmap64::of(&[1, 2, 3, 1 << 40]);
+    /// let mut buffer = bitmap.serialize::<Portable>();
+    /// assert_eq!(Bitmap64::try_deserialize_exact::<Portable>(&buffer).unwrap(), bitmap);
+    ///
+    /// buffer.push(0);
+    /// let err = Bitmap64::try_deserialize_exact::<Portable>(&buffer).unwrap_err();
+    /// assert_eq!(err, DeserializeError::TrailingBytes { extra: 1 });
+    ///
+    /// let err = Bitmap64::try_deserialize_exact::<Portable>(&[3]).unwrap_err();
+    /// assert_eq!(err, DeserializeError::Truncated { needed: 8, available: 1 });
+    /// # }
+    /// ```
+    pub fn try_deserialize_exact<D: Deserializer>(buffer: &[u8]) -> Result<Self, DeserializeError> {
+        let (bitmap, len) = D::try_deserialize_prefix(buffer)?;
+        match buffer.len() - len {
+            0 => Ok(bitmap),
+            extra => Err(DeserializeError::TrailingBytes { extra }),
+        }
+    }
+
     /// Given a serialized bitmap as slice of bytes in format `S `, returns a bitmap instance.
     /// See example of [`Self::serialize`] function.
     ///
@@ -1012,6 +1339,42 @@ impl Bitmap64 {
         Self::try_deserialize::<D>(buffer).unwrap_or_default()
     }
 
+    /// Reads a serialized bitmap in format `D` from a reader
+    ///
+    /// Only the bytes of the serialized bitmap are read, as determined by its header, so several
+    /// bitmaps can be read one after another from the same reader.
+    ///
+    /// Returns an error if reading fails, if the reader ends before the end of the bitmap (with
+    /// [`UnexpectedEof`][std::io::ErrorKind::UnexpectedEof]), or if the data is not a valid
+    /// bitmap (with [`InvalidData`][std::io::ErrorKind::InvalidData]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap64, Portable};
+    /// use std::io::Cursor;
+    ///
+    /// let bitmap1 = Bitmap64::of(&[1, 2, 3, 1 << 40]);
+    /// let bitmap2 = Bitmap64::from_range(0..100_000);
+    ///
+    /// let mut data = Vec::new();
+    /// bitmap1.serialize_into_writer::<Portable, _>(&mut data).unwrap();
+    /// bitmap2.serialize_into_writer::<Portable, _>(&mut data).unwrap();
+    ///
+    /// let mut reader = Cursor::new(data);
+    /// assert_eq!(Bitmap64::deserialize_from_reader::<Portable, _>(&mut reader).unwrap(), bitmap1);
+    /// assert_eq!(Bitmap64::deserialize_from_reader::<Portable, _>(&mut reader).unwrap(), bitmap2);
+    ///
+    /// let err = Bitmap64::deserialize_from_reader::<Portable, _>(&mut reader).unwrap_err();
+    /// assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn deserialize_from_reader<D: Deserializer, R: std::io::Read>(
+        reader: R,
+    ) -> std::io::Result<Self> {
+        D::deserialize_from_reader(reader)
+    }
+
     /// Iterate over the values in the bitmap in sorted order
     ///
     /// If `f` returns `Break`, iteration will stop and the value will be returned,
@@ -1292,6 +1655,156 @@ impl Bitmap64 {
         unsafe { ffi::roaring64_bitmap_and_inplace(self.raw.as_ptr(), other.raw.as_ptr()) }
     }
 
+    /// Computes the intersection of many bitmaps
+    ///
+    /// CRoaring does not expose the containers of 64-bit bitmaps, so unlike
+    /// [`Bitmap::fast_and`](crate::Bitmap::fast_and), this intersects whole bitmaps in turn, from
+    /// the smallest cardinality to the largest, into a single intermediate bitmap. No work is done
+    /// when the ranges of values of the bitmaps do not overlap, and the remaining bitmaps are
+    /// skipped as soon as the intersection is empty.
+    ///
+    /// An empty slice produces an empty bitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap1 = Bitmap64::of(&[15, 25, 35]);
+    /// let bitmap2 = Bitmap64::of(&[25, 35, 45]);
+    /// let bitmap3 = Bitmap64::of(&[5, 35]);
+    ///
+    /// let bitmap4 = Bitmap64::fast_and(&[&bitmap1, &bitmap2, &bitmap3]);
+    ///
+    /// assert_eq!(bitmap4.cardinality(), 1);
+    /// assert!(bitmap4.contains(35));
+    ///
+    /// assert!(Bitmap64::fast_and(&[]).is_empty());
+    /// ```
+    #[must_use]
+    #[cfg(feature = "alloc")]
+    pub fn fast_and(bitmaps: &[&Bitmap64]) -> Self {
+        let Some(bitmaps) = Self::fast_and_order(bitmaps) else {
+            return Self::new();
+        };
+        match bitmaps[..] {
+            [only] => only.clone(),
+            [first, second, ref rest @ ..] => {
+                let mut result = first.and(second);
+                for bitmap in rest {
+                    if result.is_empty() {
+                        break;
+                    }
+                    result.and_inplace(bitmap);
+                }
+                result
+            }
+            [] => unreachable!(),
+        }
+    }
+
+    /// Computes the cardinality of the intersection between many bitmaps
+    ///
+    /// This is equivalent to `Bitmap64::fast_and(bitmaps).cardinality()`, but the intersection
+    /// with the last bitmap is only counted. The intersection of the other bitmaps is built as in
+    /// [`Self::fast_and`], so nothing is allocated only when intersecting two bitmaps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap1 = Bitmap64::of(&[15, 25, 35]);
+    /// let bitmap2 = Bitmap64::of(&[25, 35, 45]);
+    /// let bitmap3 = Bitmap64::of(&[5, 25, 35]);
+    ///
+    /// assert_eq!(Bitmap64::fast_and_cardinality(&[&bitmap1, &bitmap2, &bitmap3]), 2);
+    /// assert_eq!(Bitmap64::fast_and_cardinality(&[&bitmap1, &Bitmap64::new()]), 0);
+    /// ```
+    #[must_use]
+    #[cfg(feature = "alloc")]
+    pub fn fast_and_cardinality(bitmaps: &[&Bitmap64]) -> u64 {
+        let Some(bitmaps) = Self::fast_and_order(bitmaps) else {
+            return 0;
+        };
+        match bitmaps[..] {
+            [only] => only.cardinality(),
+            [first, second] => first.and_cardinality(second),
+            [first, second, ref rest @ .., last] => {
+                let mut partial = first.and(second);
+                for bitmap in rest {
+                    if partial.is_empty() {
+                        return 0;
+                    }
+                    partial.and_inplace(bitmap);
+                }
+                partial.and_cardinality(last)
+            }
+            [] => unreachable!(),
+        }
+    }
+
+    /// Computes the values present in at least `k` of the given bitmaps
+    ///
+    /// The number of bitmaps containing each value is counted in bit-sliced form, so this takes
+    /// `O(n log n)` bitmap operations for `n` bitmaps, regardless of `k`.
+    ///
+    /// A `k` of 0 or 1 gives the union of the bitmaps, a `k` equal to the number of bitmaps gives
+    /// their intersection, and a larger `k` gives an empty bitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap1 = Bitmap64::of(&[1, 2, 3, 4]);
+    /// let bitmap2 = Bitmap64::of(&[2, 3, 4]);
+    /// let bitmap3 = Bitmap64::of(&[3, 4]);
+    /// let bitmap4 = Bitmap64::of(&[4, 5]);
+    /// let bitmaps = [&bitmap1, &bitmap2, &bitmap3, &bitmap4];
+    ///
+    /// assert_eq!(Bitmap64::threshold(&bitmaps, 1), Bitmap64::of(&[1, 2, 3, 4, 5]));
+    /// assert_eq!(Bitmap64::threshold(&bitmaps, 2), Bitmap64::of(&[2, 3, 4]));
+    /// assert_eq!(Bitmap64::threshold(&bitmaps, 3), Bitmap64::of(&[3, 4]));
+    /// assert_eq!(Bitmap64::threshold(&bitmaps, 4), Bitmap64::of(&[4]));
+    /// assert!(Bitmap64::threshold(&bitmaps, 5).is_empty());
+    /// ```
+    #[must_use]
+    #[cfg(feature = "alloc")]
+    pub fn threshold(bitmaps: &[&Bitmap64], k: usize) -> Self {
+        crate::threshold::threshold(bitmaps, k)
+    }
+
+    /// Computes the union of all the bitmaps
+    #[cfg(feature = "alloc")]
+    pub(crate) fn union(bitmaps: &[&Bitmap64]) -> Self {
+        let mut result = Self::new();
+        for bitmap in bitmaps {
+            result.or_inplace(bitmap);
+        }
+        result
+    }
+
+    /// Sorts the bitmaps to intersect by cardinality
+    ///
+    /// Returns None if the intersection is trivially empty: there are no bitmaps, or the ranges
+    /// of values in the bitmaps do not overlap.
+    #[cfg(feature = "alloc")]
+    fn fast_and_order<'a>(bitmaps: &[&'a Bitmap64]) -> Option<Vec<&'a Bitmap64>> {
+        let mut start = 0;
+        let mut end = u64::MAX;
+        for bitmap in bitmaps {
+            start = start.max(bitmap.minimum()?);
+            end = end.min(bitmap.maximum()?);
+        }
+        if bitmaps.is_empty() || start > end {
+            return None;
+        }
+        let mut bitmaps = bitmaps.to_vec();
+        bitmaps.sort_by_cached_key(|bitmap| bitmap.cardinality());
+        Some(bitmaps)
+    }
+
     /// Computes the union between two bitmaps and returns the result
     ///
     /// # Examples
@@ -1489,7 +2002,21 @@ fn range_to_exclusive<R: RangeBounds<u64>>(range: R) -> Option<ExclusiveRangeRes
     })
 }
 
-fn range_to_inclusive<R: RangeBounds<u64>>(range: R) -> (u64, u64) {
+/// The values which stay within `u64` when shifted by `offset`, if any
+fn offset_kept_range(offset: i128) -> Option<RangeInclusive<u64>> {
+    let max = i128::from(u64::MAX);
+    let (start, end) = if offset >= 0 {
+        (0, max - offset)
+    } else {
+        (offset.checked_neg()?, max)
+    };
+    if start > end {
+        return None;
+    }
+    Some(u64::try_from(start).ok()?..=u64::try_from(end).ok()?)
+}
+
+pub(super) fn range_to_inclusive<R: RangeBounds<u64>>(range: R) -> (u64, u64) {
     let start = match range.start_bound() {
         Bound::Included(&i) => i,
         Bound::Excluded(&i) => match i.checked_add(1) {
@@ -1,6 +1,8 @@
+use super::imp::range_to_inclusive;
 use crate::Bitmap64;
-use core::marker::PhantomData;
-use core::mem::{ManuallyDrop, MaybeUninit};
+use core::iter::FusedIterator;
+use core::mem::ManuallyDrop;
+use core::ops::{RangeBounds, RangeInclusive};
 use core::ptr::NonNull;
 
 impl FromIterator<u64> for Bitmap64 {
@@ -28,10 +30,7 @@ impl FromIterator<u64> for Bitmap64 {
 impl Extend<u64> for Bitmap64 {
     #[doc(alias = "roaring64_bitmap_add_bulk")]
     fn extend<T: IntoIterator<Item = u64>>(&mut self, iter: T) {
-        let mut ctx = MaybeUninit::<ffi::roaring64_bulk_context_t>::zeroed();
-        iter.into_iter().for_each(|value| unsafe {
-            ffi::roaring64_bitmap_add_bulk(self.raw.as_ptr(), ctx.as_mut_ptr(), value);
-        });
+        self.bulk_context_mut().extend(iter);
     }
 }
 
@@ -45,7 +44,7 @@ impl Extend<u64> for Bitmap64 {
 pub struct Bitmap64Cursor<'a> {
     raw: NonNull<ffi::roaring64_iterator_t>,
     has_value: bool,
-    _bitmap: PhantomData<&'a Bitmap64>,
+    bitmap: &'a Bitmap64,
 }
 
 unsafe impl Send for Bitmap64Cursor<'_> {}
@@ -61,24 +60,24 @@ impl Drop for Bitmap64Cursor<'_> {
 }
 
 impl<'a> Bitmap64Cursor<'a> {
-    fn from_raw(raw: *mut ffi::roaring64_iterator_t) -> Self {
+    fn from_raw(raw: *mut ffi::roaring64_iterator_t, bitmap: &'a Bitmap64) -> Self {
         let raw = NonNull::new(raw).expect("Failed to allocate roaring64_iterator_t");
         let has_value = unsafe { ffi::roaring64_iterator_has_value(raw.as_ptr()) };
         Self {
             raw,
             has_value,
-            _bitmap: PhantomData,
+            bitmap,
         }
     }
 
     fn at_first(bitmap: &'a Bitmap64) -> Self {
         let raw = unsafe { ffi::roaring64_iterator_create(bitmap.raw.as_ptr()) };
-        Self::from_raw(raw)
+        Self::from_raw(raw, bitmap)
     }
 
     fn at_last(bitmap: &'a Bitmap64) -> Self {
         let raw = unsafe { ffi::roaring64_iterator_create_last(bitmap.raw.as_ptr()) };
-        Self::from_raw(raw)
+        Self::from_raw(raw, bitmap)
     }
 
     /// Returns true if the cursor is pointing at a value in the bitmap.
@@ -258,7 +257,7 @@ impl<'a> Bitmap64Cursor<'a> {
         // Don't drop `self` and free the iterator
         let this = ManuallyDrop::new(self);
         unsafe { ffi::roaring64_iterator_reinit(bitmap.raw.as_ptr(), this.raw.as_ptr()) };
-        Bitmap64Cursor::from_raw(this.raw.as_ptr())
+        Bitmap64Cursor::from_raw(this.raw.as_ptr(), bitmap)
     }
 
     /// Resets this cursor to the last value in the bitmap.
@@ -282,7 +281,7 @@ impl<'a> Bitmap64Cursor<'a> {
         // Don't drop `self` and free the iterator
         let this = ManuallyDrop::new(self);
         unsafe { ffi::roaring64_iterator_reinit_last(bitmap.raw.as_ptr(), this.raw.as_ptr()) };
-        Bitmap64Cursor::from_raw(this.raw.as_ptr())
+        Bitmap64Cursor::from_raw(this.raw.as_ptr(), bitmap)
     }
 
     /// Attempt to read many values from the iterator into `dst`
@@ -424,35 +423,108 @@ impl<'a> From<Bitmap64Iterator<'a>> for Bitmap64Cursor<'a> {
 /// ```
 impl<'a> From<Bitmap64Cursor<'a>> for Bitmap64Iterator<'a> {
     fn from(cursor: Bitmap64Cursor<'a>) -> Self {
-        Bitmap64Iterator { cursor }
+        Bitmap64Iterator::from_cursor(cursor)
     }
 }
 
 impl<'a> Clone for Bitmap64Cursor<'a> {
     fn clone(&self) -> Self {
         let raw = unsafe { ffi::roaring64_iterator_copy(self.raw.as_ptr()) };
-        Self::from_raw(raw)
+        Self::from_raw(raw, self.bitmap)
     }
 }
 
 /// An iterator over the values in a bitmap
+///
+/// Values can be taken from both ends, and the number of values left is known exactly. It is
+/// counted when the iterator is created, and kept up to date as values are taken. On 64-bit
+/// targets, this also implements [`ExactSizeIterator`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap64;
+///
+/// let bitmap = Bitmap64::of(&[1, 2, 3, 4, u64::MAX]);
+/// let mut iter = bitmap.iter();
+/// assert_eq!(iter.size_hint(), (5, Some(5)));
+/// assert_eq!(iter.next(), Some(1));
+/// assert_eq!(iter.next_back(), Some(u64::MAX));
+/// assert_eq!(iter.size_hint(), (3, Some(3)));
+/// assert_eq!(iter.rev().collect::<Vec<_>>(), [4, 3, 2]);
+///
+/// // The last 2 values
+/// let last: Vec<u64> = bitmap.iter().rev().take(2).collect();
+/// assert_eq!(last, [u64::MAX, 4]);
+/// ```
 #[derive(Debug, Clone)]
 pub struct Bitmap64Iterator<'a> {
     cursor: Bitmap64Cursor<'a>,
+    /// Points at the next value to be returned from the back, only created once values are
+    /// taken from the back
+    back: Option<Bitmap64Cursor<'a>>,
+    /// Number of values between the front and back cursors, inclusive
+    len: u64,
 }
 
 impl<'a> Bitmap64Iterator<'a> {
     fn new(bitmap: &'a Bitmap64) -> Self {
         Self {
             cursor: bitmap.cursor(),
+            back: None,
+            len: bitmap.cardinality(),
+        }
+    }
+
+    fn with_range<R: RangeBounds<u64>>(bitmap: &'a Bitmap64, range: R) -> Self {
+        let (start, end) = range_to_inclusive(range);
+        let mut cursor = bitmap.cursor();
+        cursor.reset_at_or_after(start);
+        let back_cursor = match end.checked_add(1) {
+            Some(after_end) => {
+                let mut back_cursor = cursor.clone();
+                back_cursor.reset_at_or_after(after_end);
+                back_cursor.move_prev();
+                back_cursor
+            }
+            None => bitmap.cursor_to_last(),
+        };
+        let len = if start <= end {
+            bitmap.range_cardinality(start..=end)
+        } else {
+            0
+        };
+        Self {
+            cursor,
+            back: Some(back_cursor),
+            len,
+        }
+    }
+
+    /// Creates an iterator starting at the current position of `cursor`
+    fn from_cursor(cursor: Bitmap64Cursor<'a>) -> Self {
+        let len = match cursor.current() {
+            Some(value) => cursor.bitmap.range_cardinality(value..),
+            None => 0,
+        };
+        Self {
+            cursor,
+            back: None,
+            len,
         }
     }
 
     #[inline]
     fn advance(&mut self) {
+        self.len -= 1;
         self.cursor.move_next();
     }
 
+    fn back_cursor(&mut self) -> &mut Bitmap64Cursor<'a> {
+        let bitmap = self.cursor.bitmap;
+        self.back.get_or_insert_with(|| bitmap.cursor_to_last())
+    }
+
     /// Attempt to read many values from the iterator into `dst`
     ///
     /// Returns the number of items read from the iterator, may be `< dst.len()` iff
@@ -507,7 +579,10 @@ impl<'a> Bitmap64Iterator<'a> {
     #[must_use]
     #[doc(alias = "roaring64_iterator_read")]
     pub fn next_many(&mut self, dst: &mut [u64]) -> usize {
-        self.cursor.read_many(dst)
+        let len = usize::try_from(self.len).map_or(dst.len(), |len| len.min(dst.len()));
+        let n = self.cursor.read_many(&mut dst[..len]);
+        self.len -= n as u64;
+        n
     }
 
     /// Reset the iterator to the first value `>= val`
@@ -537,6 +612,15 @@ impl<'a> Bitmap64Iterator<'a> {
     #[doc(alias = "roaring64_iterator_move_equalorlarger")]
     pub fn reset_at_or_after(&mut self, val: u64) {
         self.cursor.reset_at_or_after(val);
+        let bitmap = self.cursor.bitmap;
+        self.len = match (self.cursor.current(), &self.back) {
+            (Some(front), None) => bitmap.range_cardinality(front..),
+            (Some(front), Some(back)) => match back.current() {
+                Some(last) if front <= last => bitmap.range_cardinality(front..=last),
+                _ => 0,
+            },
+            (None, _) => 0,
+        };
     }
 
     /// Peek at the next value to be returned by the iterator (if any), without consuming it
@@ -553,6 +637,9 @@ impl<'a> Bitmap64Iterator<'a> {
     #[inline]
     #[must_use]
     pub fn peek(&self) -> Option<u64> {
+        if self.len == 0 {
+            return None;
+        }
         self.cursor.current()
     }
 }
@@ -571,8 +658,111 @@ impl<'a> Iterator for Bitmap64Iterator<'a> {
             None => None,
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.len) {
+            Ok(len) => (len, Some(len)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+
+    fn count(self) -> usize {
+        usize::try_from(self.len).expect("count overflowed usize")
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+impl DoubleEndedIterator for Bitmap64Iterator<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let back = self.back_cursor();
+        let value = back.current();
+        back.move_prev();
+        value
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+impl ExactSizeIterator for Bitmap64Iterator<'_> {}
+
+/// Iterator over the maximal runs of consecutive values in a bitmap
+///
+/// Runs are returned in ascending order. See [`Bitmap64::ranges`].
+#[derive(Debug, Clone)]
+pub struct Bitmap64Ranges<'a> {
+    bitmap: &'a Bitmap64,
+    cursor: Bitmap64Cursor<'a>,
+}
+
+impl<'a> Bitmap64Ranges<'a> {
+    /// Past this many values, the end of a run is found by searching, rather than stepping
+    const SCAN_LIMIT: usize = 16;
+
+    fn new(bitmap: &'a Bitmap64) -> Self {
+        Self {
+            bitmap,
+            cursor: bitmap.cursor(),
+        }
+    }
+
+    /// Returns the last value of the run containing `end`
+    ///
+    /// Uses an exponential search, so long runs take a logarithmic number of steps
+    fn run_end(&self, mut end: u64) -> u64 {
+        let is_run = |end: u64, len: u64| match end.checked_add(len) {
+            Some(last) => self.bitmap.range_cardinality(end + 1..=last) == len,
+            None => false,
+        };
+        let mut step = 1;
+        while is_run(end, step) {
+            end += step;
+            match step.checked_mul(2) {
+                Some(next) => step = next,
+                None => break,
+            }
+        }
+        while step > 1 {
+            step /= 2;
+            if is_run(end, step) {
+                end += step;
+            }
+        }
+        end
+    }
 }
 
+impl Iterator for Bitmap64Ranges<'_> {
+    type Item = RangeInclusive<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.cursor.current()?;
+        let mut end = start;
+        for _ in 0..Self::SCAN_LIMIT {
+            match self.cursor.next() {
+                Some(value) if value == end + 1 => end = value,
+                _ => return Some(start..=end),
+            }
+        }
+        end = self.run_end(end);
+        match end.checked_add(1) {
+            Some(next) => self.cursor.reset_at_or_after(next),
+            None => {
+                self.cursor = self.bitmap.cursor_to_last();
+                self.cursor.move_next();
+            }
+        }
+        Some(start..=end)
+    }
+}
+
+impl FusedIterator for Bitmap64Ranges<'_> {}
+
 impl Bitmap64 {
     /// Returns an iterator over the values in the bitmap.
     #[inline]
@@ -581,6 +771,36 @@ impl Bitmap64 {
         Bitmap64Iterator::new(self)
     }
 
+    /// Returns an iterator over the values in the bitmap which are in `range`
+    ///
+    /// The iterator supports everything [`Bitmap64::iter`] does, including
+    /// [`next_many`](Bitmap64Iterator::next_many) and reverse iteration, without ever returning a
+    /// value outside of `range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmap = Bitmap64::of(&[1, 5, 10, 15, 20, u64::MAX]);
+    ///
+    /// assert_eq!(bitmap.iter_range(5..20).collect::<Vec<_>>(), [5, 10, 15]);
+    /// assert_eq!(bitmap.iter_range(5..=20).rev().collect::<Vec<_>>(), [20, 15, 10, 5]);
+    /// assert_eq!(bitmap.iter_range(11..).collect::<Vec<_>>(), [15, 20, u64::MAX]);
+    /// assert_eq!(bitmap.iter_range(..).count(), 6);
+    /// assert_eq!(bitmap.iter_range(2..5).next(), None);
+    ///
+    /// let mut buf = [0; 10];
+    /// let mut iter = bitmap.iter_range(2..=15);
+    /// assert_eq!(iter.next_many(&mut buf), 3);
+    /// assert_eq!(buf[..3], [5, 10, 15]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn iter_range<R: RangeBounds<u64>>(&self, range: R) -> Bitmap64Iterator<'_> {
+        Bitmap64Iterator::with_range(self, range)
+    }
+
     /// Returns a cursor pointing at the first value in the bitmap.
     ///
     /// See [`Bitmap64Cursor`] for more details.
@@ -598,4 +818,31 @@ impl Bitmap64 {
     pub fn cursor_to_last(&self) -> Bitmap64Cursor<'_> {
         Bitmap64Cursor::at_last(self)
     }
+
+    /// Returns an iterator over the maximal runs of consecutive values in the bitmap
+    ///
+    /// Each run is returned as an inclusive range, in ascending order. Long runs are found without
+    /// visiting every value in them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let mut bitmap = Bitmap64::of(&[1, 2, 3, 7, u64::MAX]);
+    /// bitmap.add_range(0xFFFF_FFF0..=0x1_0002_0000);
+    ///
+    /// let ranges: Vec<_> = bitmap.ranges().collect();
+    /// assert_eq!(
+    ///     ranges,
+    ///     [1..=3, 7..=7, 0xFFFF_FFF0..=0x1_0002_0000, u64::MAX..=u64::MAX]
+    /// );
+    ///
+    /// assert_eq!(Bitmap64::from_ranges(ranges), bitmap);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn ranges(&self) -> Bitmap64Ranges<'_> {
+        Bitmap64Ranges::new(self)
+    }
 }
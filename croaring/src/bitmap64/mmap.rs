@@ -0,0 +1,104 @@
+use super::serialization::ViewDeserializer;
+use super::OwnedBitmap64View;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// A frozen view of a 64-bit bitmap, backed by a memory mapped file
+///
+/// See [`OwnedBitmap64View::open`] and [`OwnedBitmap64View::map_range`].
+pub type MmapBitmap64View = OwnedBitmap64View<Mmap>;
+
+impl OwnedBitmap64View<Mmap> {
+    /// Memory map the file at `path`, and view the 64-bit bitmap serialized in it
+    ///
+    /// The whole file must contain exactly one bitmap serialized with the format `S`. The data
+    /// is checked as with [`Bitmap64View::try_deserialize`](crate::Bitmap64View::try_deserialize)
+    /// before the view is returned, so a file with invalid contents returns an error. The
+    /// mapping starts on a page boundary, so it is always aligned for
+    /// [`Frozen`](crate::Frozen) data.
+    ///
+    /// Errors opening or mapping the file are returned as is, and invalid data is returned as an
+    /// error converted from a [`DeserializeError`](crate::DeserializeError).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap64, Frozen, OwnedBitmap64View};
+    ///
+    /// let mut bitmap = Bitmap64::of(&[1, 2, 3, 1 << 40]);
+    /// bitmap.shrink_to_fit();
+    /// let name = format!("croaring-doc-open64-{}.bin", std::process::id());
+    /// let path = std::env::temp_dir().join(name);
+    /// let mut buf = Vec::new();
+    /// std::fs::write(&path, bitmap.serialize_into_vec::<Frozen>(&mut buf)).unwrap();
+    ///
+    /// let view = unsafe { OwnedBitmap64View::open::<Frozen>(&path) }.unwrap();
+    /// assert_eq!(view, bitmap);
+    /// # drop(view);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other process, while the view
+    /// is alive. See [`Mmap::map`].
+    pub unsafe fn open<S: ViewDeserializer>(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let mmap = Mmap::map(&file)?;
+        Ok(Self::try_deserialize::<S>(mmap)?)
+    }
+
+    /// Memory map `len` bytes of `file` starting at `offset`, and view the 64-bit bitmap
+    /// serialized there
+    ///
+    /// This allows viewing a bitmap stored inside a larger file. The range must contain exactly
+    /// one bitmap serialized with the format `S`, and is checked as with
+    /// [`Bitmap64View::try_deserialize`](crate::Bitmap64View::try_deserialize).
+    ///
+    /// The mapped data is only aligned as far as `offset` is: for [`Frozen`](crate::Frozen)
+    /// data, `offset` must be a multiple of 64, the alignment required for 64-bit frozen bitmaps,
+    /// or a [`DeserializeError::Misaligned`](crate::DeserializeError::Misaligned) error is
+    /// returned. A range which extends past the end of the file returns an
+    /// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap64, Frozen, OwnedBitmap64View};
+    /// use std::fs::File;
+    ///
+    /// let mut bitmap = Bitmap64::of(&[1, 2, 3, 1 << 40]);
+    /// bitmap.shrink_to_fit();
+    /// let mut buf = Vec::new();
+    /// let frozen = bitmap.serialize_into_vec::<Frozen>(&mut buf);
+    /// let mut data = vec![0; 64];
+    /// data.extend_from_slice(frozen);
+    /// let name = format!("croaring-doc-map-range64-{}.bin", std::process::id());
+    /// let path = std::env::temp_dir().join(name);
+    /// std::fs::write(&path, &data).unwrap();
+    ///
+    /// let file = File::open(&path).unwrap();
+    /// let view = unsafe { OwnedBitmap64View::map_range::<Frozen>(&file, 64, frozen.len()) };
+    /// assert_eq!(view.unwrap(), bitmap);
+    ///
+    /// // Frozen data must be aligned within the file
+    /// let view = unsafe { OwnedBitmap64View::map_range::<Frozen>(&file, 32, frozen.len()) };
+    /// assert!(view.is_err());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other process, while the view
+    /// is alive. See [`Mmap::map`].
+    pub unsafe fn map_range<S: ViewDeserializer>(
+        file: &File,
+        offset: u64,
+        len: usize,
+    ) -> io::Result<Self> {
+        let mmap = crate::mmap::map_range(file, offset, len)?;
+        Ok(Self::try_deserialize::<S>(mmap)?)
+    }
+}
@@ -1,14 +1,35 @@
 //! A compressed bitmap which can hold 64-bit integers
 
-pub use self::iter::{Bitmap64Cursor, Bitmap64Iterator};
+pub use self::iter::{Bitmap64Cursor, Bitmap64Iterator, Bitmap64Ranges};
 use core::marker::PhantomData;
 
+mod bulk;
+#[cfg(feature = "alloc")]
+mod containers;
 mod imp;
 mod iter;
+#[cfg(feature = "mmap")]
+mod mmap;
 mod ops;
+#[cfg(feature = "alloc")]
+mod owned_view;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "serde")]
+mod serde;
 mod serialization;
+mod split;
 mod view;
 
+pub use self::bulk::Bitmap64BulkContext;
+#[cfg(feature = "alloc")]
+pub use self::containers::{Bitmap64ContainerInfo, Bitmap64Containers, ContainerType};
+#[cfg(feature = "mmap")]
+pub use self::mmap::MmapBitmap64View;
+#[cfg(feature = "alloc")]
+pub use self::owned_view::OwnedBitmap64View;
+#[cfg(feature = "rayon")]
+pub use self::par::Bitmap64ParIter;
 pub use self::serialization::{Deserializer, Serializer};
 
 /// A Bitmap which can hold 64-bit integers
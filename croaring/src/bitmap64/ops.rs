@@ -1,6 +1,11 @@
 use crate::{Bitmap, Bitmap64};
+use core::cmp::Ordering;
 use core::fmt;
-use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+use core::hash::{Hash, Hasher};
+use core::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Shl, ShlAssign, Shr, ShrAssign,
+    Sub, SubAssign,
+};
 use ffi::roaring64_bitmap_copy;
 
 impl fmt::Debug for Bitmap64 {
@@ -69,6 +74,67 @@ impl PartialEq for Bitmap64 {
 
 impl Eq for Bitmap64 {}
 
+impl Hash for Bitmap64 {
+    /// Hashes the values in the bitmap
+    ///
+    /// The hash does not depend on the internal representation of the bitmap (e.g. whether
+    /// [`Bitmap64::run_optimize`] has been called), and a [`Bitmap64View`](crate::Bitmap64View)
+    /// hashes the same as an equal `Bitmap64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    /// use std::hash::BuildHasher;
+    ///
+    /// let hasher = std::collections::hash_map::RandomState::new();
+    /// let bitmap1 = Bitmap64::from_range(0..10_000);
+    /// let mut bitmap2 = bitmap1.clone();
+    /// bitmap2.run_optimize();
+    ///
+    /// assert_eq!(hasher.hash_one(&bitmap1), hasher.hash_one(&bitmap2));
+    /// ```
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.cardinality().hash(state);
+        let mut iter = self.iter();
+        let mut buffer = [0; 256];
+        loop {
+            let count = iter.next_many(&mut buffer);
+            if count == 0 {
+                break;
+            }
+            u64::hash_slice(&buffer[..count], state);
+        }
+    }
+}
+
+impl PartialOrd for Bitmap64 {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Bitmap64 {
+    /// Compares bitmaps lexicographically by their values in sorted order
+    ///
+    /// This matches the ordering of a `BTreeSet` of the same values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// assert!(Bitmap64::of(&[1, 2]) < Bitmap64::of(&[1, 3]));
+    /// assert!(Bitmap64::of(&[1, 2]) < Bitmap64::of(&[1, 2, 3]));
+    /// assert!(Bitmap64::of(&[2]) > Bitmap64::of(&[1, 3]));
+    /// assert!(Bitmap64::new() < Bitmap64::of(&[0]));
+    /// ```
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
 impl Clone for Bitmap64 {
     #[inline]
     #[doc(alias = "roaring64_bitmap_copy")]
@@ -398,3 +464,105 @@ impl_binop_assign! {
         fn sub_assign as andnot_inplace
     }
 }
+
+/// Shifts all values up by `rhs`, dropping any which would be `> u64::MAX`
+///
+/// See [`Bitmap64::add_offset`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap64;
+///
+/// let bitmap = Bitmap64::of(&[0, 1, u64::MAX]);
+/// assert_eq!((&bitmap << 2).iter().collect::<Vec<_>>(), [2, 3]);
+/// assert_eq!((bitmap << (1 << 40)).iter().collect::<Vec<_>>(), [1 << 40, (1 << 40) + 1]);
+/// ```
+impl Shl<u64> for &Bitmap64 {
+    type Output = Bitmap64;
+
+    #[inline]
+    fn shl(self, rhs: u64) -> Bitmap64 {
+        self.add_offset(i128::from(rhs))
+    }
+}
+
+impl Shl<u64> for Bitmap64 {
+    type Output = Bitmap64;
+
+    #[inline]
+    fn shl(self, rhs: u64) -> Bitmap64 {
+        &self << rhs
+    }
+}
+
+/// Shifts all values down by `rhs`, dropping any which would be `< 0`
+///
+/// See [`Bitmap64::add_offset`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap64;
+///
+/// let bitmap = Bitmap64::of(&[0, 1, u64::MAX]);
+/// assert_eq!((&bitmap >> 1).iter().collect::<Vec<_>>(), [0, u64::MAX - 1]);
+/// assert_eq!((bitmap >> u64::MAX).iter().collect::<Vec<_>>(), [0]);
+/// ```
+impl Shr<u64> for &Bitmap64 {
+    type Output = Bitmap64;
+
+    #[inline]
+    fn shr(self, rhs: u64) -> Bitmap64 {
+        self.add_offset(-i128::from(rhs))
+    }
+}
+
+impl Shr<u64> for Bitmap64 {
+    type Output = Bitmap64;
+
+    #[inline]
+    fn shr(self, rhs: u64) -> Bitmap64 {
+        &self >> rhs
+    }
+}
+
+/// Shifts all values up by `rhs` in place, dropping any which would be `> u64::MAX`
+///
+/// See [`Bitmap64::add_offset_inplace`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap64;
+///
+/// let mut bitmap = Bitmap64::of(&[0, 1, u64::MAX]);
+/// bitmap <<= 2;
+/// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [2, 3]);
+/// ```
+impl ShlAssign<u64> for Bitmap64 {
+    #[inline]
+    fn shl_assign(&mut self, rhs: u64) {
+        self.add_offset_inplace(i128::from(rhs));
+    }
+}
+
+/// Shifts all values down by `rhs` in place, dropping any which would be `< 0`
+///
+/// See [`Bitmap64::add_offset_inplace`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap64;
+///
+/// let mut bitmap = Bitmap64::of(&[0, 1, u64::MAX]);
+/// bitmap >>= 1;
+/// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [0, u64::MAX - 1]);
+/// ```
+impl ShrAssign<u64> for Bitmap64 {
+    #[inline]
+    fn shr_assign(&mut self, rhs: u64) {
+        self.add_offset_inplace(-i128::from(rhs));
+    }
+}
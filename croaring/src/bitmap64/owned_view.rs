@@ -0,0 +1,240 @@
+use super::serialization::ViewDeserializer;
+use super::{Bitmap64, Bitmap64View};
+use crate::owned_buffer::{OwnedBuffer, SharedBuffer};
+use crate::DeserializeError;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+/// A frozen view of a 64-bit bitmap, which owns the buffer backing it
+///
+/// This is the 64-bit equivalent of [`OwnedBitmapView`](crate::OwnedBitmapView): it keeps its
+/// buffer alive itself, so unlike a [`Bitmap64View`], it can be stored alongside other owned
+/// data.
+///
+/// All read-only methods for [`Bitmap64`] are also usable on an `OwnedBitmap64View`.
+///
+/// An `OwnedBitmap64View` is [`Send`] and [`Sync`] if its buffer is. As with
+/// [`OwnedBitmapView`](crate::OwnedBitmapView), views of a [`SharedBuffer`](crate::SharedBuffer)
+/// are [`Clone`], and views of other buffers can be cloned with [`try_clone`](Self::try_clone).
+///
+/// # Examples
+///
+/// ```
+/// use croaring::{Bitmap64, Frozen, OwnedBitmap64View};
+///
+/// // Frozen bitmaps must be aligned, and may be stored inline in the buffer
+/// #[repr(C, align(64))]
+/// struct AlignedBuf {
+///     data: [u8; 1024],
+///     len: usize,
+/// }
+///
+/// impl AsRef<[u8]> for AlignedBuf {
+///     fn as_ref(&self) -> &[u8] {
+///         &self.data[..self.len]
+///     }
+/// }
+///
+/// let mut bitmap = Bitmap64::of(&[1, 2, 3, 1 << 40]);
+/// bitmap.shrink_to_fit();
+/// let mut buf = AlignedBuf { data: [0; 1024], len: 0 };
+/// buf.len = bitmap.try_serialize_into::<Frozen>(&mut buf.data).unwrap().len();
+///
+/// let view = unsafe { OwnedBitmap64View::deserialize::<Frozen>(buf) }.unwrap();
+/// assert_eq!(view, bitmap);
+/// ```
+pub struct OwnedBitmap64View<B> {
+    // Declared before the buffer, so it is dropped before the data it borrows
+    view: Bitmap64View<'static>,
+    // Recreate the view for a clone of the buffer
+    deserialize: unsafe fn(&[u8]) -> Option<Bitmap64View<'_>>,
+    try_deserialize: fn(&[u8]) -> Result<Bitmap64View<'_>, DeserializeError>,
+    buffer: OwnedBuffer<B>,
+}
+
+/// [`Bitmap64View::deserialize`], for any lifetime of `data`
+unsafe fn deserialize_view<S: ViewDeserializer>(data: &[u8]) -> Option<Bitmap64View<'_>> {
+    Bitmap64View::deserialize::<S>(data)
+}
+
+impl<B: AsRef<[u8]>> OwnedBitmap64View<B> {
+    /// Create a view of a bitmap serialized in `buffer`, checking that it is valid
+    ///
+    /// See [`Bitmap64View::try_deserialize`] for the checks performed. `buffer` is dropped if the
+    /// data is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{DeserializeError, Frozen, OwnedBitmap64View};
+    ///
+    /// let err = OwnedBitmap64View::try_deserialize::<Frozen>(vec![1, 2, 3]).unwrap_err();
+    /// assert!(matches!(
+    ///     err,
+    ///     DeserializeError::Truncated { .. } | DeserializeError::Misaligned { .. }
+    /// ));
+    /// ```
+    pub fn try_deserialize<S: ViewDeserializer>(buffer: B) -> Result<Self, DeserializeError> {
+        let buffer = OwnedBuffer::new(buffer);
+        // SAFETY: the view is dropped before the buffer
+        let view = Bitmap64View::try_deserialize::<S>(unsafe { buffer.bytes_unbounded() })?;
+        Ok(Self {
+            view,
+            deserialize: deserialize_view::<S>,
+            try_deserialize: S::try_deserialize_view,
+            buffer,
+        })
+    }
+
+    /// Create a view of a bitmap serialized in `buffer` without copying
+    ///
+    /// As with [`Bitmap64View::deserialize`], returns `None` if the data is found not to be a
+    /// valid bitmap, but this is only done on a best-effort basis. `buffer` is dropped if `None` is
+    /// returned.
+    ///
+    /// See [`OwnedBitmap64View`] for examples.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must be the result of serializing a bitmap with the same serialization format
+    #[must_use]
+    pub unsafe fn deserialize<S: ViewDeserializer>(buffer: B) -> Option<Self> {
+        let buffer = OwnedBuffer::new(buffer);
+        let view = Bitmap64View::deserialize::<S>(buffer.bytes_unbounded())?;
+        Some(Self {
+            view,
+            deserialize: deserialize_view::<S>,
+            try_deserialize: S::try_deserialize_view,
+            buffer,
+        })
+    }
+
+    /// Clone the buffer, and view the bitmap in the clone, checking that it is valid
+    ///
+    /// The data of the cloned buffer is checked as by [`Self::try_deserialize`], so this fails
+    /// if the clone does not hold the same bitmap, or if its data is not aligned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap64, Frozen, OwnedBitmap64View};
+    ///
+    /// // Clones of an aligned buffer are aligned too
+    /// #[derive(Clone)]
+    /// #[repr(C, align(64))]
+    /// struct AlignedBuf {
+    ///     data: [u8; 1024],
+    ///     len: usize,
+    /// }
+    ///
+    /// impl AsRef<[u8]> for AlignedBuf {
+    ///     fn as_ref(&self) -> &[u8] {
+    ///         &self.data[..self.len]
+    ///     }
+    /// }
+    ///
+    /// let mut bitmap = Bitmap64::of(&[1, 2, 3, 1 << 40]);
+    /// bitmap.shrink_to_fit();
+    /// let mut buf = AlignedBuf { data: [0; 1024], len: 0 };
+    /// buf.len = bitmap.try_serialize_into::<Frozen>(&mut buf.data).unwrap().len();
+    ///
+    /// let view = OwnedBitmap64View::try_deserialize::<Frozen>(buf).unwrap();
+    /// let clone = view.try_clone().unwrap();
+    /// assert_eq!(clone, bitmap);
+    /// ```
+    pub fn try_clone(&self) -> Result<Self, DeserializeError>
+    where
+        B: Clone,
+    {
+        let buffer = OwnedBuffer::new(self.buffer.get().clone());
+        // SAFETY: the view is dropped before the buffer
+        let view = (self.try_deserialize)(unsafe { buffer.bytes_unbounded() })?;
+        Ok(Self {
+            view,
+            deserialize: self.deserialize,
+            try_deserialize: self.try_deserialize,
+            buffer,
+        })
+    }
+
+    /// The view of the bitmap
+    #[inline]
+    #[must_use]
+    pub fn as_view(&self) -> &Bitmap64View<'_> {
+        &self.view
+    }
+
+    /// The buffer backing the view
+    #[inline]
+    #[must_use]
+    pub fn buffer(&self) -> &B {
+        self.buffer.get()
+    }
+
+    /// Drop the view, returning the buffer which backed it
+    #[must_use]
+    pub fn into_buffer(self) -> B {
+        let Self { view, buffer, .. } = self;
+        drop(view);
+        buffer.into_inner()
+    }
+}
+
+impl<B> Deref for OwnedBitmap64View<B> {
+    type Target = Bitmap64;
+
+    #[inline]
+    fn deref(&self) -> &Bitmap64 {
+        &self.view
+    }
+}
+
+impl<B: SharedBuffer> Clone for OwnedBitmap64View<B> {
+    /// Views the bitmap in a clone of the buffer, which shares the data of this view
+    fn clone(&self) -> Self {
+        let buffer = OwnedBuffer::new(self.buffer.get().clone());
+        // SAFETY: the clone shares the bytes which were viewed by `self.view`, at the same
+        // address, and they cannot be modified while shared
+        let view = unsafe { (self.deserialize)(buffer.bytes_unbounded()) };
+        Self {
+            view: view.unwrap_or_else(|| unreachable!("the same bytes were viewed before")),
+            deserialize: self.deserialize,
+            try_deserialize: self.try_deserialize,
+            buffer,
+        }
+    }
+}
+
+impl<B> PartialEq for OwnedBitmap64View<B> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<B> PartialEq<Bitmap64> for OwnedBitmap64View<B> {
+    fn eq(&self, other: &Bitmap64) -> bool {
+        **self == *other
+    }
+}
+
+impl<B> PartialEq<OwnedBitmap64View<B>> for Bitmap64 {
+    fn eq(&self, other: &OwnedBitmap64View<B>) -> bool {
+        *self == **other
+    }
+}
+
+impl<B> Eq for OwnedBitmap64View<B> {}
+
+impl<B> Hash for OwnedBitmap64View<B> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl<B> fmt::Debug for OwnedBitmap64View<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
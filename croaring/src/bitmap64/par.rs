@@ -0,0 +1,243 @@
+use super::Bitmap64;
+use crate::par::tree_reduce;
+use core::ops::RangeInclusive;
+use rayon::iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer};
+use rayon::iter::{FromParallelIterator, IntoParallelIterator, ParallelIterator};
+
+impl Bitmap64 {
+    /// Computes the union of many bitmaps in parallel
+    ///
+    /// Groups of bitmaps are combined on separate threads, and the results are then merged
+    /// pairwise, also in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmaps = (0..100).map(|i| Bitmap64::of(&[i, i << 40])).collect::<Vec<_>>();
+    /// let refs = bitmaps.iter().collect::<Vec<_>>();
+    ///
+    /// let union = Bitmap64::par_fast_or(&refs);
+    /// assert_eq!(union.cardinality(), 199);
+    /// assert!(Bitmap64::par_fast_or(&[]).is_empty());
+    /// ```
+    #[must_use]
+    #[doc(alias = "par_or")]
+    pub fn par_fast_or(bitmaps: &[&Bitmap64]) -> Self {
+        if bitmaps.is_empty() {
+            return Self::new();
+        }
+        tree_reduce(
+            bitmaps,
+            &|group: &[&Bitmap64]| {
+                let mut result = group[0].clone();
+                for bitmap in &group[1..] {
+                    result.or_inplace(bitmap);
+                }
+                result
+            },
+            &|mut lhs, rhs| {
+                lhs.or_inplace(&rhs);
+                lhs
+            },
+        )
+    }
+
+    /// Computes the intersection of many bitmaps in parallel
+    ///
+    /// Groups of bitmaps are combined with [`Bitmap64::fast_and`] on separate threads, and the
+    /// results are then merged pairwise, also in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmaps = (0..100).map(|i| Bitmap64::of(&[i, 500, 1 << 40])).collect::<Vec<_>>();
+    /// let refs = bitmaps.iter().collect::<Vec<_>>();
+    ///
+    /// let intersection = Bitmap64::par_fast_and(&refs);
+    /// assert_eq!(intersection.iter().collect::<Vec<_>>(), [500, 1 << 40]);
+    /// assert!(Bitmap64::par_fast_and(&[]).is_empty());
+    /// ```
+    #[must_use]
+    #[doc(alias = "par_and")]
+    pub fn par_fast_and(bitmaps: &[&Bitmap64]) -> Self {
+        if bitmaps.is_empty() {
+            return Self::new();
+        }
+        tree_reduce(bitmaps, &Self::fast_and, &|mut lhs, rhs| {
+            lhs.and_inplace(&rhs);
+            lhs
+        })
+    }
+
+    /// Computes the symmetric difference of many bitmaps in parallel
+    ///
+    /// Groups of bitmaps are combined on separate threads, and the results are then merged
+    /// pairwise, also in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let bitmaps = (0..100).map(|i| Bitmap64::of(&[i, 1 << 40])).collect::<Vec<_>>();
+    /// let refs = bitmaps.iter().collect::<Vec<_>>();
+    ///
+    /// let difference = Bitmap64::par_fast_xor(&refs);
+    /// assert_eq!(difference, Bitmap64::from_range(0..100));
+    /// assert!(Bitmap64::par_fast_xor(&[]).is_empty());
+    /// ```
+    #[must_use]
+    #[doc(alias = "par_xor")]
+    pub fn par_fast_xor(bitmaps: &[&Bitmap64]) -> Self {
+        if bitmaps.is_empty() {
+            return Self::new();
+        }
+        tree_reduce(
+            bitmaps,
+            &|group: &[&Bitmap64]| {
+                let mut result = group[0].clone();
+                for bitmap in &group[1..] {
+                    result.xor_inplace(bitmap);
+                }
+                result
+            },
+            &|mut lhs, rhs| {
+                lhs.xor_inplace(&rhs);
+                lhs
+            },
+        )
+    }
+}
+
+/// A parallel iterator over the values of a [`Bitmap64`]
+///
+/// The bitmap is split between threads on container boundaries, near the median value of each
+/// part, so each container is iterated by a single thread.
+///
+/// Returned by `par_iter()` from [`rayon::iter::IntoParallelRefIterator`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap64;
+/// use rayon::prelude::*;
+///
+/// let bitmap = Bitmap64::from_range(0..0x10_0000);
+/// let sum: u64 = bitmap.par_iter().sum();
+/// assert_eq!(sum, (0..0x10_0000u64).sum());
+/// ```
+#[derive(Clone)]
+pub struct Bitmap64ParIter<'a> {
+    bitmap: &'a Bitmap64,
+}
+
+impl ParallelIterator for Bitmap64ParIter<'_> {
+    type Item = u64;
+
+    fn drive_unindexed<C: UnindexedConsumer<u64>>(self, consumer: C) -> C::Result {
+        let producer = RangeProducer {
+            bitmap: self.bitmap,
+            range: 0..=u64::MAX,
+            len: self.bitmap.cardinality(),
+        };
+        bridge_unindexed(producer, consumer)
+    }
+}
+
+impl<'a> IntoParallelIterator for &'a Bitmap64 {
+    type Iter = Bitmap64ParIter<'a>;
+    type Item = u64;
+
+    #[inline]
+    fn into_par_iter(self) -> Self::Iter {
+        Bitmap64ParIter { bitmap: self }
+    }
+}
+
+/// Produces the values of a range of a bitmap, holding `len` values
+struct RangeProducer<'a> {
+    bitmap: &'a Bitmap64,
+    range: RangeInclusive<u64>,
+    len: u64,
+}
+
+impl RangeProducer<'_> {
+    /// Finds a container boundary which splits the values of the range roughly in half
+    fn split_point(&self) -> Option<(u64, u64)> {
+        if self.len < 2 {
+            return None;
+        }
+        let (start, end) = (*self.range.start(), *self.range.end());
+        let before = start.checked_sub(1).map_or(0, |v| self.bitmap.rank(v));
+        let median = self.bitmap.select(before + self.len / 2)?;
+        // Prefer the start of the median's container, or else the start of the next one
+        [median & !0xFFFF, (median | 0xFFFF).wrapping_add(1)]
+            .into_iter()
+            .filter(|&at| start < at && at <= end)
+            .map(|at| (at, self.bitmap.range_cardinality(start..at)))
+            .find(|&(_, left_len)| left_len != 0 && left_len != self.len)
+    }
+}
+
+impl UnindexedProducer for RangeProducer<'_> {
+    type Item = u64;
+
+    fn split(self) -> (Self, Option<Self>) {
+        let Some((at, left_len)) = self.split_point() else {
+            return (self, None);
+        };
+        let right = RangeProducer {
+            bitmap: self.bitmap,
+            range: at..=*self.range.end(),
+            len: self.len - left_len,
+        };
+        let left = RangeProducer {
+            bitmap: self.bitmap,
+            range: *self.range.start()..=at - 1,
+            len: left_len,
+        };
+        (left, Some(right))
+    }
+
+    fn fold_with<F: Folder<u64>>(self, folder: F) -> F {
+        if self.len == 0 {
+            return folder;
+        }
+        folder.consume_iter(self.bitmap.iter_range(self.range))
+    }
+}
+
+/// Collects values from a parallel iterator
+///
+/// Each thread builds a separate bitmap, and these are then merged.
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Bitmap64;
+/// use rayon::prelude::*;
+///
+/// let bitmap: Bitmap64 = (0..100_000u64).into_par_iter().map(|i| i << 20).collect();
+/// assert_eq!(bitmap.cardinality(), 100_000);
+///
+/// let halved: Bitmap64 = bitmap.par_iter().map(|i| i >> 1).collect();
+/// assert_eq!(halved.maximum(), Some(99_999 << 19));
+/// ```
+impl FromParallelIterator<u64> for Bitmap64 {
+    fn from_par_iter<I: IntoParallelIterator<Item = u64>>(par_iter: I) -> Self {
+        par_iter
+            .into_par_iter()
+            .fold(Bitmap64::new, |mut bitmap, value| {
+                bitmap.add(value);
+                bitmap
+            })
+            .reduce(Bitmap64::new, |mut lhs, rhs| {
+                lhs.or_inplace(&rhs);
+                lhs
+            })
+    }
+}
@@ -1,6 +1,21 @@
-use crate::{Bitmap64, Frozen, Portable};
+use crate::serialization::{
+    clickhouse_size, parse_clickhouse, write_clickhouse_header, ByteSource, ClickHouseState,
+    DeserializeError, SliceSource, CLICKHOUSE_SMALL_SET_SIZE,
+};
+use crate::{Bitmap64, Bitmap64View, ClickHouse, Frozen, JvmLegacy, Native, Portable};
 use core::ffi::c_char;
 use core::num::NonZeroUsize;
+#[cfg(feature = "std")]
+use {
+    crate::serialization::ReaderSource,
+    std::io::{Read, Write},
+};
+#[cfg(feature = "alloc")]
+use {alloc::vec::Vec, core::ops::Range};
+
+// The kinds of data in the native format, matching those of 32-bit bitmaps
+const SERIALIZATION_ARRAY_UINT64: u8 = 1;
+const SERIALIZATION_CONTAINER: u8 = 2;
 
 /// Trait for different formats of bitmap64 serialization
 pub trait Serializer: crate::sealed::Sealed {
@@ -34,6 +49,21 @@ pub trait Serializer: crate::sealed::Sealed {
         &mut dst[data_start..]
     }
 
+    /// Serialize a bitmap into a writer
+    ///
+    /// Returns the number of bytes written. No padding is written before the serialized data, so
+    /// formats which require alignment ([Frozen][crate::Frozen]) must be aligned by the reader.
+    ///
+    /// By default, the bitmap is serialized into a temporary buffer, which is then written.
+    #[doc(hidden)]
+    #[cfg(feature = "std")]
+    fn serialize_into_writer<W: Write>(bitmap: &Bitmap64, mut dst: W) -> std::io::Result<usize> {
+        let mut buf = Vec::new();
+        let serialized = Self::serialize_into_vec(bitmap, &mut buf);
+        dst.write_all(serialized)?;
+        Ok(serialized.len())
+    }
+
     #[doc(hidden)]
     fn try_serialize_into_aligned<'a>(
         bitmap: &Bitmap64,
@@ -108,9 +138,29 @@ pub trait Deserializer: crate::sealed::Sealed {
     /// Returns the number of bytes in the buffer which are part of the serialized bitmap, or `None` if
     /// the buffer does not start with a valid serialized bitmap.
     fn find_end(buffer: &[u8]) -> Option<NonZeroUsize>;
+
+    /// Deserialize a bitmap from the beginning of the provided buffer, describing any failure
+    ///
+    /// The [`Bitmap64::try_deserialize_prefix`] method should usually be used instead of this method
+    /// directly.
+    ///
+    /// Returns the bitmap, and the number of bytes it was deserialized from. Unlike
+    /// [`Self::try_deserialize`], the error describes why the buffer could not be deserialized.
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Bitmap64, usize), DeserializeError>;
+
+    /// Deserialize a bitmap from a reader
+    ///
+    /// Only the bytes of the serialized bitmap are read from `src`, as determined by its header.
+    #[doc(hidden)]
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R: Read>(src: R) -> std::io::Result<Bitmap64>;
 }
 
 pub trait ViewDeserializer: crate::sealed::Sealed {
+    /// The alignment required for the start of the viewed data
+    #[doc(hidden)]
+    const REQUIRED_ALIGNMENT: usize = 1;
+
     /// Create a bitmap64 view using the passed data
     ///
     /// # Safety
@@ -121,6 +171,12 @@ pub trait ViewDeserializer: crate::sealed::Sealed {
     /// See [`Bitmap64View::deserialize`] for examples.
     #[doc(hidden)]
     unsafe fn deserialize_view(data: &[u8]) -> *mut ffi::roaring64_bitmap_t;
+
+    /// Create a bitmap64 view using the passed data, checking that it is a valid bitmap
+    ///
+    /// See [`Bitmap64View::try_deserialize`] for examples.
+    #[doc(hidden)]
+    fn try_deserialize_view(data: &[u8]) -> Result<Bitmap64View<'_>, DeserializeError>;
 }
 
 impl Serializer for Portable {
@@ -136,6 +192,82 @@ impl Serializer for Portable {
             ffi::roaring64_bitmap_portable_serialize(bitmap.raw.as_ptr(), dst);
         }
     }
+
+    /// Writes the bucket count, then serializes and writes one bucket at a time, so only the
+    /// largest bucket is buffered
+    #[cfg(feature = "std")]
+    fn serialize_into_writer<W: Write>(bitmap: &Bitmap64, mut dst: W) -> std::io::Result<usize> {
+        write_portable(bitmap, &mut dst)
+    }
+}
+
+/// Writes `bitmap` in the portable format, returning the number of bytes written
+///
+/// The buckets of a 64-bit bitmap are not accessible on their own, so each is copied out with
+/// [`copy_bucket`] and serialized by CRoaring. If that would convert a run container, the whole
+/// bitmap is serialized into a temporary buffer instead, keeping the output identical to
+/// [`Serializer::serialize_into_vec`].
+#[cfg(feature = "std")]
+fn write_portable<W: Write + ?Sized>(bitmap: &Bitmap64, dst: &mut W) -> std::io::Result<usize> {
+    let len = Portable::get_serialized_size_in_bytes(bitmap);
+    let run_containers = bitmap.statistics().n_run_containers;
+    if run_containers != 0 {
+        let copied_run_containers = bucket_starts(bitmap)
+            .map(|first| copy_bucket(bitmap, first).statistics().n_run_containers)
+            .sum::<u64>();
+        if copied_run_containers != run_containers {
+            let mut buf = Vec::new();
+            dst.write_all(Portable::serialize_into_vec(bitmap, &mut buf))?;
+            return Ok(len);
+        }
+    }
+
+    let buckets = u64::try_from(bucket_starts(bitmap).count()).unwrap();
+    dst.write_all(&buckets.to_ne_bytes())?;
+    let mut buf = Vec::new();
+    for first in bucket_starts(bitmap) {
+        buf.clear();
+        let serialized = Portable::serialize_into_vec(&copy_bucket(bitmap, first), &mut buf);
+        // Skip the bucket count of 1, leaving the bucket's key and 32-bit bitmap
+        dst.write_all(&serialized[size_of::<u64>()..])?;
+    }
+    Ok(len)
+}
+
+/// Returns the first value of each bucket of `bitmap` (the values sharing their high 32 bits)
+#[cfg(feature = "std")]
+fn bucket_starts(bitmap: &Bitmap64) -> impl Iterator<Item = u64> + '_ {
+    let mut cursor = Some(bitmap.cursor());
+    core::iter::from_fn(move || {
+        let current = cursor.as_mut()?;
+        let first = current.current()?;
+        match (first | u64::from(u32::MAX)).checked_add(1) {
+            Some(next) => current.reset_at_or_after(next),
+            None => cursor = None,
+        }
+        Some(first)
+    })
+}
+
+/// Copies the bucket of `bitmap` starting at `first` into a new bitmap
+///
+/// The bitmap is intersected with a full container at each container of the bucket. This copies
+/// array and bitset containers unchanged, but converts run containers which are larger than their
+/// array or bitset form, as [`Bitmap64::run_optimize`] would.
+#[cfg(feature = "std")]
+fn copy_bucket(bitmap: &Bitmap64, first: u64) -> Bitmap64 {
+    let mut cursor = bitmap.cursor();
+    cursor.reset_at_or_after(first);
+    let mut mask = Bitmap64::new();
+    while let Some(value) = cursor.current().filter(|value| value >> 32 == first >> 32) {
+        let container_end = value | 0xFFFF;
+        mask.add_range(value & !0xFFFF..=container_end);
+        match container_end.checked_add(1) {
+            Some(next) => cursor.reset_at_or_after(next),
+            None => break,
+        }
+    }
+    bitmap.and(&mask)
 }
 
 impl Deserializer for Portable {
@@ -171,6 +303,332 @@ impl Deserializer for Portable {
         };
         NonZeroUsize::new(end)
     }
+
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Bitmap64, usize), DeserializeError> {
+        let mut src = SliceSource::new(buffer);
+        parse_portable(&mut src)?;
+        let len = src.position();
+        let raw =
+            unsafe { ffi::roaring64_bitmap_portable_deserialize_safe(buffer.as_ptr().cast(), len) };
+        if raw.is_null() {
+            // The headers have already been parsed, so CRoaring must have rejected a container
+            return Err(DeserializeError::InvalidContainer {
+                reason: "container data is inconsistent with its header",
+            });
+        }
+        let bitmap = unsafe { Bitmap64::take_heap(raw) };
+        bitmap
+            .internal_validate()
+            .map_err(|reason| DeserializeError::InvalidContainer { reason })?;
+        Ok((bitmap, len))
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R: Read>(mut src: R) -> std::io::Result<Bitmap64> {
+        let mut buf = Vec::new();
+        parse_portable(&mut ReaderSource::new(&mut src, &mut buf))?;
+        Ok(Self::try_deserialize_prefix(&buf)?.0)
+    }
+}
+
+/// Consumes a bitmap in the portable format from `src`
+///
+/// This is the number of buckets, then each bucket's key and 32-bit portable bitmap. Only the
+/// headers are checked, as with [`crate::bitmap::parse_portable`].
+fn parse_portable<S: ByteSource + ?Sized>(src: &mut S) -> Result<(), S::Error> {
+    let buckets = u64::from_ne_bytes(src.take_array()?);
+    for _ in 0..buckets {
+        src.take_array::<4>()?;
+        crate::bitmap::parse_portable(src)?;
+    }
+    Ok(())
+}
+
+impl Serializer for Native {
+    /// Computes the serialized size in bytes of the Bitmap in native format.
+    /// See [`Bitmap64::get_serialized_size_in_bytes`] for examples.
+    fn get_serialized_size_in_bytes(bitmap: &Bitmap64) -> usize {
+        1 + native_array_len(bitmap)
+            .unwrap_or_else(|| <Portable as Serializer>::get_serialized_size_in_bytes(bitmap))
+    }
+
+    unsafe fn raw_serialize(bitmap: &Bitmap64, dst: *mut c_char) {
+        let dst = dst.cast::<u8>();
+        let Some(len) = native_array_len(bitmap) else {
+            unsafe {
+                dst.write(SERIALIZATION_CONTAINER);
+                Portable::raw_serialize(bitmap, dst.add(1).cast::<c_char>());
+            }
+            return;
+        };
+        let dst = unsafe { core::slice::from_raw_parts_mut(dst, 1 + len) };
+        dst[0] = SERIALIZATION_ARRAY_UINT64;
+        dst[1..9].copy_from_slice(&bitmap.cardinality().to_ne_bytes());
+        for (dst, value) in dst[9..].chunks_exact_mut(8).zip(bitmap.iter()) {
+            dst.copy_from_slice(&value.to_ne_bytes());
+        }
+    }
+}
+
+/// The length of the bitmap as an array of values, if that is smaller than the portable format
+///
+/// As with 32-bit bitmaps, the array is the number of values, followed by each value.
+fn native_array_len(bitmap: &Bitmap64) -> Option<usize> {
+    let portable_len = <Portable as Serializer>::get_serialized_size_in_bytes(bitmap);
+    let array_len = bitmap.cardinality().checked_mul(8)?.checked_add(8)?;
+    (array_len <= portable_len as u64).then_some(array_len as usize)
+}
+
+impl Deserializer for Native {
+    fn try_deserialize(buffer: &[u8]) -> Option<Bitmap64> {
+        Self::try_deserialize_prefix(buffer)
+            .ok()
+            .map(|(bitmap, _)| bitmap)
+    }
+
+    unsafe fn try_deserialize_unchecked(buffer: &[u8]) -> Bitmap64 {
+        Self::try_deserialize(buffer).unwrap_unchecked()
+    }
+
+    fn find_end(buffer: &[u8]) -> Option<NonZeroUsize> {
+        let mut src = SliceSource::new(buffer);
+        parse_native(&mut src).ok()?;
+        NonZeroUsize::new(src.position())
+    }
+
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Bitmap64, usize), DeserializeError> {
+        let mut src = SliceSource::new(buffer);
+        parse_native(&mut src)?;
+        let len = src.position();
+        let bitmap = match buffer[0] {
+            SERIALIZATION_ARRAY_UINT64 => buffer[9..len]
+                .chunks_exact(8)
+                .map(|value| u64::from_ne_bytes(value.try_into().unwrap()))
+                .collect(),
+            _ => {
+                <Portable as Deserializer>::try_deserialize_prefix(&buffer[1..len])
+                    .map_err(|e| e.offset_by(1))?
+                    .0
+            }
+        };
+        Ok((bitmap, len))
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R: Read>(mut src: R) -> std::io::Result<Bitmap64> {
+        let mut buf = Vec::new();
+        parse_native(&mut ReaderSource::new(&mut src, &mut buf))?;
+        Ok(Self::try_deserialize_prefix(&buf)?.0)
+    }
+}
+
+/// Consumes a bitmap in the native format from `src`
+///
+/// This is a byte giving the kind of data, then either the number of values and each value, or a
+/// bitmap in the portable format. Only the headers are checked, as with [`parse_portable`].
+fn parse_native<S: ByteSource + ?Sized>(src: &mut S) -> Result<(), S::Error> {
+    let [kind] = src.take_array()?;
+    match kind {
+        SERIALIZATION_ARRAY_UINT64 => {
+            let cardinality = u64::from_ne_bytes(src.take_array()?);
+            let len = cardinality
+                .checked_mul(8)
+                .and_then(|len| usize::try_from(len).ok())
+                .ok_or(DeserializeError::InvalidHeader {
+                    reason: "too many values",
+                })?;
+            src.take(len)?;
+            Ok(())
+        }
+        SERIALIZATION_CONTAINER => parse_portable(src),
+        _ => Err(DeserializeError::UnknownCookie {
+            cookie: u32::from(kind),
+        }
+        .into()),
+    }
+}
+
+impl crate::sealed::Sealed for JvmLegacy {}
+
+/// The `JvmLegacy` header (a signed flag byte, and a big-endian `u32` bucket count) is this many
+/// bytes shorter than the `Portable` header (a `u64` bucket count). The buckets are the same,
+/// except that their keys are big-endian.
+#[cfg(feature = "alloc")]
+const JVM_LEGACY_HEADER_SHRINK: usize = 3;
+
+#[cfg(feature = "alloc")]
+impl Serializer for JvmLegacy {
+    /// Computes the serialized size in bytes of the Bitmap in the JvmLegacy format.
+    /// See [`Bitmap64::get_serialized_size_in_bytes`] for examples.
+    fn get_serialized_size_in_bytes(bitmap: &Bitmap64) -> usize {
+        <Portable as Serializer>::get_serialized_size_in_bytes(bitmap) - JVM_LEGACY_HEADER_SHRINK
+    }
+
+    unsafe fn raw_serialize(bitmap: &Bitmap64, dst: *mut c_char) {
+        let portable = bitmap.serialize::<Portable>();
+        let len = portable.len() - JVM_LEGACY_HEADER_SHRINK;
+        let dst = unsafe { core::slice::from_raw_parts_mut(dst.cast::<u8>(), len) };
+        portable_to_jvm_legacy(&portable, dst)
+            .expect("bitmap must serialize to valid portable data");
+    }
+}
+
+/// Rewrites a bitmap serialized in the portable format into the JvmLegacy format
+#[cfg(feature = "alloc")]
+fn portable_to_jvm_legacy(portable: &[u8], dst: &mut [u8]) -> Result<(), DeserializeError> {
+    let mut src = SliceSource::new(portable);
+    let buckets = u32::try_from(u64::from_ne_bytes(src.take_array()?)).unwrap();
+    // The values are not signed
+    dst[0] = 0;
+    dst[1..5].copy_from_slice(&buckets.to_be_bytes());
+    dst[5..].copy_from_slice(&portable[8..]);
+    for _ in 0..buckets {
+        let key = u32::from_ne_bytes(src.take_array()?);
+        let key_start = src.position() - 4 - JVM_LEGACY_HEADER_SHRINK;
+        dst[key_start..key_start + 4].copy_from_slice(&key.to_be_bytes());
+        crate::bitmap::parse_portable(&mut src)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "alloc")]
+impl Deserializer for JvmLegacy {
+    fn try_deserialize(buffer: &[u8]) -> Option<Bitmap64> {
+        Self::try_deserialize_prefix(buffer)
+            .ok()
+            .map(|(bitmap, _)| bitmap)
+    }
+
+    unsafe fn try_deserialize_unchecked(buffer: &[u8]) -> Bitmap64 {
+        Self::try_deserialize(buffer).unwrap_unchecked()
+    }
+
+    fn find_end(buffer: &[u8]) -> Option<NonZeroUsize> {
+        let mut src = SliceSource::new(buffer);
+        parse_jvm_legacy(&mut src).ok()?;
+        NonZeroUsize::new(src.position())
+    }
+
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Bitmap64, usize), DeserializeError> {
+        let mut src = SliceSource::new(buffer);
+        let mut buckets = parse_jvm_legacy(&mut src)?;
+        let len = src.position();
+
+        // Buckets are sorted as signed integers if the signed flag is set, but the portable
+        // format requires them in increasing order
+        buckets.sort_unstable_by_key(|&(key, _)| key);
+        if buckets.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return Err(DeserializeError::InvalidHeader {
+                reason: "duplicate bucket key",
+            });
+        }
+        let mut portable = Vec::with_capacity(len + JVM_LEGACY_HEADER_SHRINK);
+        portable.extend_from_slice(&(buckets.len() as u64).to_ne_bytes());
+        for (key, range) in buckets {
+            portable.extend_from_slice(&key.to_ne_bytes());
+            portable.extend_from_slice(&buffer[range]);
+        }
+        let (bitmap, _) = <Portable as Deserializer>::try_deserialize_prefix(&portable)?;
+        Ok((bitmap, len))
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R: Read>(mut src: R) -> std::io::Result<Bitmap64> {
+        let mut buf = Vec::new();
+        parse_jvm_legacy(&mut ReaderSource::new(&mut src, &mut buf))?;
+        Ok(Self::try_deserialize_prefix(&buf)?.0)
+    }
+}
+
+/// Consumes a bitmap in the JvmLegacy format from `src`, returning the key of each bucket and
+/// the range of its 32-bit portable bitmap
+///
+/// This is a signed flag byte, the big-endian number of buckets, then each bucket's big-endian
+/// key and 32-bit portable bitmap. Only the headers are checked, as with
+/// [`crate::bitmap::parse_portable`].
+#[cfg(feature = "alloc")]
+fn parse_jvm_legacy<S: ByteSource + ?Sized>(
+    src: &mut S,
+) -> Result<Vec<(u32, Range<usize>)>, S::Error> {
+    // Ignored, the values of each bucket are the same either way
+    let [_is_signed] = src.take_array()?;
+    let count = u32::from_be_bytes(src.take_array()?);
+    let mut buckets = Vec::new();
+    for _ in 0..count {
+        let key = u32::from_be_bytes(src.take_array()?);
+        let start = src.consumed().len();
+        crate::bitmap::parse_portable(src)?;
+        buckets.push((key, start..src.consumed().len()));
+    }
+    Ok(buckets)
+}
+
+impl Serializer for ClickHouse {
+    /// Computes the serialized size in bytes of the Bitmap as a ClickHouse `groupBitmap` state.
+    /// See [`Bitmap64::get_serialized_size_in_bytes`] for examples.
+    fn get_serialized_size_in_bytes(bitmap: &Bitmap64) -> usize {
+        clickhouse_size(
+            bitmap.cardinality(),
+            8,
+            <Portable as Serializer>::get_serialized_size_in_bytes(bitmap),
+        )
+    }
+
+    unsafe fn raw_serialize(bitmap: &Bitmap64, dst: *mut c_char) {
+        let len = Self::get_serialized_size_in_bytes(bitmap);
+        let dst = unsafe { core::slice::from_raw_parts_mut(dst.cast::<u8>(), len) };
+        let cardinality = bitmap.cardinality();
+        let portable_len = <Portable as Serializer>::get_serialized_size_in_bytes(bitmap);
+        let header_len = write_clickhouse_header(cardinality, portable_len, dst);
+        let data = &mut dst[header_len..];
+        if cardinality <= CLICKHOUSE_SMALL_SET_SIZE {
+            for (dst, value) in data.chunks_exact_mut(8).zip(bitmap.iter()) {
+                dst.copy_from_slice(&value.to_le_bytes());
+            }
+        } else {
+            unsafe { Portable::raw_serialize(bitmap, data.as_mut_ptr().cast::<c_char>()) };
+        }
+    }
+}
+
+impl Deserializer for ClickHouse {
+    fn try_deserialize(buffer: &[u8]) -> Option<Bitmap64> {
+        Self::try_deserialize_prefix(buffer)
+            .ok()
+            .map(|(bitmap, _)| bitmap)
+    }
+
+    unsafe fn try_deserialize_unchecked(buffer: &[u8]) -> Bitmap64 {
+        Self::try_deserialize(buffer).unwrap_unchecked()
+    }
+
+    fn find_end(buffer: &[u8]) -> Option<NonZeroUsize> {
+        let mut src = SliceSource::new(buffer);
+        parse_clickhouse(&mut src, 8).ok()?;
+        NonZeroUsize::new(src.position())
+    }
+
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Bitmap64, usize), DeserializeError> {
+        let mut src = SliceSource::new(buffer);
+        let bitmap = match parse_clickhouse(&mut src, 8)? {
+            ClickHouseState::Small { start, count } => buffer[start..start + count * 8]
+                .chunks_exact(8)
+                .map(|value| u64::from_le_bytes(value.try_into().unwrap()))
+                .collect(),
+            ClickHouseState::Large { start, len } => {
+                Bitmap64::try_deserialize_exact::<Portable>(&buffer[start..start + len])
+                    .map_err(|e| e.offset_by(start))?
+            }
+        };
+        Ok((bitmap, src.position()))
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R: Read>(mut src: R) -> std::io::Result<Bitmap64> {
+        let mut buf = Vec::new();
+        parse_clickhouse(&mut ReaderSource::new(&mut src, &mut buf), 8)?;
+        Ok(Self::try_deserialize_prefix(&buf)?.0)
+    }
 }
 
 impl Serializer for Frozen {
@@ -189,7 +647,239 @@ impl Serializer for Frozen {
 }
 
 impl ViewDeserializer for Frozen {
+    const REQUIRED_ALIGNMENT: usize = <Self as Serializer>::REQUIRED_ALIGNMENT;
+
     unsafe fn deserialize_view(data: &[u8]) -> *mut ffi::roaring64_bitmap_t {
         unsafe { ffi::roaring64_bitmap_frozen_view(data.as_ptr().cast(), data.len()) }
     }
+
+    fn try_deserialize_view(data: &[u8]) -> Result<Bitmap64View<'_>, DeserializeError> {
+        // The inherent constant on Frozen is the alignment of 32-bit bitmaps
+        let required = <Self as ViewDeserializer>::REQUIRED_ALIGNMENT;
+        if data.as_ptr().align_offset(required) != 0 {
+            return Err(DeserializeError::Misaligned { required });
+        }
+        check_frozen(data)?;
+        // SAFETY: check_frozen has checked everything roaring64_bitmap_frozen_view reads
+        let view = unsafe { Bitmap64View::deserialize::<Self>(data) }.ok_or(
+            DeserializeError::InvalidHeader {
+                reason: "data is not a frozen bitmap",
+            },
+        )?;
+        view.internal_validate()
+            .map_err(|reason| DeserializeError::InvalidContainer { reason })?;
+        Ok(view)
+    }
+}
+
+// The layout of the ART (adaptive radix tree) holding the high 48 bits of each container's key,
+// as serialized by CRoaring in the frozen format. Each type of node is stored in its own array,
+// and nodes refer to each other by typecode and index into those arrays.
+const ART_KEY_BYTES: usize = 6;
+const ART_LEAF_TYPE: u8 = 1;
+const ART_NODE4_TYPE: u8 = 2;
+const ART_NODE16_TYPE: u8 = 3;
+const ART_NODE48_TYPE: u8 = 4;
+const ART_NODE256_TYPE: u8 = 5;
+// Indexed by typecode
+const ART_NODE_SIZES: [usize; 6] = [0, 16, 48, 152, 656, 2056];
+// Marks a missing child in the keys of a node48
+const ART_NODE48_EMPTY: u8 = 48;
+
+/// Checks that `data` holds a bitmap in the frozen format, before CRoaring views it
+///
+/// `roaring64_bitmap_frozen_view` only checks the sizes in its header: the ART nodes are used in
+/// place, and it follows every reference between them, and from the leaves to the containers.
+/// This checks each reference is in bounds, that the tree is no deeper than its keys, and that
+/// every container is referenced by exactly one leaf, with its data within the sizes given in
+/// the header. The contents of the containers are left to [`Bitmap64::internal_validate`].
+fn check_frozen(data: &[u8]) -> Result<(), DeserializeError> {
+    let as_usize = |value: u64| usize::try_from(value).unwrap_or(usize::MAX);
+    let mut src = SliceSource::new(data);
+    let take_u64 = |src: &mut SliceSource<'_>| src.take_array().map(u64::from_ne_bytes);
+    let align = |src: &mut SliceSource<'_>, alignment: usize| {
+        let pos = src.position();
+        src.take(pos.next_multiple_of(alignment) - pos)
+    };
+
+    // Flags
+    src.take(1)?;
+    let containers = take_u64(&mut src)?;
+    let counts = src.take(as_usize(containers).saturating_mul(2))?;
+    // Bitset, run then array containers
+    let total_sizes = [
+        take_u64(&mut src)?,
+        take_u64(&mut src)?,
+        take_u64(&mut src)?,
+    ];
+
+    align(&mut src, 8)?;
+    let root = take_u64(&mut src)?;
+    let mut capacities = [0_u64; 6];
+    for capacity in &mut capacities {
+        *capacity = take_u64(&mut src)?;
+    }
+    let mut nodes = [0; 6];
+    for typecode in ART_LEAF_TYPE..=ART_NODE256_TYPE {
+        let t = usize::from(typecode);
+        nodes[t] = src.take(as_usize(capacities[t]).saturating_mul(ART_NODE_SIZES[t]))?;
+    }
+
+    align(&mut src, <Frozen as Serializer>::REQUIRED_ALIGNMENT)?;
+    for (i, &size) in total_sizes.iter().enumerate() {
+        if i != 0 {
+            align(&mut src, 2)?;
+        }
+        src.take(as_usize(size))?;
+    }
+    align(&mut src, <Frozen as Serializer>::REQUIRED_ALIGNMENT)?;
+    if src.position() != data.len() {
+        return Err(DeserializeError::TrailingBytes {
+            extra: data.len() - src.position(),
+        });
+    }
+
+    let art = FrozenArt {
+        data,
+        capacities,
+        nodes,
+    };
+    let mut leaves = 0;
+    let mut seen = Bitmap64::new();
+    let mut sizes = [0_u64; 3];
+    let mut visit_leaf = |leaf: u64| {
+        // The container's typecode is in the low byte, and its index in the rest
+        let index = leaf >> 8;
+        if index >= containers || !seen.add_checked(index) {
+            return Err(DeserializeError::InvalidHeader {
+                reason: "containers are not each referenced by one leaf",
+            });
+        }
+        let count_at = counts + leaves * 2;
+        let count = u64::from(u16::from_ne_bytes([data[count_at], data[count_at + 1]])) + 1;
+        leaves += 1;
+        match leaf as u8 {
+            1 => sizes[0] += 8192,
+            3 => sizes[1] += count * 4,
+            2 => sizes[2] += count * 2,
+            _ => {
+                return Err(DeserializeError::InvalidHeader {
+                    reason: "invalid container type",
+                })
+            }
+        }
+        Ok(())
+    };
+    if root != 0 {
+        art.check(root, 0, &mut visit_leaf)?;
+    }
+    if seen.cardinality() != containers {
+        return Err(DeserializeError::InvalidHeader {
+            reason: "containers are not each referenced by one leaf",
+        });
+    }
+    if sizes != total_sizes {
+        return Err(DeserializeError::InvalidHeader {
+            reason: "container sizes do not match the header",
+        });
+    }
+    Ok(())
+}
+
+/// The nodes of a frozen ART, as found by [`check_frozen`]
+struct FrozenArt<'a> {
+    data: &'a [u8],
+    capacities: [u64; 6],
+    // The offset of the array of each type of node in `data`
+    nodes: [usize; 6],
+}
+
+impl FrozenArt<'_> {
+    /// Checks the node `node_ref` refers to, and its children at a key depth of `depth`
+    ///
+    /// Each leaf is passed to `visit_leaf` in key order, as CRoaring iterates over them.
+    fn check(
+        &self,
+        node_ref: u64,
+        depth: usize,
+        visit_leaf: &mut dyn FnMut(u64) -> Result<(), DeserializeError>,
+    ) -> Result<(), DeserializeError> {
+        // The typecode is in the low byte, and the index from the third byte up
+        let typecode = node_ref as u8;
+        let index = node_ref >> 16;
+        if !(ART_LEAF_TYPE..=ART_NODE256_TYPE).contains(&typecode)
+            || node_ref & 0xFF00 != 0
+            || index >= self.capacities[usize::from(typecode)]
+        {
+            return Err(DeserializeError::InvalidHeader {
+                reason: "reference to a missing tree node",
+            });
+        }
+        let size = ART_NODE_SIZES[usize::from(typecode)];
+        let start = self.nodes[usize::from(typecode)] + index as usize * size;
+        let node = &self.data[start..start + size];
+        let child =
+            |offset: usize| u64::from_ne_bytes(node[offset..offset + 8].try_into().unwrap());
+
+        if typecode == ART_LEAF_TYPE {
+            return visit_leaf(child(8));
+        }
+        // Inner nodes start with the size of their prefix, and use another byte to pick a child
+        let depth = depth + usize::from(node[0]) + 1;
+        if depth > ART_KEY_BYTES {
+            return Err(DeserializeError::InvalidHeader {
+                reason: "tree is deeper than its keys",
+            });
+        }
+        let mut children = 0;
+        let mut check_child = |child_ref: u64| {
+            children += 1;
+            self.check(child_ref, depth, visit_leaf)
+        };
+        match typecode {
+            ART_NODE4_TYPE | ART_NODE16_TYPE => {
+                let (max, children_at) = if typecode == ART_NODE4_TYPE {
+                    (4, 16)
+                } else {
+                    (16, 24)
+                };
+                let count = usize::from(node[6]);
+                if count > max {
+                    return Err(DeserializeError::InvalidHeader {
+                        reason: "tree node has too many children",
+                    });
+                }
+                for i in 0..count {
+                    check_child(child(children_at + i * 8))?;
+                }
+            }
+            ART_NODE48_TYPE => {
+                for &key in &node[16..272] {
+                    match key {
+                        ART_NODE48_EMPTY => {}
+                        0..ART_NODE48_EMPTY => check_child(child(272 + usize::from(key) * 8))?,
+                        _ => {
+                            return Err(DeserializeError::InvalidHeader {
+                                reason: "tree node has too many children",
+                            })
+                        }
+                    }
+                }
+            }
+            _ => {
+                for i in 0..256 {
+                    let child_ref = child(8 + i * 8);
+                    if child_ref != 0 {
+                        check_child(child_ref)?;
+                    }
+                }
+            }
+        }
+        if children == 0 {
+            return Err(DeserializeError::InvalidHeader {
+                reason: "tree node has no children",
+            });
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,115 @@
+use super::imp::range_to_inclusive;
+use super::Bitmap64;
+use core::mem;
+use core::ops::RangeBounds;
+
+impl Bitmap64 {
+    /// Splits the bitmap in two at `at`
+    ///
+    /// Returns a bitmap containing all values `>= at`, leaving all values `< at` in `self`.
+    ///
+    /// The internal structure of a 64-bit bitmap is not directly accessible, so unlike
+    /// [`Bitmap::split_off`](crate::Bitmap::split_off), containers cannot be moved to the new
+    /// bitmap. Instead, the containers on the side of `at` with fewer values are copied, and
+    /// removed from `self`. The other containers are visited, but never copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let mut bitmap = Bitmap64::of(&[1, 2, 3, 1 << 40, u64::MAX]);
+    /// let tail = bitmap.split_off(3);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [1, 2]);
+    /// assert_eq!(tail.iter().collect::<Vec<_>>(), [3, 1 << 40, u64::MAX]);
+    ///
+    /// let tail = bitmap.split_off(2);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [1]);
+    /// assert_eq!(tail.iter().collect::<Vec<_>>(), [2]);
+    /// ```
+    #[must_use]
+    pub fn split_off(&mut self, at: u64) -> Bitmap64 {
+        let below = self.range_cardinality(..at);
+        let at_or_above = self.cardinality() - below;
+        if at_or_above <= below {
+            let tail = self.copy_at_or_above(at);
+            self.remove_range(at..);
+            tail
+        } else {
+            let head = self.copy_below(at);
+            self.remove_range(..at);
+            mem::replace(self, head)
+        }
+    }
+
+    /// Returns a copy of the values `>= at`
+    ///
+    /// The bitmap is shifted down, then back up, by the start of the container holding `at`.
+    /// These shifts copy whole containers, and drop those shifted below 0 without copying them.
+    fn copy_at_or_above(&self, at: u64) -> Bitmap64 {
+        let offset = i128::from(at & !0xFFFF);
+        let mut tail = self.add_offset(-offset).add_offset(offset);
+        tail.remove_range(..at);
+        tail
+    }
+
+    /// Returns a copy of the values `< at`
+    ///
+    /// As for [`Self::copy_at_or_above`], but the bitmap is shifted up so that the container
+    /// holding `at` becomes the last one, dropping those after it.
+    fn copy_below(&self, at: u64) -> Bitmap64 {
+        let offset = i128::from(!at & !0xFFFF);
+        let mut head = self.add_offset(offset).add_offset(-offset);
+        head.remove_range(at..);
+        head
+    }
+
+    /// Moves all values of `other` into `self`, leaving `other` empty
+    ///
+    /// This is equivalent to [`Bitmap64::or_inplace`] followed by clearing `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let mut bitmap = Bitmap64::of(&[1, 2, 3]);
+    /// let mut other = Bitmap64::of(&[2, 1 << 40]);
+    /// bitmap.append(&mut other);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [1, 2, 3, 1 << 40]);
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Bitmap64) {
+        self.or_inplace(other);
+        other.clear();
+    }
+
+    /// Removes all values outside of `range`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Bitmap64;
+    ///
+    /// let mut bitmap = Bitmap64::of(&[1, 2, 3, 1 << 40, u64::MAX]);
+    /// bitmap.retain_range(2..=1 << 40);
+    /// assert_eq!(bitmap.iter().collect::<Vec<_>>(), [2, 3, 1 << 40]);
+    ///
+    /// bitmap.retain_range(3..3);
+    /// assert!(bitmap.is_empty());
+    /// ```
+    #[doc(alias = "truncate_range")]
+    pub fn retain_range<R: RangeBounds<u64>>(&mut self, range: R) {
+        let (start, end) = range_to_inclusive(range);
+        if start > end {
+            self.clear();
+            return;
+        }
+        if let Some(before) = start.checked_sub(1) {
+            self.remove_range(..=before);
+        }
+        if let Some(after) = end.checked_add(1) {
+            self.remove_range(after..);
+        }
+    }
+}
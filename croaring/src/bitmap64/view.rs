@@ -1,6 +1,9 @@
 use crate::bitmap64::serialization::ViewDeserializer;
 use crate::bitmap64::{Bitmap64, Bitmap64View};
+use crate::DeserializeError;
+use core::cmp::Ordering;
 use core::fmt;
+use core::hash::{Hash, Hasher};
 use core::marker::PhantomData;
 use core::ops::Deref;
 
@@ -9,7 +12,19 @@ impl<'a> Bitmap64View<'a> {
     ///
     /// This function returns an option, which will return `None` if the data is not a valid bitmap,
     /// however, this is only done on a best-effort basis, and may not catch all invalid data.
-    /// This function is _only_ safe to call if the caller _knows_ that the data is a valid bitmap.
+    /// This function is _only_ safe to call if the caller _knows_ that the data is a valid bitmap:
+    /// see [`Self::try_deserialize`] for a safe alternative.
+    ///
+    /// Only [`Frozen`](crate::Frozen) data can be viewed. Unlike for 32-bit
+    /// [`BitmapView`](crate::BitmapView)s, CRoaring offers no way to reference the containers of
+    /// [`Portable`](crate::Portable) 64-bit data in place: such data must be copied with
+    /// [`Bitmap64::deserialize`], and can be stored with [`Frozen`](crate::Frozen) to be viewed
+    /// afterwards.
+    ///
+    /// ```compile_fail
+    /// use croaring::{Bitmap64View, Portable};
+    /// let view = Bitmap64View::try_deserialize::<Portable>(&[]);
+    /// ```
     ///
     /// # Examples
     ///
@@ -41,6 +56,36 @@ impl<'a> Bitmap64View<'a> {
         }
     }
 
+    /// Create a bitmap view of a slice of data without copying, checking that it is valid
+    ///
+    /// Unlike [`Self::deserialize`], this is safe to call with any data, such as data read from
+    /// disk or the network: every header, tree node and container reference CRoaring follows is
+    /// bounds-checked first, and the view is validated as with [`Bitmap64::internal_validate`]
+    /// before it is returned. `data` must contain exactly one serialized bitmap, aligned to 64
+    /// bytes, the alignment required for 64-bit [`Frozen`](crate::Frozen) bitmaps.
+    ///
+    /// Validation visits every container, so this is slower than [`Self::deserialize`], though
+    /// still without copying the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap64, Bitmap64View, DeserializeError, Frozen};
+    ///
+    /// let mut orig_bitmap = Bitmap64::of(&[1, 2, 3, 1 << 40]);
+    /// orig_bitmap.shrink_to_fit();
+    /// let mut buf = [0; 1024];
+    /// let data: &[u8] = orig_bitmap.try_serialize_into::<Frozen>(&mut buf).unwrap();
+    /// let view = Bitmap64View::try_deserialize::<Frozen>(data).unwrap();
+    /// assert_eq!(orig_bitmap, view);
+    ///
+    /// let err = Bitmap64View::try_deserialize::<Frozen>(&data[..data.len() - 1]).unwrap_err();
+    /// assert!(matches!(err, DeserializeError::Truncated { .. }));
+    /// ```
+    pub fn try_deserialize<S: ViewDeserializer>(data: &'a [u8]) -> Result<Self, DeserializeError> {
+        S::try_deserialize_view(data)
+    }
+
     /// Create an owned, mutable bitmap from this view
     ///
     /// # Examples
@@ -82,6 +127,27 @@ impl<'a, 'b> PartialEq<Bitmap64View<'a>> for Bitmap64View<'b> {
 
 impl Eq for Bitmap64View<'_> {}
 
+impl Hash for Bitmap64View<'_> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bitmap.hash(state);
+    }
+}
+
+impl<'a, 'b> PartialOrd<Bitmap64View<'a>> for Bitmap64View<'b> {
+    #[inline]
+    fn partial_cmp(&self, other: &Bitmap64View<'a>) -> Option<Ordering> {
+        Some(self.bitmap.cmp(&other.bitmap))
+    }
+}
+
+impl Ord for Bitmap64View<'_> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bitmap.cmp(&other.bitmap)
+    }
+}
+
 impl PartialEq<Bitmap64View<'_>> for Bitmap64 {
     fn eq(&self, other: &Bitmap64View<'_>) -> bool {
         *self == other.bitmap
@@ -161,7 +161,13 @@ impl Bitset {
     #[inline]
     #[doc(alias = "bitset_grow")]
     fn grow(&mut self, new_array_size: usize) {
-        assert!(unsafe { ffi::bitset_grow(&mut self.bitset, new_array_size) });
+        assert!(self.try_grow(new_array_size));
+    }
+
+    /// Grow the bitset as with `grow`, returning false if the memory could not be allocated
+    #[inline]
+    pub(super) fn try_grow(&mut self, new_array_size: usize) -> bool {
+        unsafe { ffi::bitset_grow(&mut self.bitset, new_array_size) }
     }
 
     /// Attempts to recover unused memory by shrinking capacity to fit the highest set bit
@@ -3,6 +3,8 @@
 mod imp;
 mod iter;
 mod ops;
+#[cfg(feature = "serde")]
+mod serde;
 
 /// A dense bitset
 #[repr(transparent)]
@@ -0,0 +1,79 @@
+use super::Bitset;
+use core::fmt;
+use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// Human-readable formats (e.g. JSON) serialize a bitset as a sorted sequence of the indexes of
+/// set bits, other formats serialize it as the sequence of its underlying 64-bit words.
+impl Serialize for Bitset {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_seq(self.iter())
+        } else {
+            serializer.collect_seq(self.as_slice())
+        }
+    }
+}
+
+/// Human-readable formats (e.g. JSON) expect a sequence of the indexes of set bits, other formats
+/// expect the sequence of the bitset's 64-bit words.
+impl<'de> Deserialize<'de> for Bitset {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_seq(IndexesVisitor)
+        } else {
+            deserializer.deserialize_seq(WordsVisitor)
+        }
+    }
+}
+
+/// The largest index accepted when deserializing indexes, which bounds the memory an untrusted
+/// input can make the bitset allocate (512 MiB)
+const MAX_INDEX: usize = u32::MAX as usize;
+
+struct IndexesVisitor;
+
+impl<'de> Visitor<'de> for IndexesVisitor {
+    type Value = Bitset;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of bit indexes")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Bitset, A::Error> {
+        let mut bitset = Bitset::new();
+        while let Some(index) = seq.next_element::<usize>()? {
+            if index > MAX_INDEX {
+                return Err(A::Error::custom(format_args!(
+                    "bit index {index} is larger than the maximum of {MAX_INDEX}"
+                )));
+            }
+            let array_idx = index / 64;
+            if array_idx >= bitset.size_in_words() && !bitset.try_grow(array_idx + 1) {
+                return Err(A::Error::custom("could not allocate the bitset"));
+            }
+            bitset.set(index);
+        }
+        Ok(bitset)
+    }
+}
+
+struct WordsVisitor;
+
+impl<'de> Visitor<'de> for WordsVisitor {
+    type Value = Bitset;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of 64-bit words")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Bitset, A::Error> {
+        let mut bitset = Bitset::new();
+        while let Some(word) = seq.next_element::<u64>()? {
+            let len = bitset.size_in_words();
+            bitset.resize_words(len + 1, false);
+            bitset.as_mut_slice()[len] = word;
+        }
+        Ok(bitset)
+    }
+}
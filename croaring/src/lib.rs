@@ -19,9 +19,17 @@ pub mod bitset;
 pub mod treemap;
 
 mod callback;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "alloc")]
+mod owned_buffer;
+#[cfg(feature = "rayon")]
+mod par;
 #[cfg(any(feature = "alloc", feature = "allocator-api2"))]
 mod rust_alloc;
 mod serialization;
+#[cfg(feature = "alloc")]
+mod threshold;
 
 mod sealed {
     pub trait Sealed {}
@@ -29,10 +37,22 @@ mod sealed {
 
 pub use serialization::*;
 
+#[cfg(feature = "std")]
+pub use bitmap::CowBitmap;
+#[cfg(feature = "mmap")]
+pub use bitmap::MmapBitmapView;
+#[cfg(feature = "alloc")]
+pub use bitmap::OwnedBitmapView;
 pub use bitmap::{Bitmap, BitmapView};
+#[cfg(feature = "mmap")]
+pub use bitmap64::MmapBitmap64View;
+#[cfg(feature = "alloc")]
+pub use bitmap64::OwnedBitmap64View;
 pub use bitmap64::{Bitmap64, Bitmap64View};
 pub use bitset::Bitset;
 
+#[cfg(feature = "alloc")]
+pub use owned_buffer::SharedBuffer;
 #[cfg(feature = "alloc")]
 pub use treemap::Treemap;
 
@@ -0,0 +1,21 @@
+use memmap2::{Mmap, MmapOptions};
+use std::fs::File;
+use std::io;
+
+/// Map `len` bytes of `file` starting at `offset`
+///
+/// Reading a mapped page past the end of the file raises `SIGBUS`, so the range is checked
+/// against the length of the file before it is mapped.
+pub(crate) unsafe fn map_range(file: &File, offset: u64, len: usize) -> io::Result<Mmap> {
+    let file_len = file.metadata()?.len();
+    let in_bounds = offset
+        .checked_add(len as u64)
+        .is_some_and(|end| end <= file_len);
+    if !in_bounds {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "range extends past the end of the file",
+        ));
+    }
+    MmapOptions::new().offset(offset).len(len).map(file)
+}
@@ -0,0 +1,106 @@
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+/// A heap allocated buffer, whose bytes may be borrowed for as long as it is alive
+///
+/// The buffer is boxed so that bytes stored inline (e.g. in an array) do not move when the
+/// owner is moved, and is held through a raw pointer rather than a `Box` so that moving the
+/// owner does not assert unique access to bytes which are still borrowed. No mutable access to
+/// the buffer is ever given out, so the slice returned by [`AsRef::as_ref`] stays valid until
+/// the buffer is dropped.
+pub(crate) struct OwnedBuffer<B> {
+    ptr: NonNull<B>,
+    _owned: PhantomData<B>,
+}
+
+// The buffer is owned, only shared references to it are given out
+unsafe impl<B: Send> Send for OwnedBuffer<B> {}
+unsafe impl<B: Sync> Sync for OwnedBuffer<B> {}
+
+impl<B: AsRef<[u8]>> OwnedBuffer<B> {
+    pub(crate) fn new(buffer: B) -> Self {
+        let ptr = NonNull::from(Box::leak(Box::new(buffer)));
+        Self {
+            ptr,
+            _owned: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn get(&self) -> &B {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// The bytes of the buffer, which remain valid until the buffer is dropped
+    ///
+    /// # Safety
+    ///
+    /// The returned slice must not be used after `self` is dropped
+    #[inline]
+    pub(crate) unsafe fn bytes_unbounded<'a>(&self) -> &'a [u8] {
+        let bytes: *const [u8] = self.get().as_ref();
+        &*bytes
+    }
+
+    pub(crate) fn into_inner(self) -> B {
+        let ptr = self.ptr;
+        core::mem::forget(self);
+        *unsafe { Box::from_raw(ptr.as_ptr()) }
+    }
+}
+
+impl<B> Drop for OwnedBuffer<B> {
+    fn drop(&mut self) {
+        drop(unsafe { Box::from_raw(self.ptr.as_ptr()) });
+    }
+}
+
+/// A buffer whose clones share its bytes
+///
+/// An [`OwnedBitmapView`](crate::OwnedBitmapView) or
+/// [`OwnedBitmap64View`](crate::OwnedBitmap64View) of a shared buffer is [`Clone`]: the clone
+/// views the same bytes as the original, so they are not checked again, and cannot fail to be
+/// viewed. Views of other buffers can be cloned with `try_clone`.
+///
+/// Other shared buffers, such as `bytes::Bytes`, can implement this through a wrapper type.
+///
+/// # Safety
+///
+/// [`AsRef::as_ref`] must return the same bytes, at the same address, for a buffer and all of its
+/// clones, and the bytes must not be modified while the buffer or any of its clones is alive.
+///
+/// # Examples
+///
+/// ```
+/// use croaring::{Bitmap, OwnedBitmapView, Portable, SharedBuffer};
+/// use std::sync::Arc;
+///
+/// #[derive(Clone)]
+/// struct SharedVec(Arc<Vec<u8>>);
+///
+/// impl AsRef<[u8]> for SharedVec {
+///     fn as_ref(&self) -> &[u8] {
+///         &self.0
+///     }
+/// }
+///
+/// // SAFETY: clones share the same `Vec`, which is never modified
+/// unsafe impl SharedBuffer for SharedVec {}
+///
+/// let bitmap = Bitmap::of(&[1, 2, 3]);
+/// let data = SharedVec(Arc::new(bitmap.serialize::<Portable>()));
+/// let view = OwnedBitmapView::try_deserialize::<Portable>(data).unwrap();
+/// let clone = view.clone();
+/// assert_eq!(clone, bitmap);
+/// assert!(Arc::ptr_eq(&clone.buffer().0, &view.buffer().0));
+/// ```
+pub unsafe trait SharedBuffer: AsRef<[u8]> + Clone {}
+
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl SharedBuffer for Arc<[u8]> {}
+unsafe impl SharedBuffer for Rc<[u8]> {}
+unsafe impl SharedBuffer for &[u8] {}
@@ -0,0 +1,29 @@
+//! Helpers shared by the parallel operations of the `rayon` feature
+
+/// The largest number of items reduced sequentially at each leaf of [`tree_reduce`]
+const LEAF_SIZE: usize = 16;
+
+/// Reduces `items` as a balanced tree, with each level of the tree computed in parallel
+///
+/// Runs of at most [`LEAF_SIZE`] items are reduced sequentially with `leaf`, then the results
+/// are combined pairwise with `combine`.
+///
+/// `items` must not be empty.
+pub(crate) fn tree_reduce<T, R, L, C>(items: &[T], leaf: &L, combine: &C) -> R
+where
+    T: Sync,
+    R: Send,
+    L: Fn(&[T]) -> R + Sync,
+    C: Fn(R, R) -> R + Sync,
+{
+    debug_assert!(!items.is_empty());
+    if items.len() <= LEAF_SIZE {
+        return leaf(items);
+    }
+    let (left, right) = items.split_at(items.len() / 2);
+    let (left, right) = rayon::join(
+        || tree_reduce(left, leaf, combine),
+        || tree_reduce(right, leaf, combine),
+    );
+    combine(left, right)
+}
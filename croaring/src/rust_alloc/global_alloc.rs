@@ -107,11 +107,11 @@ fn impossible_aligned_alloc() {
         let ptr = aligned_malloc(usize::MAX, usize::MAX);
         assert!(ptr.is_null());
 
-        let max_pow_2 = 1usize << (size_of::<usize>() * 8 - 1);
+        let max_pow_2 = 1usize << (usize::BITS - 1);
         let ptr = aligned_malloc(max_pow_2, max_pow_2);
         assert!(ptr.is_null());
 
-        let max_pow_2_isize = 1usize << (size_of::<usize>() * 8 - 2);
+        let max_pow_2_isize = 1usize << (usize::BITS - 2);
         let ptr = aligned_malloc(max_pow_2_isize, max_pow_2_isize);
         assert!(ptr.is_null());
     }
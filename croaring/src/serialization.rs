@@ -3,12 +3,19 @@
 /// Note despite the name, it is not fully portable: it depends on native endianness.
 ///
 /// It's defined here: <https://github.com/RoaringBitmap/RoaringFormatSpec>
+///
+/// 32-bit [`BitmapView`][crate::BitmapView]s can be created from this format, but
+/// [`Bitmap64View`][crate::Bitmap64View]s cannot: CRoaring only views 64-bit bitmaps
+/// serialized with [`Frozen`].
 pub enum Portable {}
 
 /// The `Native` format format can sometimes be more space efficient than [`Portable`],
 ///
 /// e.g. when the data is sparse. It's not compatible with Java and Go implementations.
 /// Use [`Portable`] for that purpose.
+///
+/// [`Bitmap64`][crate::Bitmap64]s are stored the same way: as an array of 64-bit values when that
+/// is smaller, otherwise in the [`Portable`] format.
 pub enum Native {}
 
 /// The `Frozen` format imitates memory layout of the underlying C library.
@@ -27,12 +34,14 @@ impl Frozen {
 }
 
 mod private {
-    use crate::{Native, Portable};
+    use crate::{ClickHouse, JvmLegacy, Native, Portable};
 
     #[allow(unused)]
     pub trait NoAlign: crate::sealed::Sealed {}
     impl NoAlign for Native {}
     impl NoAlign for Portable {}
+    impl NoAlign for JvmLegacy {}
+    impl NoAlign for ClickHouse {}
 }
 
 #[allow(unused)]
@@ -40,11 +49,220 @@ pub(crate) use private::NoAlign;
 
 /// The `JvmLegacy` format is meant to be compatible with the original Java implementation of `Roaring64NavigableMap`
 ///
-/// It is used only for [Treemap][crate::Treemap]s, not bitmaps.
+/// It is used for [Treemap][crate::Treemap]s and [Bitmap64][crate::Bitmap64]s, not 32-bit bitmaps.
 ///
 /// See <https://github.com/RoaringBitmap/RoaringBitmap/blob/2669c4f5a49ee7da5ff4cd70e18ee5520018d6a5/RoaringBitmap/src/main/java/org/roaringbitmap/longlong/Roaring64NavigableMap.java#L1215-L1238>
 pub enum JvmLegacy {}
 
+/// The `ClickHouse` format is the state of ClickHouse's `groupBitmap` aggregate function
+///
+/// It is the format of `AggregateFunction(groupBitmap, UInt32)` columns for [`Bitmap`][crate::Bitmap]s,
+/// and of `AggregateFunction(groupBitmap, UInt64)` columns for [`Bitmap64`][crate::Bitmap64]s and
+/// [`Treemap`][crate::Treemap]s.
+///
+/// Bitmaps of up to 32 values are stored as a small set: a zero byte, the number of values as a
+/// variable length integer, then each value as little-endian bytes. Larger bitmaps are stored as a
+/// one byte, the length of the serialized bitmap as a variable length integer, then the bitmap in
+/// the [`Portable`] format.
+///
+/// See <https://github.com/ClickHouse/ClickHouse/blob/master/src/AggregateFunctions/AggregateFunctionGroupBitmapData.h>
+pub enum ClickHouse {}
+
+/// An error describing why deserialization failed
+///
+/// Returned by methods such as [`Bitmap::try_deserialize_exact`][crate::Bitmap::try_deserialize_exact]
+/// and [`Bitmap::try_deserialize_prefix`][crate::Bitmap::try_deserialize_prefix].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeserializeError {
+    /// The input ended before the end of the serialized data
+    Truncated {
+        /// The number of bytes from the start of the input needed to continue deserializing
+        ///
+        /// The serialized data may be longer still: its full length is only known once every
+        /// header has been read.
+        needed: usize,
+        /// The number of bytes in the input
+        available: usize,
+    },
+    /// The input does not start with a cookie (or type marker) of the expected format
+    UnknownCookie {
+        /// The cookie read from the input
+        cookie: u32,
+    },
+    /// The header describing the serialized containers is invalid
+    InvalidHeader {
+        /// A description of the problem
+        reason: &'static str,
+    },
+    /// The data of a serialized container is invalid
+    InvalidContainer {
+        /// A description of the problem
+        reason: &'static str,
+    },
+    /// The input continues after the end of the serialized data
+    TrailingBytes {
+        /// The number of bytes after the end of the serialized data
+        extra: usize,
+    },
+    /// The input, or data within it which is viewed in place, is not aligned as required by the
+    /// format
+    Misaligned {
+        /// The alignment required, in bytes
+        required: usize,
+    },
+}
+
+impl DeserializeError {
+    /// Adjusts the error of data read from `offset` bytes into the input
+    pub(crate) fn offset_by(self, offset: usize) -> Self {
+        match self {
+            Self::Truncated { needed, available } => Self::Truncated {
+                needed: needed.saturating_add(offset),
+                available: available + offset,
+            },
+            other => other,
+        }
+    }
+}
+
+impl core::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated { needed, available } => write!(
+                f,
+                "input truncated: needed at least {needed} bytes, but only {available} available"
+            ),
+            Self::UnknownCookie { cookie } => write!(f, "unknown cookie {cookie:#x}"),
+            Self::InvalidHeader { reason } => write!(f, "invalid header: {reason}"),
+            Self::InvalidContainer { reason } => write!(f, "invalid container: {reason}"),
+            Self::TrailingBytes { extra } => {
+                write!(f, "{extra} unexpected bytes after the serialized data")
+            }
+            Self::Misaligned { required } => {
+                write!(f, "input is not aligned to {required} bytes")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DeserializeError {}
+
+/// Truncated input is reported as [`UnexpectedEof`][std::io::ErrorKind::UnexpectedEof], all
+/// other errors as [`InvalidData`][std::io::ErrorKind::InvalidData].
+#[cfg(feature = "std")]
+impl From<DeserializeError> for std::io::Error {
+    fn from(err: DeserializeError) -> Self {
+        let kind = match err {
+            DeserializeError::Truncated { .. } => std::io::ErrorKind::UnexpectedEof,
+            _ => std::io::ErrorKind::InvalidData,
+        };
+        std::io::Error::new(kind, err)
+    }
+}
+
+/// A source of serialized data, which is consumed in order as its headers are parsed
+///
+/// This allows the same parsing code to find the end of serialized data in a slice, and to read
+/// exactly the serialized data from a reader.
+pub(crate) trait ByteSource {
+    type Error: From<DeserializeError>;
+
+    /// Consumes the next `len` bytes, returning their offset from the start of the data
+    fn take(&mut self, len: usize) -> Result<usize, Self::Error>;
+
+    /// The bytes consumed so far
+    fn consumed(&self) -> &[u8];
+
+    /// Consumes the next `N` bytes, returning a copy of them
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], Self::Error> {
+        let start = self.take(N)?;
+        Ok(self.consumed()[start..start + N].try_into().unwrap())
+    }
+}
+
+/// Parses serialized data at the start of a slice
+pub(crate) struct SliceSource<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SliceSource<'a> {
+    pub(crate) fn new(buffer: &'a [u8]) -> Self {
+        Self { buffer, pos: 0 }
+    }
+
+    /// The number of bytes consumed so far
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// The bytes which have not been consumed yet
+    #[cfg(feature = "alloc")]
+    pub(crate) fn remaining(&self) -> &'a [u8] {
+        &self.buffer[self.pos..]
+    }
+}
+
+impl ByteSource for SliceSource<'_> {
+    type Error = DeserializeError;
+
+    fn take(&mut self, len: usize) -> Result<usize, DeserializeError> {
+        let start = self.pos;
+        let end = start.saturating_add(len);
+        if end > self.buffer.len() {
+            return Err(DeserializeError::Truncated {
+                needed: end,
+                available: self.buffer.len(),
+            });
+        }
+        self.pos = end;
+        Ok(start)
+    }
+
+    fn consumed(&self) -> &[u8] {
+        &self.buffer[..self.pos]
+    }
+}
+
+/// Reads serialized data from a reader into a buffer, reading no further than its end
+#[cfg(feature = "std")]
+pub(crate) struct ReaderSource<'a, R: ?Sized> {
+    reader: &'a mut R,
+    buf: &'a mut alloc::vec::Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, R: std::io::Read + ?Sized> ReaderSource<'a, R> {
+    /// Reads into `buf`, which must be empty
+    pub(crate) fn new(reader: &'a mut R, buf: &'a mut alloc::vec::Vec<u8>) -> Self {
+        debug_assert!(buf.is_empty());
+        Self { reader, buf }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read + ?Sized> ByteSource for ReaderSource<'_, R> {
+    type Error = std::io::Error;
+
+    fn take(&mut self, len: usize) -> std::io::Result<usize> {
+        use std::io::Read;
+
+        let start = self.buf.len();
+        // Grow the buffer as data arrives, rather than trusting `len` to allocate up front
+        let read = (&mut *self.reader).take(len as u64).read_to_end(self.buf)?;
+        if read != len {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(start)
+    }
+
+    fn consumed(&self) -> &[u8] {
+        self.buf
+    }
+}
+
 #[cfg(feature = "alloc")]
 pub(crate) fn get_aligned_spare_capacity(
     dst: &mut alloc::vec::Vec<u8>,
@@ -74,3 +292,112 @@ pub(crate) fn get_aligned_spare_capacity(
 
     &mut spare_capacity[..required_len]
 }
+
+/// The most values ClickHouse stores in the small set form of a `groupBitmap` state
+pub(crate) const CLICKHOUSE_SMALL_SET_SIZE: u64 = 32;
+/// The longest header of a ClickHouse `groupBitmap` state: a kind byte and a 64-bit varint
+#[cfg(feature = "alloc")]
+pub(crate) const CLICKHOUSE_MAX_HEADER_LEN: usize = 11;
+const CLICKHOUSE_SMALL: u8 = 0;
+const CLICKHOUSE_LARGE: u8 = 1;
+
+/// The location of the values in a ClickHouse `groupBitmap` state
+pub(crate) enum ClickHouseState {
+    /// `count` little-endian values, starting at `start`
+    Small { start: usize, count: usize },
+    /// A bitmap in the portable format, in `start..start + len`
+    Large { start: usize, len: usize },
+}
+
+/// Consumes a ClickHouse `groupBitmap` state with values of `value_size` bytes from `src`
+pub(crate) fn parse_clickhouse<S: ByteSource + ?Sized>(
+    src: &mut S,
+    value_size: usize,
+) -> Result<ClickHouseState, S::Error> {
+    let [kind] = src.take_array()?;
+    match kind {
+        CLICKHOUSE_SMALL => {
+            let count = read_varint(src)?;
+            if count > CLICKHOUSE_SMALL_SET_SIZE {
+                return Err(DeserializeError::InvalidHeader {
+                    reason: "too many values for a small set",
+                }
+                .into());
+            }
+            let count = count as usize;
+            let start = src.take(count * value_size)?;
+            Ok(ClickHouseState::Small { start, count })
+        }
+        CLICKHOUSE_LARGE => {
+            let len = usize::try_from(read_varint(src)?).unwrap_or(usize::MAX);
+            let start = src.take(len)?;
+            Ok(ClickHouseState::Large { start, len })
+        }
+        kind => Err(DeserializeError::UnknownCookie {
+            cookie: u32::from(kind),
+        }
+        .into()),
+    }
+}
+
+/// The length of a ClickHouse `groupBitmap` state, given its cardinality, the size of each
+/// value, and the length of the bitmap in the portable format
+pub(crate) fn clickhouse_size(cardinality: u64, value_size: usize, portable_len: usize) -> usize {
+    if cardinality <= CLICKHOUSE_SMALL_SET_SIZE {
+        1 + varint_len(cardinality) + cardinality as usize * value_size
+    } else {
+        1 + varint_len(portable_len as u64) + portable_len
+    }
+}
+
+/// Writes the header of a ClickHouse `groupBitmap` state, returning its length
+///
+/// The header is followed by `cardinality` little-endian values if the cardinality is small
+/// enough, otherwise by `portable_len` bytes of a bitmap in the portable format.
+pub(crate) fn write_clickhouse_header(
+    cardinality: u64,
+    portable_len: usize,
+    dst: &mut [u8],
+) -> usize {
+    let (kind, len) = if cardinality <= CLICKHOUSE_SMALL_SET_SIZE {
+        (CLICKHOUSE_SMALL, cardinality)
+    } else {
+        (CLICKHOUSE_LARGE, portable_len as u64)
+    };
+    dst[0] = kind;
+    1 + write_varint(len, &mut dst[1..])
+}
+
+/// The length of `value` as a LEB128 variable length integer
+const fn varint_len(value: u64) -> usize {
+    let bits = 64 - (value | 1).leading_zeros() as usize;
+    bits.div_ceil(7)
+}
+
+/// Writes `value` as a LEB128 variable length integer, returning its length
+fn write_varint(mut value: u64, dst: &mut [u8]) -> usize {
+    let mut len = 0;
+    while value >= 0x80 {
+        dst[len] = (value as u8) | 0x80;
+        value >>= 7;
+        len += 1;
+    }
+    dst[len] = value as u8;
+    len + 1
+}
+
+/// Consumes a LEB128 variable length integer from `src`
+fn read_varint<S: ByteSource + ?Sized>(src: &mut S) -> Result<u64, S::Error> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let [byte] = src.take_array()?;
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DeserializeError::InvalidHeader {
+        reason: "variable length integer is too long",
+    }
+    .into())
+}
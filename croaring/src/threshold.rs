@@ -0,0 +1,122 @@
+//! The bit-sliced counter shared by [`Bitmap::threshold`] and [`Bitmap64::threshold`]
+
+use crate::{Bitmap, Bitmap64};
+use alloc::vec::Vec;
+
+/// The set operations used by [`threshold`]
+pub(crate) trait ThresholdOps: Clone + Sized {
+    fn new() -> Self;
+    fn is_empty(&self) -> bool;
+    fn and(&self, other: &Self) -> Self;
+    fn and_inplace(&mut self, other: &Self);
+    fn or_inplace(&mut self, other: &Self);
+    fn xor_inplace(&mut self, other: &Self);
+    fn andnot_inplace(&mut self, other: &Self);
+    fn union(bitmaps: &[&Self]) -> Self;
+    fn intersection(bitmaps: &[&Self]) -> Self;
+}
+
+macro_rules! impl_threshold_ops {
+    ($ty:ty, union = $union:path) => {
+        impl ThresholdOps for $ty {
+            #[inline]
+            fn new() -> Self {
+                <$ty>::new()
+            }
+
+            #[inline]
+            fn is_empty(&self) -> bool {
+                <$ty>::is_empty(self)
+            }
+
+            #[inline]
+            fn and(&self, other: &Self) -> Self {
+                <$ty>::and(self, other)
+            }
+
+            #[inline]
+            fn and_inplace(&mut self, other: &Self) {
+                <$ty>::and_inplace(self, other)
+            }
+
+            #[inline]
+            fn or_inplace(&mut self, other: &Self) {
+                <$ty>::or_inplace(self, other)
+            }
+
+            #[inline]
+            fn xor_inplace(&mut self, other: &Self) {
+                <$ty>::xor_inplace(self, other)
+            }
+
+            #[inline]
+            fn andnot_inplace(&mut self, other: &Self) {
+                <$ty>::andnot_inplace(self, other)
+            }
+
+            #[inline]
+            fn union(bitmaps: &[&Self]) -> Self {
+                $union(bitmaps)
+            }
+
+            #[inline]
+            fn intersection(bitmaps: &[&Self]) -> Self {
+                <$ty>::fast_and(bitmaps)
+            }
+        }
+    };
+}
+
+impl_threshold_ops!(Bitmap, union = Bitmap::fast_or);
+impl_threshold_ops!(Bitmap64, union = Bitmap64::union);
+
+/// Computes the values present in at least `k` of `bitmaps`
+///
+/// The number of bitmaps containing each value is counted in bit-sliced form, then compared
+/// against `k` from the most significant bit down.
+pub(crate) fn threshold<B: ThresholdOps>(bitmaps: &[&B], k: usize) -> B {
+    if k > bitmaps.len() {
+        return B::new();
+    }
+    if k <= 1 {
+        return B::union(bitmaps);
+    }
+    if k == bitmaps.len() {
+        return B::intersection(bitmaps);
+    }
+
+    // slices[i] holds the values whose count has bit i set
+    let mut slices: Vec<B> = Vec::new();
+    for &bitmap in bitmaps {
+        let mut carry = bitmap.clone();
+        for slice in &mut slices {
+            let next_carry = slice.and(&carry);
+            slice.xor_inplace(&carry);
+            carry = next_carry;
+            if carry.is_empty() {
+                break;
+            }
+        }
+        if !carry.is_empty() {
+            slices.push(carry);
+        }
+    }
+    if k >> slices.len() != 0 {
+        // Every count is less than k
+        return B::new();
+    }
+
+    // Compare the counts against k, from the most significant bit down
+    let mut greater = B::new();
+    let mut equal = B::union(bitmaps);
+    for (i, slice) in slices.iter().enumerate().rev() {
+        if (k >> i) & 1 == 1 {
+            equal.and_inplace(slice);
+        } else {
+            greater.or_inplace(&equal.and(slice));
+            equal.andnot_inplace(slice);
+        }
+    }
+    greater.or_inplace(&equal);
+    greater
+}
@@ -3,11 +3,13 @@ use crate::Treemap;
 
 use super::util;
 use crate::treemap::{Deserializer, Serializer};
+use crate::DeserializeError;
 use alloc::collections::btree_map::Entry;
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 use core::cmp::Ordering;
-use core::ops::{Bound, RangeBounds};
+use core::mem;
+use core::ops::{Bound, RangeBounds, RangeInclusive};
 use core::prelude::v1::*;
 
 impl Treemap {
@@ -120,6 +122,29 @@ impl Treemap {
         self.add_range_inclusive(start, end);
     }
 
+    /// Creates a new treemap with all values in each of `ranges`
+    ///
+    /// This is the inverse of [`Treemap::ranges`]. The ranges may overlap, and need not be sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let start = u64::from(u32::MAX) - 1;
+    /// let treemap = Treemap::from_ranges([10..=12, 1..=2, start..=start + 3]);
+    /// assert_eq!(treemap.cardinality(), 9);
+    /// assert_eq!(treemap.ranges().collect::<Vec<_>>(), [1..=2, 10..=12, start..=start + 3]);
+    /// ```
+    #[must_use]
+    pub fn from_ranges<I: IntoIterator<Item = RangeInclusive<u64>>>(ranges: I) -> Self {
+        let mut result = Self::new();
+        for range in ranges {
+            result.add_range(range);
+        }
+        result
+    }
+
     fn add_range_inclusive(&mut self, start: u64, end: u64) {
         if start > end {
             return;
@@ -234,7 +259,7 @@ impl Treemap {
     #[must_use]
     pub fn is_subset(&self, other: &Treemap) -> bool {
         self.map.iter().all(|(key, lhs)| {
-            lhs.is_empty() || other.map.get(key).map_or(false, |rhs| lhs.is_subset(rhs))
+            lhs.is_empty() || other.map.get(key).is_some_and(|rhs| lhs.is_subset(rhs))
         })
     }
 
@@ -430,6 +455,102 @@ impl Treemap {
         self.remove_range_inclusive(start, end);
     }
 
+    /// Splits the treemap in two at `at`
+    ///
+    /// Returns a treemap containing all values `>= at`, leaving all values `< at` in `self`.
+    ///
+    /// Inner bitmaps are moved to the new treemap rather than copied: only the bitmap holding
+    /// `at`, if any, is split with [`Bitmap::split_off`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let mut treemap = Treemap::of(&[1, 2, 3, 1 << 40, u64::MAX]);
+    /// let tail = treemap.split_off(3);
+    /// assert_eq!(treemap.iter().collect::<Vec<_>>(), [1, 2]);
+    /// assert_eq!(tail.iter().collect::<Vec<_>>(), [3, 1 << 40, u64::MAX]);
+    /// ```
+    #[must_use]
+    pub fn split_off(&mut self, at: u64) -> Treemap {
+        let (high, low) = util::split(at);
+        let mut tail = Treemap {
+            map: self.map.split_off(&high),
+        };
+        if low != 0 {
+            if let Entry::Occupied(mut e) = tail.map.entry(high) {
+                let upper = e.get_mut().split_off(low);
+                let lower = if upper.is_empty() {
+                    e.remove()
+                } else {
+                    mem::replace(e.get_mut(), upper)
+                };
+                if !lower.is_empty() {
+                    self.map.insert(high, lower);
+                }
+            }
+        }
+        tail
+    }
+
+    /// Moves all values of `other` into `self`, leaving `other` empty
+    ///
+    /// Inner bitmaps are moved rather than copied, and bitmaps present in both treemaps are
+    /// combined with [`Bitmap::append`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let mut treemap = Treemap::of(&[1, 2, 3]);
+    /// let mut other = Treemap::of(&[2, 4, 1 << 40]);
+    /// treemap.append(&mut other);
+    /// assert_eq!(treemap.iter().collect::<Vec<_>>(), [1, 2, 3, 4, 1 << 40]);
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Treemap) {
+        for (key, mut bitmap) in mem::take(&mut other.map) {
+            match self.map.entry(key) {
+                Entry::Vacant(e) => {
+                    e.insert(bitmap);
+                }
+                Entry::Occupied(mut e) => e.get_mut().append(&mut bitmap),
+            }
+        }
+    }
+
+    /// Removes all values outside of `range`
+    ///
+    /// Inner bitmaps entirely outside of `range` are dropped without being visited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let mut treemap = Treemap::of(&[1, 2, 3, 1 << 40, u64::MAX]);
+    /// treemap.retain_range(2..=1 << 40);
+    /// assert_eq!(treemap.iter().collect::<Vec<_>>(), [2, 3, 1 << 40]);
+    ///
+    /// treemap.retain_range(3..3);
+    /// assert!(treemap.is_empty());
+    /// ```
+    #[doc(alias = "truncate_range")]
+    pub fn retain_range<R: RangeBounds<u64>>(&mut self, range: R) {
+        let (start, end) = range_to_inclusive(range);
+        if start > end {
+            self.clear();
+            return;
+        }
+        let mut retained = self.split_off(start);
+        if let Some(after) = end.checked_add(1) {
+            drop(retained.split_off(after));
+        }
+        *self = retained;
+    }
+
     fn remove_range_inclusive(&mut self, start: u64, end: u64) {
         if start > end {
             return;
@@ -479,6 +600,121 @@ impl Treemap {
         }
     }
 
+    /// Returns a new treemap with all values shifted by the given offset
+    ///
+    /// Any values which would underflow or overflow `u64` are dropped.
+    ///
+    /// When `offset` is a multiple of 2^32, bitmaps are only moved to new keys. Otherwise, each
+    /// bitmap is split across two keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let treemap = Treemap::of(&[0, 1, u64::from(u32::MAX), u64::MAX]);
+    /// let shifted_down = treemap.add_offset(-1);
+    /// assert_eq!(
+    ///     shifted_down.iter().collect::<Vec<_>>(),
+    ///     [0, u64::from(u32::MAX) - 1, u64::MAX - 1],
+    /// );
+    /// let shifted_up = treemap.add_offset(1);
+    /// assert_eq!(shifted_up.iter().collect::<Vec<_>>(), [1, 2, u64::from(u32::MAX) + 1]);
+    /// let big_shifted = treemap.add_offset(i128::from(u64::MAX) + 1);
+    /// assert!(big_shifted.is_empty());
+    /// ```
+    #[must_use]
+    pub fn add_offset(&self, offset: i128) -> Self {
+        let (high_offset, low_offset) = split_offset(offset);
+        let mut result = Treemap::new();
+        for (&key, bitmap) in &self.map {
+            let high = i128::from(key) + high_offset;
+            if low_offset == 0 {
+                result.merge_shifted(high, bitmap.clone());
+                continue;
+            }
+            // Values which stay below 2^32 remain at `high`, the rest carry into `high + 1`
+            result.merge_shifted(high, bitmap.add_offset(low_offset));
+            result.merge_shifted(high + 1, bitmap.add_offset(low_offset - (1 << 32)));
+        }
+        result
+    }
+
+    /// Shifts all values in the treemap by `offset`, in place
+    ///
+    /// Any values which would underflow or overflow `u64` are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let mut treemap = Treemap::of(&[0, 1, u64::from(u32::MAX), u64::MAX]);
+    /// treemap.add_offset_inplace(-1);
+    /// assert_eq!(
+    ///     treemap.iter().collect::<Vec<_>>(),
+    ///     [0, u64::from(u32::MAX) - 1, u64::MAX - 1],
+    /// );
+    /// treemap.add_offset_inplace(1 << 32);
+    /// assert_eq!(
+    ///     treemap.iter().collect::<Vec<_>>(),
+    ///     [1 << 32, (1 << 32) + u64::from(u32::MAX) - 1],
+    /// );
+    /// ```
+    pub fn add_offset_inplace(&mut self, offset: i128) {
+        let (high_offset, low_offset) = split_offset(offset);
+        if low_offset != 0 {
+            *self = self.add_offset(offset);
+            return;
+        }
+        for (key, bitmap) in mem::take(&mut self.map) {
+            self.merge_shifted(i128::from(key) + high_offset, bitmap);
+        }
+    }
+
+    /// Returns a new treemap with all values shifted by the given offset
+    ///
+    /// Returns the new treemap, and the number of values which were dropped because they would
+    /// underflow or overflow `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let treemap = Treemap::of(&[0, 1, 1000, u64::MAX]);
+    /// let (shifted, dropped) = treemap.add_offset_checked(-1);
+    /// assert_eq!(shifted.iter().collect::<Vec<_>>(), [0, 999, u64::MAX - 1]);
+    /// assert_eq!(dropped, 1);
+    ///
+    /// let (shifted, dropped) = treemap.add_offset_checked(i128::from(u64::MAX) + 1);
+    /// assert!(shifted.is_empty());
+    /// assert_eq!(dropped, 4);
+    /// ```
+    #[must_use]
+    pub fn add_offset_checked(&self, offset: i128) -> (Self, u64) {
+        let result = self.add_offset(offset);
+        // Shifting never merges distinct values, so every missing value was dropped
+        let dropped = self.cardinality() - result.cardinality();
+        (result, dropped)
+    }
+
+    /// Merges `bitmap` into the bucket at `key`, dropping it if `key` is not a valid bucket
+    fn merge_shifted(&mut self, key: i128, mut bitmap: Bitmap) {
+        let Ok(key) = u32::try_from(key) else {
+            return;
+        };
+        if bitmap.is_empty() {
+            return;
+        }
+        match self.map.entry(key) {
+            Entry::Vacant(e) => {
+                e.insert(bitmap);
+            }
+            Entry::Occupied(mut e) => e.get_mut().append(&mut bitmap),
+        }
+    }
+
     /// Reallocate memory to shrink the memory usage
     pub fn shrink_to_fit(&mut self) {
         for bitmap in self.map.values_mut() {
@@ -520,6 +756,72 @@ impl Treemap {
         None
     }
 
+    /// Selects the elements at each of the given positions
+    ///
+    /// Writes the element at `positions[i]` into `dst[i]`, stopping at the first position which
+    /// is not less than the cardinality of the treemap. Returns the number of elements written.
+    ///
+    /// This is equivalent to calling [`select`][Self::select] for each position, but when
+    /// `positions` is sorted, the treemap is only walked once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is shorter than `positions`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let treemap = Treemap::of(&[1, 10, u64::from(u32::MAX) + 1, u64::MAX]);
+    ///
+    /// let mut dst = [0; 3];
+    /// assert_eq!(treemap.select_many(&[0, 2, 3], &mut dst), 3);
+    /// assert_eq!(dst, [1, u64::from(u32::MAX) + 1, u64::MAX]);
+    ///
+    /// // Selection stops at the first out-of-range position
+    /// let mut dst = [0; 3];
+    /// assert_eq!(treemap.select_many(&[1, 4, 0], &mut dst), 1);
+    /// assert_eq!(dst[0], 10);
+    /// ```
+    pub fn select_many(&self, positions: &[u64], dst: &mut [u64]) -> usize {
+        let dst = &mut dst[..positions.len()];
+        if !positions.windows(2).all(|w| w[0] <= w[1]) {
+            for (i, (&position, out)) in positions.iter().zip(dst).enumerate() {
+                match self.select(position) {
+                    Some(value) => *out = value,
+                    None => return i,
+                }
+            }
+            return positions.len();
+        }
+
+        let mut local_positions = Vec::new();
+        let mut local_values = Vec::new();
+        let mut base = 0;
+        let mut i = 0;
+        for (&key, bitmap) in &self.map {
+            if i == positions.len() {
+                break;
+            }
+            let sub_cardinality = bitmap.cardinality();
+            let end = i + positions[i..].partition_point(|&p| p - base < sub_cardinality);
+            // p - base < sub_cardinality <= 2^32, so the local position fits in a u32
+            local_positions.clear();
+            local_positions.extend(positions[i..end].iter().map(|&p| (p - base) as u32));
+            local_values.clear();
+            local_values.resize(local_positions.len(), 0);
+            let n = bitmap.select_many(&local_positions, &mut local_values);
+            debug_assert_eq!(n, local_values.len());
+            for (out, &low) in dst[i..end].iter_mut().zip(&local_values) {
+                *out = util::join(key, low);
+            }
+            i = end;
+            base += sub_cardinality;
+        }
+        i
+    }
+
     /// Returns the number of elements that are smaller or equal to `value`
     #[must_use]
     pub fn rank(&self, value: u64) -> u64 {
@@ -539,6 +841,68 @@ impl Treemap {
         rank
     }
 
+    /// Computes the [rank][Self::rank] of each of the given values
+    ///
+    /// Writes the number of elements smaller or equal to `values[i]` into `ranks[i]`.
+    ///
+    /// This is equivalent to calling [`rank`][Self::rank] for each value, but when `values`
+    /// is sorted, the treemap is only walked once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ranks` is shorter than `values`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let treemap = Treemap::of(&[1, 10, u64::from(u32::MAX) + 1, u64::MAX]);
+    ///
+    /// let mut ranks = [0; 4];
+    /// treemap.rank_many(&[0, 10, u64::from(u32::MAX) + 5, u64::MAX], &mut ranks);
+    /// assert_eq!(ranks, [0, 2, 3, 4]);
+    ///
+    /// // Unsorted values are allowed
+    /// treemap.rank_many(&[u64::MAX, 10, 0, 1], &mut ranks);
+    /// assert_eq!(ranks, [4, 2, 0, 1]);
+    /// ```
+    pub fn rank_many(&self, values: &[u64], ranks: &mut [u64]) {
+        let ranks = &mut ranks[..values.len()];
+        if !values.windows(2).all(|w| w[0] <= w[1]) {
+            for (&value, rank) in values.iter().zip(ranks) {
+                *rank = self.rank(value);
+            }
+            return;
+        }
+
+        let mut bitmaps = self.map.iter().peekable();
+        let mut local_values = Vec::new();
+        let mut base = 0;
+        let mut i = 0;
+        while i < values.len() {
+            let (hi, _) = util::split(values[i]);
+            let end = i + values[i..].partition_point(|&v| util::split(v).0 == hi);
+            while let Some((_, bitmap)) = bitmaps.next_if(|&(&key, _)| key < hi) {
+                base += bitmap.cardinality();
+            }
+            let group_ranks = &mut ranks[i..end];
+            match bitmaps.next_if(|&(&key, _)| key == hi) {
+                Some((_, bitmap)) => {
+                    local_values.clear();
+                    local_values.extend(values[i..end].iter().map(|&v| util::split(v).1));
+                    bitmap.rank_many(&local_values, group_ranks);
+                    for rank in group_ranks {
+                        *rank += base;
+                    }
+                    base += bitmap.cardinality();
+                }
+                None => group_ranks.fill(base),
+            }
+            i = end;
+        }
+    }
+
     /// Returns the index of `value` in the set (zero based index)
     ///
     /// If the set doesn't contain `value`, return None
@@ -723,6 +1087,85 @@ impl Treemap {
         }
     }
 
+    /// Computes the intersection of many treemaps
+    ///
+    /// Only the high 32 bits present in every treemap are considered, and the bitmaps for each
+    /// are intersected with [`Bitmap::fast_and`]. An empty slice produces an empty treemap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let treemap1 = Treemap::of(&[15, 25, u64::MAX]);
+    /// let treemap2 = Treemap::of(&[25, 35, u64::MAX]);
+    /// let treemap3 = Treemap::of(&[5, 25, u64::MAX]);
+    ///
+    /// let treemap4 = Treemap::fast_and(&[&treemap1, &treemap2, &treemap3]);
+    ///
+    /// assert_eq!(treemap4.cardinality(), 2);
+    /// assert!(treemap4.contains(25));
+    /// assert!(treemap4.contains(u64::MAX));
+    ///
+    /// assert!(Treemap::fast_and(&[]).is_empty());
+    /// ```
+    #[must_use]
+    pub fn fast_and(treemaps: &[&Treemap]) -> Self {
+        let mut result = Treemap::new();
+        Self::fast_and_for_each(treemaps, |key, bitmaps| {
+            let bitmap = Bitmap::fast_and(bitmaps);
+            if !bitmap.is_empty() {
+                result.map.insert(key, bitmap);
+            }
+        });
+        result
+    }
+
+    /// Computes the cardinality of the intersection between many treemaps
+    ///
+    /// This is equivalent to `Treemap::fast_and(treemaps).cardinality()`, but the bitmaps for each
+    /// key are intersected with [`Bitmap::fast_and_cardinality`], so the intersection is never
+    /// collected into a treemap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let treemap1 = Treemap::of(&[15, 25, u64::MAX]);
+    /// let treemap2 = Treemap::of(&[25, 35, u64::MAX]);
+    ///
+    /// assert_eq!(Treemap::fast_and_cardinality(&[&treemap1, &treemap2]), 2);
+    /// assert_eq!(Treemap::fast_and_cardinality(&[&treemap1, &Treemap::new()]), 0);
+    /// ```
+    #[must_use]
+    pub fn fast_and_cardinality(treemaps: &[&Treemap]) -> u64 {
+        let mut cardinality = 0;
+        Self::fast_and_for_each(treemaps, |_, bitmaps| {
+            cardinality += Bitmap::fast_and_cardinality(bitmaps);
+        });
+        cardinality
+    }
+
+    /// Calls `f` with the bitmaps of every treemap for each key present in all treemaps
+    fn fast_and_for_each<F: FnMut(u32, &[&Bitmap])>(treemaps: &[&Treemap], mut f: F) {
+        // Walk the keys of the treemap with the fewest
+        let Some(smallest) = treemaps.iter().min_by_key(|treemap| treemap.map.len()) else {
+            return;
+        };
+        let mut bitmaps = Vec::with_capacity(treemaps.len());
+        'keys: for &key in smallest.map.keys() {
+            bitmaps.clear();
+            for treemap in treemaps {
+                match treemap.map.get(&key) {
+                    Some(bitmap) => bitmaps.push(bitmap),
+                    None => continue 'keys,
+                }
+            }
+            f(key, &bitmaps);
+        }
+    }
+
     /// Or computes the union between two bitmaps and returns the result
     /// as a new bitmap
     ///
@@ -1114,6 +1557,63 @@ impl Treemap {
         D::try_deserialize(buffer).map(|(treemap, _bytes_read)| treemap)
     }
 
+    /// Deserializes a treemap in format `D` from the beginning of `buffer`, describing any failure
+    ///
+    /// Returns the treemap, and the number of bytes it was deserialized from: `buffer` may continue
+    /// after the end of the treemap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Treemap, DeserializeError, Portable};
+    ///
+    /// let treemap = Treemap::of(&[1, 2, 3, 1 << 40]);
+    /// let mut buffer = treemap.serialize::<Portable>();
+    /// let len = buffer.len();
+    /// buffer.extend_from_slice(b"more data");
+    ///
+    /// let (deserialized, read) = Treemap::try_deserialize_prefix::<Portable>(&buffer).unwrap();
+    /// assert_eq!(deserialized, treemap);
+    /// assert_eq!(read, len);
+    ///
+    /// let err = Treemap::try_deserialize_prefix::<Portable>(&buffer[..len - 1]).unwrap_err();
+    /// assert_eq!(err, DeserializeError::Truncated { needed: len, available: len - 1 });
+    /// ```
+    pub fn try_deserialize_prefix<D: Deserializer>(
+        buffer: &[u8],
+    ) -> Result<(Self, usize), DeserializeError> {
+        D::try_deserialize_prefix(buffer)
+    }
+
+    /// Deserializes a treemap in format `D` which fills the whole of `buffer`, describing any failure
+    ///
+    /// Unlike [`Self::try_deserialize_prefix`], it is an error for `buffer` to continue after
+    /// the end of the treemap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Treemap, DeserializeError, Portable};
+    ///
+    /// let treemap = Treemap::of(&[1, 2, 3, 1 << 40]);
+    /// let mut buffer = treemap.serialize::<Portable>();
+    /// assert_eq!(Treemap::try_deserialize_exact::<Portable>(&buffer).unwrap(), treemap);
+    ///
+    /// buffer.push(0);
+    /// let err = Treemap::try_deserialize_exact::<Portable>(&buffer).unwrap_err();
+    /// assert_eq!(err, DeserializeError::TrailingBytes { extra: 1 });
+    ///
+    /// let err = Treemap::try_deserialize_exact::<Portable>(&[3]).unwrap_err();
+    /// assert_eq!(err, DeserializeError::Truncated { needed: 8, available: 1 });
+    /// ```
+    pub fn try_deserialize_exact<D: Deserializer>(buffer: &[u8]) -> Result<Self, DeserializeError> {
+        let (treemap, len) = D::try_deserialize_prefix(buffer)?;
+        match buffer.len() - len {
+            0 => Ok(treemap),
+            extra => Err(DeserializeError::TrailingBytes { extra }),
+        }
+    }
+
     /// Given a serialized treemap as slice of bytes in format `S `, returns a treemap instance.
     /// See example of [`Self::serialize`] function.
     ///
@@ -1123,6 +1623,43 @@ impl Treemap {
         Self::try_deserialize::<D>(buffer).unwrap_or_default()
     }
 
+    /// Reads a serialized treemap in format `D` from a reader
+    ///
+    /// Only the bytes of the serialized treemap are read, as determined by its header and the
+    /// headers of its bitmaps, so several treemaps can be read one after another from the same
+    /// reader.
+    ///
+    /// Returns an error if reading fails, if the reader ends before the end of the treemap (with
+    /// [`UnexpectedEof`][std::io::ErrorKind::UnexpectedEof]), or if the data is not a valid
+    /// treemap (with [`InvalidData`][std::io::ErrorKind::InvalidData]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{JvmLegacy, Portable, Treemap};
+    /// use std::io::Cursor;
+    ///
+    /// let treemap1 = Treemap::of(&[1, 2, 3, 1 << 40]);
+    /// let treemap2: Treemap = (0..100_000).collect();
+    ///
+    /// let mut data = Vec::new();
+    /// treemap1.serialize_into_writer::<Portable, _>(&mut data).unwrap();
+    /// treemap2.serialize_into_writer::<JvmLegacy, _>(&mut data).unwrap();
+    ///
+    /// let mut reader = Cursor::new(data);
+    /// assert_eq!(Treemap::deserialize_from_reader::<Portable, _>(&mut reader).unwrap(), treemap1);
+    /// assert_eq!(Treemap::deserialize_from_reader::<JvmLegacy, _>(&mut reader).unwrap(), treemap2);
+    ///
+    /// let err = Treemap::deserialize_from_reader::<Portable, _>(&mut reader).unwrap_err();
+    /// assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn deserialize_from_reader<D: Deserializer, R: std::io::Read>(
+        reader: R,
+    ) -> std::io::Result<Self> {
+        D::deserialize_from_reader(reader)
+    }
+
     /// Creates a new treemap from a slice of u64 integers
     ///
     /// # Examples
@@ -1265,7 +1802,14 @@ where
     treemap
 }
 
-fn range_to_inclusive<R: RangeBounds<u64>>(range: R) -> (u64, u64) {
+/// Splits `offset` into an offset for the bucket keys, and an offset within buckets in `0..2^32`
+fn split_offset(offset: i128) -> (i128, i64) {
+    let high = offset.div_euclid(1 << 32);
+    let low = offset.rem_euclid(1 << 32);
+    (high, low as i64)
+}
+
+pub(super) fn range_to_inclusive<R: RangeBounds<u64>>(range: R) -> (u64, u64) {
     let start = match range.start_bound() {
         Bound::Included(&i) => i,
         Bound::Excluded(&i) => match i.checked_add(1) {
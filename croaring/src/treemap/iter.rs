@@ -1,8 +1,10 @@
+use super::imp::range_to_inclusive;
 use super::util;
-use crate::bitmap::BitmapIterator;
+use crate::bitmap::{BitmapIterator, BitmapRanges};
 use crate::{Bitmap, Treemap};
 use alloc::collections::btree_map;
-use core::iter;
+use core::iter::FusedIterator;
+use core::ops::{Bound, RangeBounds, RangeInclusive};
 
 struct To64Iter<'a> {
     key: u32,
@@ -17,6 +19,12 @@ impl<'a> Iterator for To64Iter<'a> {
     }
 }
 
+impl DoubleEndedIterator for To64Iter<'_> {
+    fn next_back(&mut self) -> Option<u64> {
+        self.iterator.next_back().map(|n| util::join(self.key, n))
+    }
+}
+
 fn to64iter<'a>((key, bitmap): (&'a u32, &'a Bitmap)) -> To64Iter<'a> {
     assert!(!bitmap.is_empty(), "empty bitmap at {key}");
     To64Iter {
@@ -25,24 +33,143 @@ fn to64iter<'a>((key, bitmap): (&'a u32, &'a Bitmap)) -> To64Iter<'a> {
     }
 }
 
-type InnerIter<'a> = iter::FlatMap<
-    btree_map::Iter<'a, u32, Bitmap>,
-    To64Iter<'a>,
-    fn((&'a u32, &'a Bitmap)) -> To64Iter<'a>,
->;
-
 /// Iterator over values stored in the treemap
 ///
-/// Values are ordered in ascending order
+/// Values are ordered in ascending order. They can be taken from both ends, and the number of
+/// values left is known exactly. It is counted when the iterator is created, and kept up to date
+/// as values are taken. On 64-bit targets, this also implements [`ExactSizeIterator`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Treemap;
+///
+/// let treemap = Treemap::of(&[1, 2, 3, u64::MAX - 1, u64::MAX]);
+/// let mut iter = treemap.iter();
+/// assert_eq!(iter.size_hint(), (5, Some(5)));
+/// assert_eq!(iter.next(), Some(1));
+/// assert_eq!(iter.next_back(), Some(u64::MAX));
+/// assert_eq!(iter.size_hint(), (3, Some(3)));
+/// assert_eq!(iter.rev().collect::<Vec<_>>(), [u64::MAX - 1, 3, 2]);
+/// ```
 pub struct TreemapIterator<'a> {
-    iter: InnerIter<'a>,
+    map: btree_map::Range<'a, u32, Bitmap>,
+    front: Option<To64Iter<'a>>,
+    back: Option<To64Iter<'a>>,
+    /// Number of values left to be returned
+    len: u64,
 }
 
 impl<'a> TreemapIterator<'a> {
     fn new(treemap: &'a Treemap) -> Self {
-        let iter = treemap.map.iter().flat_map(to64iter as _);
+        TreemapIterator {
+            map: treemap.map.range(..),
+            front: None,
+            back: None,
+            len: treemap.cardinality(),
+        }
+    }
 
-        TreemapIterator { iter }
+    fn with_range<R: RangeBounds<u64>>(treemap: &'a Treemap, range: R) -> Self {
+        let (start, end) = range_to_inclusive(range);
+        if start > end {
+            return TreemapIterator {
+                map: treemap.map.range(0..0),
+                front: None,
+                back: None,
+                len: 0,
+            };
+        }
+        let (start_high, start_low) = util::split(start);
+        let (end_high, end_low) = util::split(end);
+        let partial = |key: u32, range: RangeInclusive<u32>| {
+            treemap.map.get(&key).map(|bitmap| To64Iter {
+                key,
+                iterator: bitmap.iter_range(range),
+            })
+        };
+        if start_high == end_high {
+            return TreemapIterator::from_parts(
+                treemap.map.range(0..0),
+                partial(start_high, start_low..=end_low),
+                None,
+            );
+        }
+        TreemapIterator::from_parts(
+            treemap
+                .map
+                .range((Bound::Excluded(start_high), Bound::Excluded(end_high))),
+            partial(start_high, start_low..=u32::MAX),
+            partial(end_high, 0..=end_low),
+        )
+    }
+
+    fn from_parts(
+        map: btree_map::Range<'a, u32, Bitmap>,
+        front: Option<To64Iter<'a>>,
+        back: Option<To64Iter<'a>>,
+    ) -> Self {
+        let partial =
+            |iter: &Option<To64Iter<'_>>| iter.as_ref().map_or(0, |iter| iter.iterator.remaining());
+        let inner: u64 = map.clone().map(|(_, bitmap)| bitmap.cardinality()).sum();
+        let len = partial(&front) + inner + partial(&back);
+        TreemapIterator {
+            map,
+            front,
+            back,
+            len,
+        }
+    }
+
+    /// Attempt to read many values from the iterator into `dst`
+    ///
+    /// Returns the number of items read from the iterator, may be `< dst.len()` iff
+    /// the iterator is exhausted.
+    ///
+    /// This can be much more efficient than repeated iteration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let mut treemap = Treemap::new();
+    /// treemap.add_range(0..100);
+    /// treemap.add(u64::MAX);
+    ///
+    /// let mut buf = [0; 64];
+    /// let mut iter = treemap.iter();
+    /// assert_eq!(iter.next_many(&mut buf), 64);
+    /// assert_eq!(buf[63], 63);
+    /// assert_eq!(iter.next_many(&mut buf), 37);
+    /// assert_eq!(buf[..37], (64..100).chain([u64::MAX]).collect::<Vec<_>>()[..]);
+    /// assert_eq!(iter.next_many(&mut buf), 0);
+    /// ```
+    pub fn next_many(&mut self, dst: &mut [u64]) -> usize {
+        let mut buf = [0; 256];
+        let mut read = 0;
+        while read < dst.len() {
+            if let Some(front) = &mut self.front {
+                let want = (dst.len() - read).min(buf.len());
+                let n = front.iterator.next_many(&mut buf[..want]);
+                for (dst, &low) in dst[read..read + n].iter_mut().zip(&buf[..n]) {
+                    *dst = util::join(front.key, low);
+                }
+                read += n;
+                if n == want {
+                    continue;
+                }
+            }
+            self.front = match self.map.next() {
+                Some(entry) => Some(to64iter(entry)),
+                None => match self.back.take() {
+                    Some(back) => Some(back),
+                    None => break,
+                },
+            };
+        }
+        self.len -= read as u64;
+        read
     }
 }
 
@@ -50,10 +177,60 @@ impl<'a> Iterator for TreemapIterator<'a> {
     type Item = u64;
 
     fn next(&mut self) -> Option<u64> {
-        self.iter.next()
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        loop {
+            if let Some(value) = self.front.as_mut().and_then(Iterator::next) {
+                return Some(value);
+            }
+            match self.map.next() {
+                Some(entry) => self.front = Some(to64iter(entry)),
+                None => {
+                    self.front = None;
+                    return self.back.as_mut()?.next();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.len) {
+            Ok(len) => (len, Some(len)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+
+    fn last(mut self) -> Option<u64> {
+        self.next_back()
+    }
+}
+
+impl DoubleEndedIterator for TreemapIterator<'_> {
+    fn next_back(&mut self) -> Option<u64> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        loop {
+            if let Some(value) = self.back.as_mut().and_then(DoubleEndedIterator::next_back) {
+                return Some(value);
+            }
+            match self.map.next_back() {
+                Some(entry) => self.back = Some(to64iter(entry)),
+                None => {
+                    self.back = None;
+                    return self.front.as_mut()?.next_back();
+                }
+            }
+        }
     }
 }
 
+#[cfg(target_pointer_width = "64")]
+impl ExactSizeIterator for TreemapIterator<'_> {}
+
 impl Treemap {
     /// Returns an iterator over each value stored in the bitmap.
     /// Returned values are ordered in ascending order.
@@ -82,8 +259,120 @@ impl Treemap {
     pub fn iter(&self) -> TreemapIterator<'_> {
         TreemapIterator::new(self)
     }
+
+    /// Returns an iterator over the values stored in the treemap which are in `range`
+    ///
+    /// The iterator supports everything [`Treemap::iter`] does, including
+    /// [`next_many`](TreemapIterator::next_many) and reverse iteration, without ever returning a
+    /// value outside of `range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let big = u64::from(u32::MAX);
+    /// let treemap = Treemap::of(&[1, 5, big, big + 1, big * 3, u64::MAX]);
+    ///
+    /// assert_eq!(treemap.iter_range(5..big * 3).collect::<Vec<_>>(), [5, big, big + 1]);
+    /// assert_eq!(
+    ///     treemap.iter_range(5..=big * 3).rev().collect::<Vec<_>>(),
+    ///     [big * 3, big + 1, big, 5]
+    /// );
+    /// assert_eq!(treemap.iter_range(big + 1..).collect::<Vec<_>>(), [big + 1, big * 3, u64::MAX]);
+    /// assert_eq!(treemap.iter_range(..).count(), 6);
+    /// assert_eq!(treemap.iter_range(2..5).next(), None);
+    ///
+    /// let mut buf = [0; 10];
+    /// let mut iter = treemap.iter_range(2..=big);
+    /// assert_eq!(iter.next_many(&mut buf), 2);
+    /// assert_eq!(buf[..2], [5, big]);
+    /// ```
+    #[must_use]
+    pub fn iter_range<R: RangeBounds<u64>>(&self, range: R) -> TreemapIterator<'_> {
+        TreemapIterator::with_range(self, range)
+    }
+
+    /// Returns an iterator over the maximal runs of consecutive values in the treemap
+    ///
+    /// Each run is returned as an inclusive range, in ascending order. Runs which span several
+    /// inner bitmaps are returned as a single range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let mut treemap = Treemap::of(&[1, 2, 3, 7, u64::MAX]);
+    /// treemap.add_range(0xFFFF_FFF0..=0x2_0000_0010);
+    ///
+    /// let ranges: Vec<_> = treemap.ranges().collect();
+    /// assert_eq!(
+    ///     ranges,
+    ///     [1..=3, 7..=7, 0xFFFF_FFF0..=0x2_0000_0010, u64::MAX..=u64::MAX]
+    /// );
+    ///
+    /// assert_eq!(Treemap::from_ranges(ranges), treemap);
+    /// ```
+    #[must_use]
+    pub fn ranges(&self) -> TreemapRanges<'_> {
+        TreemapRanges::new(self)
+    }
+}
+
+/// Iterator over the maximal runs of consecutive values in a treemap
+///
+/// Runs are returned in ascending order. See [`Treemap::ranges`].
+#[derive(Debug, Clone)]
+pub struct TreemapRanges<'a> {
+    map: btree_map::Iter<'a, u32, Bitmap>,
+    current: Option<(u32, BitmapRanges<'a>)>,
+    pending: Option<RangeInclusive<u64>>,
+}
+
+impl<'a> TreemapRanges<'a> {
+    fn new(treemap: &'a Treemap) -> Self {
+        TreemapRanges {
+            map: treemap.map.iter(),
+            current: None,
+            pending: None,
+        }
+    }
+
+    /// Returns the next run, without joining runs which cross a bitmap boundary
+    fn next_split(&mut self) -> Option<RangeInclusive<u64>> {
+        loop {
+            if let Some((key, ranges)) = &mut self.current {
+                if let Some(range) = ranges.next() {
+                    let key = *key;
+                    return Some(util::join(key, *range.start())..=util::join(key, *range.end()));
+                }
+            }
+            let (&key, bitmap) = self.map.next()?;
+            self.current = Some((key, bitmap.ranges()));
+        }
+    }
+}
+
+impl Iterator for TreemapRanges<'_> {
+    type Item = RangeInclusive<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut range = self.pending.take().or_else(|| self.next_split())?;
+        while let Some(next) = self.next_split() {
+            if range.end().checked_add(1) == Some(*next.start()) {
+                range = *range.start()..=*next.end();
+            } else {
+                self.pending = Some(next);
+                break;
+            }
+        }
+        Some(range)
+    }
 }
 
+impl FusedIterator for TreemapRanges<'_> {}
+
 impl FromIterator<u64> for Treemap {
     /// Convenience method for creating treemap from an iterator.
     ///
@@ -25,16 +25,22 @@ use alloc::collections::BTreeMap;
 mod imp;
 mod iter;
 mod ops;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "serde")]
+mod serde;
 mod serialization;
 mod util;
 
-pub use iter::TreemapIterator;
+pub use iter::{TreemapIterator, TreemapRanges};
+#[cfg(feature = "rayon")]
+pub use par::TreemapParIter;
 pub use serialization::{Deserializer, Serializer};
 
 /// A RoaringBitmap-based structure that supports 64bit unsigned integer values
 ///
 /// Implemented as a [`BTreeMap`] of [`Bitmap`]s.
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone)]
 pub struct Treemap {
     /// The underlying map of bitmaps
     pub map: BTreeMap<u32, Bitmap>,
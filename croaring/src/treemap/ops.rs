@@ -1,7 +1,12 @@
+use core::cmp::Ordering;
 use core::fmt;
-use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+use core::hash::{Hash, Hasher};
+use core::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Shl, ShlAssign, Shr, ShrAssign,
+    Sub, SubAssign,
+};
 
-use super::Treemap;
+use super::{util, Treemap};
 
 impl fmt::Debug for Treemap {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -25,6 +30,98 @@ impl Default for Treemap {
     }
 }
 
+impl PartialEq for Treemap {
+    /// Compares the values in the treemaps
+    ///
+    /// Empty bitmaps in [`Treemap::map`] are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, Treemap};
+    ///
+    /// let treemap1 = Treemap::of(&[1, 2]);
+    /// let mut treemap2 = treemap1.clone();
+    /// treemap2.map.insert(5, Bitmap::new());
+    ///
+    /// assert_eq!(treemap1, treemap2);
+    /// ```
+    fn eq(&self, other: &Self) -> bool {
+        self.non_empty_bitmaps().eq(other.non_empty_bitmaps())
+    }
+}
+
+impl Eq for Treemap {}
+
+impl Hash for Treemap {
+    /// Hashes the values in the treemap
+    ///
+    /// The hash does not depend on the internal representation of the treemap: empty bitmaps in
+    /// [`Treemap::map`] are ignored, and the representation of each bitmap doesn't matter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::{Bitmap, Treemap};
+    /// use std::hash::BuildHasher;
+    ///
+    /// let hasher = std::collections::hash_map::RandomState::new();
+    /// let treemap1 = Treemap::from_iter(0..10_000);
+    /// let mut treemap2 = treemap1.clone();
+    /// treemap2.run_optimize();
+    /// treemap2.map.insert(5, Bitmap::new());
+    ///
+    /// assert_eq!(hasher.hash_one(&treemap1), hasher.hash_one(&treemap2));
+    /// ```
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.cardinality().hash(state);
+        for (key, bitmap) in self.non_empty_bitmaps() {
+            key.hash(state);
+            bitmap.hash(state);
+        }
+    }
+}
+
+impl PartialOrd for Treemap {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Treemap {
+    /// Compares treemaps lexicographically by their values in sorted order
+    ///
+    /// This matches the ordering of a `BTreeSet` of the same values. As for [`PartialEq`], empty
+    /// bitmaps in [`Treemap::map`] are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// assert!(Treemap::of(&[1, 2]) < Treemap::of(&[1, 3]));
+    /// assert!(Treemap::of(&[1, 2]) < Treemap::of(&[1, 2, u64::MAX]));
+    /// assert!(Treemap::of(&[u64::MAX]) > Treemap::of(&[1, 3]));
+    /// assert!(Treemap::new() < Treemap::of(&[0]));
+    /// ```
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.non_empty_values().cmp(other.non_empty_values())
+    }
+}
+
+impl Treemap {
+    fn non_empty_bitmaps(&self) -> impl Iterator<Item = (&u32, &crate::Bitmap)> {
+        self.map.iter().filter(|(_, bitmap)| !bitmap.is_empty())
+    }
+
+    /// The values of the treemap, skipping empty bitmaps rather than panicking like `iter`
+    fn non_empty_values(&self) -> impl Iterator<Item = u64> + '_ {
+        self.non_empty_bitmaps()
+            .flat_map(|(&key, bitmap)| bitmap.iter().map(move |low| util::join(key, low)))
+    }
+}
+
 impl BitAnd for Treemap {
     type Output = Treemap;
 
@@ -82,7 +179,7 @@ impl<'a> BitAnd<&'a Treemap> for Treemap {
     }
 }
 
-impl<'a, 'b> BitAnd<&'a Treemap> for &'b Treemap {
+impl<'a> BitAnd<&'a Treemap> for &Treemap {
     type Output = Treemap;
 
     /// Syntactic sugar for `.and`
@@ -205,7 +302,7 @@ impl<'a> BitOr<&'a Treemap> for Treemap {
     }
 }
 
-impl<'a, 'b> BitOr<&'a Treemap> for &'b Treemap {
+impl<'a> BitOr<&'a Treemap> for &Treemap {
     type Output = Treemap;
 
     /// Syntatic sugar for `.or`
@@ -323,7 +420,7 @@ impl<'a> BitXor<&'a Treemap> for Treemap {
     }
 }
 
-impl<'a, 'b> BitXor<&'a Treemap> for &'b Treemap {
+impl<'a> BitXor<&'a Treemap> for &Treemap {
     type Output = Treemap;
 
     /// Syntatic sugar for `.xor`
@@ -453,7 +550,7 @@ impl<'a> Sub<&'a Treemap> for Treemap {
     }
 }
 
-impl<'a, 'b> Sub<&'a Treemap> for &'b Treemap {
+impl<'a> Sub<&'a Treemap> for &Treemap {
     type Output = Treemap;
 
     /// Syntatic sugar for `.andnot`
@@ -518,3 +615,107 @@ impl SubAssign for Treemap {
         self.andnot_inplace(&other);
     }
 }
+
+/// Shifts all values up by `rhs`, dropping any which would be `> u64::MAX`
+///
+/// See [`Treemap::add_offset`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Treemap;
+///
+/// let treemap = Treemap::of(&[0, 1, u64::MAX]);
+/// assert_eq!((&treemap << 2).iter().collect::<Vec<_>>(), [2, 3]);
+/// assert_eq!((treemap << (1 << 40)).iter().collect::<Vec<_>>(), [1 << 40, (1 << 40) + 1]);
+/// ```
+impl Shl<u64> for &Treemap {
+    type Output = Treemap;
+
+    #[inline]
+    fn shl(self, rhs: u64) -> Treemap {
+        self.add_offset(i128::from(rhs))
+    }
+}
+
+impl Shl<u64> for Treemap {
+    type Output = Treemap;
+
+    #[inline]
+    fn shl(mut self, rhs: u64) -> Treemap {
+        self <<= rhs;
+        self
+    }
+}
+
+/// Shifts all values down by `rhs`, dropping any which would be `< 0`
+///
+/// See [`Treemap::add_offset`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Treemap;
+///
+/// let treemap = Treemap::of(&[0, 1, u64::MAX]);
+/// assert_eq!((&treemap >> 1).iter().collect::<Vec<_>>(), [0, u64::MAX - 1]);
+/// assert_eq!((treemap >> u64::MAX).iter().collect::<Vec<_>>(), [0]);
+/// ```
+impl Shr<u64> for &Treemap {
+    type Output = Treemap;
+
+    #[inline]
+    fn shr(self, rhs: u64) -> Treemap {
+        self.add_offset(-i128::from(rhs))
+    }
+}
+
+impl Shr<u64> for Treemap {
+    type Output = Treemap;
+
+    #[inline]
+    fn shr(mut self, rhs: u64) -> Treemap {
+        self >>= rhs;
+        self
+    }
+}
+
+/// Shifts all values up by `rhs` in place, dropping any which would be `> u64::MAX`
+///
+/// See [`Treemap::add_offset_inplace`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Treemap;
+///
+/// let mut treemap = Treemap::of(&[0, 1, u64::MAX]);
+/// treemap <<= 2;
+/// assert_eq!(treemap.iter().collect::<Vec<_>>(), [2, 3]);
+/// ```
+impl ShlAssign<u64> for Treemap {
+    #[inline]
+    fn shl_assign(&mut self, rhs: u64) {
+        self.add_offset_inplace(i128::from(rhs));
+    }
+}
+
+/// Shifts all values down by `rhs` in place, dropping any which would be `< 0`
+///
+/// See [`Treemap::add_offset_inplace`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Treemap;
+///
+/// let mut treemap = Treemap::of(&[0, 1, u64::MAX]);
+/// treemap >>= 1;
+/// assert_eq!(treemap.iter().collect::<Vec<_>>(), [0, u64::MAX - 1]);
+/// ```
+impl ShrAssign<u64> for Treemap {
+    #[inline]
+    fn shr_assign(&mut self, rhs: u64) {
+        self.add_offset_inplace(-i128::from(rhs));
+    }
+}
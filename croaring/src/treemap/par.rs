@@ -0,0 +1,183 @@
+use super::{util, Treemap};
+use crate::par::tree_reduce;
+use crate::Bitmap;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use rayon::iter::plumbing::UnindexedConsumer;
+use rayon::iter::{
+    FromParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+};
+
+impl Treemap {
+    /// Groups the bitmaps of all treemaps by key
+    fn bitmaps_by_key<'a>(treemaps: &[&'a Treemap]) -> BTreeMap<u32, Vec<&'a Bitmap>> {
+        let mut groups = BTreeMap::<u32, Vec<&Bitmap>>::new();
+        for treemap in treemaps {
+            for (&key, bitmap) in &treemap.map {
+                groups.entry(key).or_default().push(bitmap);
+            }
+        }
+        groups
+    }
+
+    /// Computes the union of many treemaps in parallel
+    ///
+    /// The bitmaps for each key are combined with [`Bitmap::par_fast_or`], with separate keys
+    /// also handled in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let treemaps = (0..100).map(|i| Treemap::of(&[i, i << 40])).collect::<Vec<_>>();
+    /// let refs = treemaps.iter().collect::<Vec<_>>();
+    ///
+    /// let union = Treemap::par_fast_or(&refs);
+    /// assert_eq!(union.cardinality(), 199);
+    /// assert!(Treemap::par_fast_or(&[]).is_empty());
+    /// ```
+    #[must_use]
+    #[doc(alias = "par_or")]
+    pub fn par_fast_or(treemaps: &[&Treemap]) -> Self {
+        let map = Self::bitmaps_by_key(treemaps)
+            .into_par_iter()
+            .map(|(key, bitmaps)| (key, Bitmap::par_fast_or(&bitmaps)))
+            .filter(|(_, bitmap)| !bitmap.is_empty())
+            .collect();
+        Treemap { map }
+    }
+
+    /// Computes the intersection of many treemaps in parallel
+    ///
+    /// Groups of treemaps are combined with [`Treemap::fast_and`] on separate threads, and the
+    /// results are then merged pairwise, also in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let treemaps = (0..100).map(|i| Treemap::of(&[i, 500, 1 << 40])).collect::<Vec<_>>();
+    /// let refs = treemaps.iter().collect::<Vec<_>>();
+    ///
+    /// let intersection = Treemap::par_fast_and(&refs);
+    /// assert_eq!(intersection.iter().collect::<Vec<_>>(), [500, 1 << 40]);
+    /// assert!(Treemap::par_fast_and(&[]).is_empty());
+    /// ```
+    #[must_use]
+    #[doc(alias = "par_and")]
+    pub fn par_fast_and(treemaps: &[&Treemap]) -> Self {
+        if treemaps.is_empty() {
+            return Self::new();
+        }
+        tree_reduce(treemaps, &Self::fast_and, &|mut lhs, rhs| {
+            lhs.and_inplace(&rhs);
+            lhs
+        })
+    }
+
+    /// Computes the symmetric difference of many treemaps in parallel
+    ///
+    /// The bitmaps for each key are combined with [`Bitmap::par_fast_xor`], with separate keys
+    /// also handled in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use croaring::Treemap;
+    ///
+    /// let treemaps = (0..100).map(|i| Treemap::of(&[i, 1 << 40])).collect::<Vec<_>>();
+    /// let refs = treemaps.iter().collect::<Vec<_>>();
+    ///
+    /// let difference = Treemap::par_fast_xor(&refs);
+    /// assert_eq!(difference.iter().collect::<Vec<_>>(), (0..100).collect::<Vec<_>>());
+    /// assert!(Treemap::par_fast_xor(&[]).is_empty());
+    /// ```
+    #[must_use]
+    #[doc(alias = "par_xor")]
+    pub fn par_fast_xor(treemaps: &[&Treemap]) -> Self {
+        let map = Self::bitmaps_by_key(treemaps)
+            .into_par_iter()
+            .map(|(key, bitmaps)| (key, Bitmap::par_fast_xor(&bitmaps)))
+            .filter(|(_, bitmap)| !bitmap.is_empty())
+            .collect();
+        Treemap { map }
+    }
+}
+
+/// A parallel iterator over the values of a [`Treemap`]
+///
+/// Each bitmap of the treemap is split between threads by container, using
+/// [`BitmapParIter`](crate::bitmap::BitmapParIter).
+///
+/// Returned by `par_iter()` from [`rayon::iter::IntoParallelRefIterator`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Treemap;
+/// use rayon::prelude::*;
+///
+/// let mut treemap = Treemap::new();
+/// treemap.add_range(0..0x1_0000);
+/// treemap.add_range(1 << 40..(1 << 40) + 0x1_0000);
+/// assert_eq!(treemap.par_iter().filter(|&v| v >= 1 << 40).count(), 0x1_0000);
+/// ```
+#[derive(Clone)]
+pub struct TreemapParIter<'a> {
+    treemap: &'a Treemap,
+}
+
+impl ParallelIterator for TreemapParIter<'_> {
+    type Item = u64;
+
+    fn drive_unindexed<C: UnindexedConsumer<u64>>(self, consumer: C) -> C::Result {
+        self.treemap
+            .map
+            .par_iter()
+            .flat_map(|(&key, bitmap)| bitmap.par_iter().map(move |low| util::join(key, low)))
+            .drive_unindexed(consumer)
+    }
+}
+
+impl<'a> IntoParallelIterator for &'a Treemap {
+    type Iter = TreemapParIter<'a>;
+    type Item = u64;
+
+    #[inline]
+    fn into_par_iter(self) -> Self::Iter {
+        TreemapParIter { treemap: self }
+    }
+}
+
+/// Collects values from a parallel iterator
+///
+/// Each thread builds a separate treemap, and these are then merged with [`Treemap::append`].
+///
+/// # Examples
+///
+/// ```
+/// use croaring::Treemap;
+/// use rayon::prelude::*;
+///
+/// let treemap: Treemap = (0..100_000u64).into_par_iter().map(|i| i << 20).collect();
+/// assert_eq!(treemap.cardinality(), 100_000);
+///
+/// let halved: Treemap = treemap.par_iter().map(|i| i >> 1).collect();
+/// assert_eq!(halved.maximum(), Some(99_999 << 19));
+/// ```
+impl FromParallelIterator<u64> for Treemap {
+    fn from_par_iter<I: IntoParallelIterator<Item = u64>>(par_iter: I) -> Self {
+        par_iter
+            .into_par_iter()
+            .fold(Treemap::new, |mut treemap, value| {
+                treemap.add(value);
+                treemap
+            })
+            .reduce(Treemap::new, |mut lhs, mut rhs| {
+                lhs.append(&mut rhs);
+                lhs
+            })
+    }
+}
@@ -1,6 +1,12 @@
-use crate::serialization::{Frozen, Native, Portable};
+#[cfg(feature = "std")]
+use crate::serialization::ReaderSource;
+use crate::serialization::{
+    clickhouse_size, parse_clickhouse, write_clickhouse_header, ByteSource, ClickHouseState,
+    DeserializeError, Frozen, Native, Portable, SliceSource, CLICKHOUSE_MAX_HEADER_LEN,
+    CLICKHOUSE_SMALL_SET_SIZE,
+};
 use crate::{bitmap, Treemap};
-use crate::{Bitmap, JvmLegacy};
+use crate::{Bitmap, ClickHouse, JvmLegacy};
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 use core::prelude::v1::*;
@@ -54,6 +60,31 @@ pub trait Deserializer {
     /// If the buffer does not start with a serialized treemap (or contains an invalidly
     /// truncated treemap), then this method will return `None`.
     fn try_deserialize(buffer: &[u8]) -> Option<(Treemap, usize)>;
+
+    /// Try to deserialize a treemap from the beginning of the provided buffer, describing any
+    /// failure
+    ///
+    /// On success, returns the same as [`Self::try_deserialize`].
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Treemap, usize), DeserializeError> {
+        Self::try_deserialize(buffer).ok_or(DeserializeError::InvalidHeader {
+            reason: "not a serialized treemap",
+        })
+    }
+
+    /// Deserialize a treemap from a reader
+    ///
+    /// The formats in this crate read only the bytes of the serialized treemap from `src`, as
+    /// determined by its header and the headers of its bitmaps. The default implementation
+    /// reads all of `src` into memory, and deserializes the treemap from the start of it.
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R>(mut src: R) -> std::io::Result<Treemap>
+    where
+        R: std::io::Read,
+    {
+        let mut buf = Vec::new();
+        src.read_to_end(&mut buf)?;
+        Ok(Self::try_deserialize_prefix(&buf)?.0)
+    }
 }
 
 fn serialize_impl<'a, S>(treemap: &Treemap, dst: &'a mut Vec<u8>) -> &'a [u8]
@@ -114,23 +145,42 @@ where
     overhead + total_sizes
 }
 
-fn deserialize_impl<S>(buffer: &[u8]) -> Option<(Treemap, usize)>
+fn deserialize_impl<S>(buffer: &[u8]) -> Result<(Treemap, usize), DeserializeError>
 where
-    S: bitmap::Serializer + bitmap::Deserializer,
+    S: bitmap::Deserializer,
 {
-    let start_len = buffer.len();
-    let (map_len_bytes, mut buffer) = buffer.split_first_chunk()?;
-    let map_len = u64::from_ne_bytes(*map_len_bytes);
+    let mut src = SliceSource::new(buffer);
+    let map_len = u64::from_ne_bytes(src.take_array()?);
     let mut map = BTreeMap::new();
     for _ in 0..map_len {
-        let (key_bytes, rest) = buffer.split_first_chunk()?;
-        buffer = rest;
-        let key = u32::from_ne_bytes(*key_bytes);
-        let bitmap = Bitmap::try_deserialize::<S>(buffer)?;
-        buffer = &buffer[bitmap.get_serialized_size_in_bytes::<S>()..];
+        let key = u32::from_ne_bytes(src.take_array()?);
+        let start = src.position();
+        let (bitmap, len) =
+            S::try_deserialize_prefix(src.remaining()).map_err(|e| e.offset_by(start))?;
+        src.take(len)?;
         map.insert(key, bitmap);
     }
-    Some((Treemap { map }, start_len - buffer.len()))
+    Ok((Treemap { map }, src.position()))
+}
+
+#[cfg(feature = "std")]
+fn deserialize_reader_impl<S, R>(mut src: R) -> std::io::Result<Treemap>
+where
+    S: bitmap::Deserializer,
+    R: std::io::Read,
+{
+    let mut map_len_bytes = [0; size_of::<u64>()];
+    src.read_exact(&mut map_len_bytes)?;
+    let map_len = u64::from_ne_bytes(map_len_bytes);
+    let mut map = BTreeMap::new();
+    for _ in 0..map_len {
+        let mut key_bytes = [0; size_of::<u32>()];
+        src.read_exact(&mut key_bytes)?;
+        let key = u32::from_ne_bytes(key_bytes);
+        let bitmap = Bitmap::deserialize_from_reader::<S, _>(&mut src)?;
+        map.insert(key, bitmap);
+    }
+    Ok(Treemap { map })
 }
 
 impl Serializer for Portable {
@@ -159,8 +209,20 @@ impl Serializer for Portable {
 
 impl Deserializer for Portable {
     fn try_deserialize(buffer: &[u8]) -> Option<(Treemap, usize)> {
+        deserialize_impl::<Self>(buffer).ok()
+    }
+
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Treemap, usize), DeserializeError> {
         deserialize_impl::<Self>(buffer)
     }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R>(src: R) -> std::io::Result<Treemap>
+    where
+        R: std::io::Read,
+    {
+        deserialize_reader_impl::<Self, R>(src)
+    }
 }
 
 impl Serializer for Native {
@@ -189,8 +251,20 @@ impl Serializer for Native {
 
 impl Deserializer for Native {
     fn try_deserialize(buffer: &[u8]) -> Option<(Treemap, usize)> {
+        deserialize_impl::<Self>(buffer).ok()
+    }
+
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Treemap, usize), DeserializeError> {
         deserialize_impl::<Self>(buffer)
     }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R>(src: R) -> std::io::Result<Treemap>
+    where
+        R: std::io::Read,
+    {
+        deserialize_reader_impl::<Self, R>(src)
+    }
 }
 
 const FROZEN_BITMAP_METADATA_SIZE: usize = size_of::<usize>() + size_of::<u32>();
@@ -353,23 +427,144 @@ impl Serializer for JvmLegacy {
 
 impl Deserializer for JvmLegacy {
     fn try_deserialize(buffer: &[u8]) -> Option<(Treemap, usize)> {
-        let start_len = buffer.len();
+        Self::try_deserialize_prefix(buffer).ok()
+    }
+
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Treemap, usize), DeserializeError> {
+        let mut src = SliceSource::new(buffer);
         // Ignored, we assume that the values are not signed
-        let (_is_signed, buffer) = buffer.split_first()?;
+        let [_is_signed] = src.take_array()?;
 
-        let (bitmap_count_bytes, mut buffer) = buffer.split_first_chunk()?;
-        let bitmap_count = u32::from_be_bytes(*bitmap_count_bytes);
+        let bitmap_count = u32::from_be_bytes(src.take_array()?);
         let mut map = BTreeMap::new();
         for _ in 0..bitmap_count {
-            let (key_bytes, rest) = buffer.split_first_chunk()?;
-            buffer = rest;
-            let key = u32::from_be_bytes(*key_bytes);
-            let bitmap = Bitmap::try_deserialize::<Portable>(buffer)?;
-            buffer = &buffer[bitmap.get_serialized_size_in_bytes::<Portable>()..];
+            let key = u32::from_be_bytes(src.take_array()?);
+            let start = src.position();
+            let (bitmap, len) =
+                <Portable as bitmap::Deserializer>::try_deserialize_prefix(src.remaining())
+                    .map_err(|e| e.offset_by(start))?;
+            src.take(len)?;
             map.insert(key, bitmap);
         }
 
-        Some((Treemap { map }, start_len - buffer.len()))
+        Ok((Treemap { map }, src.position()))
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R>(mut src: R) -> std::io::Result<Treemap>
+    where
+        R: std::io::Read,
+    {
+        // Ignored, we assume that the values are not signed
+        let mut is_signed = [0; 1];
+        src.read_exact(&mut is_signed)?;
+
+        let mut bitmap_count_bytes = [0; size_of::<u32>()];
+        src.read_exact(&mut bitmap_count_bytes)?;
+        let bitmap_count = u32::from_be_bytes(bitmap_count_bytes);
+        let mut map = BTreeMap::new();
+        for _ in 0..bitmap_count {
+            let mut key_bytes = [0; size_of::<u32>()];
+            src.read_exact(&mut key_bytes)?;
+            let key = u32::from_be_bytes(key_bytes);
+            let bitmap = Bitmap::deserialize_from_reader::<Portable, _>(&mut src)?;
+            map.insert(key, bitmap);
+        }
+
+        Ok(Treemap { map })
+    }
+}
+
+impl Serializer for ClickHouse {
+    /// Serializes a Treemap to a writer as a ClickHouse `groupBitmap` state.
+    /// See [`Treemap::serialize_into_writer`] for examples.
+    #[cfg(feature = "std")]
+    fn serialize_into_writer<W>(treemap: &Treemap, dst: W) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+    {
+        use std::io::Write;
+
+        let mut dst = OffsetTrackingWriter::new(dst);
+        let cardinality = treemap.cardinality();
+        let mut header = [0; CLICKHOUSE_MAX_HEADER_LEN];
+        let header_len = write_clickhouse_header(
+            cardinality,
+            size_in_bytes_impl::<Portable>(treemap),
+            &mut header,
+        );
+        dst.write_all(&header[..header_len])?;
+        if cardinality <= CLICKHOUSE_SMALL_SET_SIZE {
+            for value in treemap.iter() {
+                dst.write_all(&value.to_le_bytes())?;
+            }
+        } else {
+            serialize_writer_impl::<Portable, _>(treemap, &mut dst)?;
+        }
+        Ok(dst.bytes_written)
+    }
+
+    /// Serializes a Treemap to a slice of bytes as a ClickHouse `groupBitmap` state.
+    /// See [`Treemap::serialize_into`] for examples.
+    fn serialize_into<'a>(treemap: &Treemap, dst: &'a mut Vec<u8>) -> &'a [u8] {
+        let start_idx = dst.len();
+        let cardinality = treemap.cardinality();
+        let mut header = [0; CLICKHOUSE_MAX_HEADER_LEN];
+        let header_len = write_clickhouse_header(
+            cardinality,
+            size_in_bytes_impl::<Portable>(treemap),
+            &mut header,
+        );
+        dst.extend_from_slice(&header[..header_len]);
+        if cardinality <= CLICKHOUSE_SMALL_SET_SIZE {
+            treemap
+                .iter()
+                .for_each(|value| dst.extend_from_slice(&value.to_le_bytes()));
+        } else {
+            serialize_impl::<Portable>(treemap, dst);
+        }
+        &dst[start_idx..]
+    }
+
+    /// Computes the serialized size in bytes of the Treemap as a ClickHouse `groupBitmap` state.
+    /// See [`Treemap::get_serialized_size_in_bytes`] for examples.
+    fn get_serialized_size_in_bytes(treemap: &Treemap) -> usize {
+        clickhouse_size(
+            treemap.cardinality(),
+            8,
+            size_in_bytes_impl::<Portable>(treemap),
+        )
+    }
+}
+
+impl Deserializer for ClickHouse {
+    fn try_deserialize(buffer: &[u8]) -> Option<(Treemap, usize)> {
+        Self::try_deserialize_prefix(buffer).ok()
+    }
+
+    fn try_deserialize_prefix(buffer: &[u8]) -> Result<(Treemap, usize), DeserializeError> {
+        let mut src = SliceSource::new(buffer);
+        let treemap = match parse_clickhouse(&mut src, 8)? {
+            ClickHouseState::Small { start, count } => buffer[start..start + count * 8]
+                .chunks_exact(8)
+                .map(|value| u64::from_le_bytes(value.try_into().unwrap()))
+                .collect(),
+            ClickHouseState::Large { start, len } => {
+                Treemap::try_deserialize_exact::<Portable>(&buffer[start..start + len])
+                    .map_err(|e| e.offset_by(start))?
+            }
+        };
+        Ok((treemap, src.position()))
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_from_reader<R>(mut src: R) -> std::io::Result<Treemap>
+    where
+        R: std::io::Read,
+    {
+        let mut buf = Vec::new();
+        parse_clickhouse(&mut ReaderSource::new(&mut src, &mut buf), 8)?;
+        Ok(Self::try_deserialize_prefix(&buf)?.0)
     }
 }
 
@@ -462,4 +657,9 @@ mod tests {
     fn smoke_jvm_legacy() {
         smoke_test_ser::<JvmLegacy>(67);
     }
+
+    #[test]
+    fn smoke_clickhouse() {
+        smoke_test_ser::<ClickHouse>(58);
+    }
 }
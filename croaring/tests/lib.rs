@@ -1,10 +1,10 @@
-use std::{fs, iter, u32};
+use std::fs;
 
 use croaring::{Bitmap, BitmapView, Frozen, Native, Portable};
 use proptest::prelude::*;
 
 #[cfg(feature = "alloc")]
-use croaring::{JvmLegacy, Treemap};
+use croaring::{DeserializeError, JvmLegacy, Treemap};
 
 fn init() {
     #[cfg(feature = "alloc")]
@@ -146,6 +146,59 @@ fn cursor_return_from_the_edge() {
     assert_eq!(cursor.next(), Some(1));
 }
 
+#[test]
+fn unbounded_ranges_include_u32_max() {
+    init();
+    let bitmap = Bitmap::of(&[1, u32::MAX - 1, u32::MAX]);
+    assert_eq!(bitmap.range_cardinality(..), 3);
+    assert_eq!(bitmap.range_cardinality(u32::MAX..), 1);
+    assert!(bitmap.contains_range(u32::MAX - 1..));
+    assert!(bitmap.intersect_with_range(u32::MAX..));
+
+    let bitmap = Bitmap::of(&[1, u32::MAX - 1]);
+    assert!(!bitmap.contains_range(u32::MAX - 1..));
+    assert!(!bitmap.intersect_with_range(u32::MAX..));
+    assert_eq!(
+        bitmap.flip(u32::MAX - 1..).iter().collect::<Vec<_>>(),
+        [1, u32::MAX]
+    );
+
+    let mut flipped = bitmap.clone();
+    flipped.flip_inplace(u32::MAX - 1..);
+    assert_eq!(flipped.iter().collect::<Vec<_>>(), [1, u32::MAX]);
+}
+
+#[test]
+fn iterator_size_hint_follows_resets() {
+    init();
+    let bitmap = Bitmap::of(&[1, 2, 3, 10, u32::MAX]);
+    let mut iter = bitmap.iter();
+    iter.reset_at_or_after(3);
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+    assert_eq!(iter.next_back(), Some(u32::MAX));
+    iter.reset_at_or_after(0);
+    assert_eq!(iter.size_hint(), (4, Some(4)));
+    assert_eq!(iter.collect::<Vec<_>>(), [1, 2, 3, 10]);
+
+    let mut cursor = bitmap.cursor();
+    cursor.move_next();
+    let iter = croaring::bitmap::BitmapIterator::from(cursor);
+    assert_eq!(iter.size_hint(), (4, Some(4)));
+
+    let bitmap = croaring::Bitmap64::of(&[1, 2, 3, 10, u64::MAX]);
+    let mut iter = bitmap.iter();
+    iter.reset_at_or_after(3);
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+    assert_eq!(iter.next_back(), Some(u64::MAX));
+    iter.reset_at_or_after(0);
+    assert_eq!(iter.size_hint(), (4, Some(4)));
+
+    let mut cursor = bitmap.cursor();
+    cursor.move_next();
+    let iter = croaring::bitmap64::Bitmap64Iterator::from(cursor);
+    assert_eq!(iter.size_hint(), (4, Some(4)));
+}
+
 #[test]
 fn test_portable_view() {
     init();
@@ -182,7 +235,7 @@ fn test_frozen_view() {
     // Ensure inserting zeros won't move the data
     buffer.reserve(32);
     let offset = 32 - (buffer.as_ptr() as usize) % 32;
-    buffer.splice(..0, iter::repeat(0).take(offset));
+    buffer.splice(..0, vec![0; offset]);
 
     let bitmap = unsafe { BitmapView::deserialize::<Frozen>(&buffer[offset..]) };
     let expected = expected_serialized_bitmap();
@@ -190,6 +243,358 @@ fn test_frozen_view() {
     assert!(bitmap.iter().eq(expected.iter()))
 }
 
+#[test]
+fn test_try_deserialize_view() {
+    init();
+    let expected = expected_serialized_bitmap();
+    let portable = fs::read("tests/data/portable_bitmap.bin").unwrap();
+    // Portable data is read in place: after the run flags (5 bytes into the data), the headers and
+    // containers of this bitmap are aligned when it starts 1 byte past an 8 byte boundary
+    let mut placed = vec![0; portable.len() + 16];
+    let start = placed.as_ptr().align_offset(8) + 1;
+    placed[start..start + portable.len()].copy_from_slice(&portable);
+    let view =
+        BitmapView::try_deserialize::<Portable>(&placed[start..start + portable.len()]).unwrap();
+    assert_eq!(view, expected);
+    assert!(view.iter().eq(expected.iter()));
+    drop(view);
+    placed.copy_within(start..start + portable.len(), start + 1);
+    let err =
+        BitmapView::try_deserialize::<Portable>(&placed[start + 1..start + 1 + portable.len()])
+            .unwrap_err();
+    assert!(matches!(err, croaring::DeserializeError::Misaligned { .. }));
+
+    let mut buffer = fs::read("tests/data/frozen_bitmap.bin").unwrap();
+    let len = buffer.len();
+    buffer.reserve(64);
+    let offset = 32 - (buffer.as_ptr() as usize) % 32;
+    buffer.splice(..0, vec![0; offset]);
+
+    let view = BitmapView::try_deserialize::<Frozen>(&buffer[offset..offset + len]).unwrap();
+    assert_eq!(view, expected);
+    assert!(view.iter().eq(expected.iter()));
+
+    let err = BitmapView::try_deserialize::<Frozen>(&buffer[offset + 1..]).unwrap_err();
+    assert_eq!(
+        err,
+        croaring::DeserializeError::Misaligned {
+            required: Frozen::REQUIRED_ALIGNMENT
+        }
+    );
+    let err = BitmapView::try_deserialize::<Frozen>(&buffer[offset..offset + len - 1]).unwrap_err();
+    assert!(matches!(
+        err,
+        croaring::DeserializeError::UnknownCookie { .. }
+    ));
+    let err = BitmapView::try_deserialize::<Frozen>(&buffer[offset..offset + 2]).unwrap_err();
+    assert!(matches!(err, croaring::DeserializeError::Truncated { .. }));
+
+    placed.copy_within(start + 1..start + 1 + portable.len(), start);
+    let err = BitmapView::try_deserialize::<Portable>(&placed[start..start + portable.len() - 1])
+        .unwrap_err();
+    assert!(matches!(err, croaring::DeserializeError::Truncated { .. }));
+    placed[start + portable.len()] = 0;
+    let err = BitmapView::try_deserialize::<Portable>(&placed[start..start + portable.len() + 1])
+        .unwrap_err();
+    assert_eq!(err, croaring::DeserializeError::TrailingBytes { extra: 1 });
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_owned_view() {
+    use croaring::OwnedBitmapView;
+    use std::sync::Arc;
+
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    init();
+    let expected = expected_serialized_bitmap();
+    // Only array containers, and no run flags, so the portable data is aligned in any allocation
+    let arrays = Bitmap::of(&[1, 2, 3, 100_000, 200_000]);
+    let portable = arrays.serialize::<Portable>();
+
+    // Views remain valid as they (and their buffers) are moved around
+    let mut views = Vec::new();
+    views.push(OwnedBitmapView::try_deserialize::<Portable>(portable.clone()).unwrap());
+    for _ in 0..10 {
+        let view = views.last().unwrap().try_clone().unwrap();
+        assert_eq!(view.as_view(), &arrays);
+        views.push(view);
+    }
+    for view in &views {
+        assert_send_sync(view);
+        assert_eq!(view, &arrays);
+    }
+    assert_eq!(views.pop().unwrap().into_buffer(), portable);
+
+    // Frozen data must be aligned, which clones of a shared buffer always are
+    #[derive(Clone)]
+    struct SharedFrozen(Arc<(Vec<u8>, std::ops::Range<usize>)>);
+    impl AsRef<[u8]> for SharedFrozen {
+        fn as_ref(&self) -> &[u8] {
+            &self.0 .0[self.0 .1.clone()]
+        }
+    }
+    // SAFETY: clones share the same `Vec` and range, which are never modified
+    unsafe impl croaring::SharedBuffer for SharedFrozen {}
+
+    let mut buf = Vec::new();
+    // The serialized data is at the end of the buffer, after any padding
+    let len = expected.serialize_into_vec::<Frozen>(&mut buf).len();
+    let range = buf.len() - len..buf.len();
+    let shared = SharedFrozen(Arc::new((buf, range)));
+    let view = OwnedBitmapView::try_deserialize::<Frozen>(shared.clone()).unwrap();
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let view = view.clone();
+            std::thread::spawn(move || view.cardinality())
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), expected.cardinality());
+    }
+    assert!(Arc::ptr_eq(&view.buffer().0, &shared.0));
+
+    // Clones which do not hold the same data are checked, rather than trusted
+    struct Truncating(Vec<u8>);
+    impl Clone for Truncating {
+        fn clone(&self) -> Self {
+            Truncating(self.0[..self.0.len() - 1].to_vec())
+        }
+    }
+    impl AsRef<[u8]> for Truncating {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+    let view = OwnedBitmapView::try_deserialize::<Portable>(Truncating(portable.clone())).unwrap();
+    assert!(view.try_clone().is_err());
+
+    // Views of an `Arc<[u8]>` share their data when cloned
+    let data: Arc<[u8]> = portable.into();
+    let view = OwnedBitmapView::try_deserialize::<Portable>(Arc::clone(&data)).unwrap();
+    let clone = view.clone();
+    assert_eq!(clone, arrays);
+    assert!(Arc::ptr_eq(clone.buffer(), &data));
+
+    // As do views of borrowed data
+    let view = OwnedBitmapView::try_deserialize::<Portable>(&data[..]).unwrap();
+    let clone = view.clone();
+    assert_eq!(clone, arrays);
+    assert!(std::ptr::eq(*clone.buffer(), *view.buffer()));
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn test_mmap_view() {
+    use croaring::MmapBitmapView;
+    use std::io::ErrorKind;
+
+    init();
+    let expected = expected_serialized_bitmap();
+
+    // The fixtures are never modified while mapped
+    // Mapped at the start of a page, the headers of this portable bitmap are misaligned
+    let err = unsafe { MmapBitmapView::open::<Portable>("tests/data/portable_bitmap.bin") };
+    let err = err.unwrap_err().into_inner().unwrap();
+    assert!(matches!(
+        err.downcast_ref::<DeserializeError>(),
+        Some(DeserializeError::Misaligned { .. })
+    ));
+    let view = unsafe { MmapBitmapView::open::<Frozen>("tests/data/frozen_bitmap.bin") };
+    assert_eq!(view.unwrap(), expected);
+    let err = unsafe { MmapBitmapView::open::<Frozen>("tests/data/portable_bitmap.bin") };
+    assert_eq!(err.unwrap_err().kind(), ErrorKind::InvalidData);
+    let err = unsafe { MmapBitmapView::open::<Portable>("tests/data/missing.bin") };
+    assert_eq!(err.unwrap_err().kind(), ErrorKind::NotFound);
+
+    // Both formats stored one after the other in a single file
+    let portable = fs::read("tests/data/portable_bitmap.bin").unwrap();
+    let frozen = fs::read("tests/data/frozen_bitmap.bin").unwrap();
+    // As in test_try_deserialize_view, the portable bitmap is aligned 1 byte past a boundary
+    let frozen_offset = (portable.len() + 1).next_multiple_of(Frozen::REQUIRED_ALIGNMENT);
+    let mut data = vec![0];
+    data.extend_from_slice(&portable);
+    data.resize(frozen_offset, 0);
+    data.extend_from_slice(&frozen);
+    let path = std::env::temp_dir().join(format!("croaring-test-mmap-{}", std::process::id()));
+    fs::write(&path, &data).unwrap();
+    let file = fs::File::open(&path).unwrap();
+
+    let views = unsafe {
+        [
+            MmapBitmapView::map_range::<Portable>(&file, 1, portable.len()).unwrap(),
+            MmapBitmapView::map_range::<Frozen>(&file, frozen_offset as u64, frozen.len()).unwrap(),
+        ]
+    };
+    for view in &views {
+        assert_eq!(view, &expected);
+    }
+
+    let misaligned = unsafe { MmapBitmapView::map_range::<Frozen>(&file, 1, frozen.len()) };
+    let err = misaligned.unwrap_err().into_inner().unwrap();
+    assert_eq!(
+        err.downcast_ref::<DeserializeError>(),
+        Some(&DeserializeError::Misaligned {
+            required: Frozen::REQUIRED_ALIGNMENT
+        })
+    );
+    let truncated = unsafe { MmapBitmapView::map_range::<Portable>(&file, 1, portable.len() - 1) };
+    assert_eq!(truncated.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    let past_end = unsafe { MmapBitmapView::map_range::<Frozen>(&file, 0, data.len() + 1) };
+    assert_eq!(past_end.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+
+    drop(views);
+    drop(file);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_deserialize_from_reader() {
+    init();
+    let portable = fs::read("tests/data/portable_bitmap.bin").unwrap();
+    let native = fs::read("tests/data/native_bitmap.bin").unwrap();
+    let expected = expected_serialized_bitmap();
+
+    let mut written = Vec::new();
+    expected
+        .serialize_into_writer::<Portable, _>(&mut written)
+        .unwrap();
+    assert_eq!(written, portable);
+    written.clear();
+    expected
+        .serialize_into_writer::<Native, _>(&mut written)
+        .unwrap();
+    assert_eq!(written, native);
+
+    // Each read must stop at the end of its bitmap
+    let data = [&portable[..], &native, &portable].concat();
+    let mut reader = std::io::Cursor::new(&data[..]);
+    let bitmap = Bitmap::deserialize_from_reader::<Portable, _>(&mut reader).unwrap();
+    assert_eq!(bitmap, expected);
+    assert_eq!(reader.position() as usize, portable.len());
+    let bitmap = Bitmap::deserialize_from_reader::<Native, _>(&mut reader).unwrap();
+    assert_eq!(bitmap, expected);
+    let bitmap = Bitmap::deserialize_from_reader::<Portable, _>(&mut reader).unwrap();
+    assert_eq!(bitmap, expected);
+    assert_eq!(reader.position() as usize, data.len());
+
+    for len in [0, 4, portable.len() / 2, portable.len() - 1] {
+        let err = Bitmap::deserialize_from_reader::<Portable, _>(&portable[..len]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+    let err = Bitmap::deserialize_from_reader::<Native, _>(&portable[..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_deserialize_errors() {
+    init();
+    let portable = fs::read("tests/data/portable_bitmap.bin").unwrap();
+    let native = fs::read("tests/data/native_bitmap.bin").unwrap();
+    let expected = expected_serialized_bitmap();
+
+    let (bitmap, len) = Bitmap::try_deserialize_prefix::<Portable>(&portable).unwrap();
+    assert_eq!(bitmap, expected);
+    assert_eq!(len, portable.len());
+    let bitmap = Bitmap::try_deserialize_exact::<Native>(&native).unwrap();
+    assert_eq!(bitmap, expected);
+
+    for len in [0, 4, portable.len() / 2, portable.len() - 1] {
+        match Bitmap::try_deserialize_prefix::<Portable>(&portable[..len]) {
+            Err(DeserializeError::Truncated { needed, available }) => {
+                assert!(needed > len && needed <= portable.len());
+                assert_eq!(available, len);
+            }
+            other => panic!("expected truncation, got {other:?}"),
+        }
+    }
+
+    let err = Bitmap::try_deserialize_prefix::<Portable>(&[0xff; 16]).unwrap_err();
+    assert_eq!(err, DeserializeError::UnknownCookie { cookie: u32::MAX });
+    assert_eq!(err.to_string(), "unknown cookie 0xffffffff");
+    let err = Bitmap::try_deserialize_prefix::<Native>(&portable).unwrap_err();
+    assert!(matches!(err, DeserializeError::UnknownCookie { .. }));
+
+    let trailing = [&portable[..], &[0; 3]].concat();
+    let err = Bitmap::try_deserialize_exact::<Portable>(&trailing).unwrap_err();
+    assert_eq!(err, DeserializeError::TrailingBytes { extra: 3 });
+
+    // A portable bitmap without run containers, claiming 70000 containers
+    let mut too_many = 12346u32.to_ne_bytes().to_vec();
+    too_many.extend_from_slice(&70_000u32.to_ne_bytes());
+    let err = Bitmap::try_deserialize_prefix::<Portable>(&too_many).unwrap_err();
+    assert!(matches!(err, DeserializeError::InvalidHeader { .. }));
+
+    // Reverse the values of a single array container, which must be sorted
+    let mut unsorted = Bitmap::of(&[1, 2, 3]).serialize::<Portable>();
+    let len = unsorted.len();
+    unsorted[len - 6..].copy_from_slice(&[3, 0, 2, 0, 1, 0]);
+    let err = Bitmap::try_deserialize_prefix::<Portable>(&unsorted).unwrap_err();
+    assert!(matches!(err, DeserializeError::InvalidContainer { .. }));
+    assert!(err.to_string().starts_with("invalid container: "));
+
+    let io_err = std::io::Error::from(DeserializeError::Truncated {
+        needed: 8,
+        available: 4,
+    });
+    assert_eq!(io_err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_treemap_deserialize_errors() {
+    init();
+    let treemap = Treemap::of(&[1, 2, 3, 1 << 40]);
+    check::<Portable>(&treemap);
+    check::<Native>(&treemap);
+    check::<JvmLegacy>(&treemap);
+
+    fn check<S: croaring::treemap::Serializer + croaring::treemap::Deserializer>(
+        treemap: &Treemap,
+    ) {
+        let buffer = treemap.serialize::<S>();
+        let (deserialized, len) = Treemap::try_deserialize_prefix::<S>(&buffer).unwrap();
+        assert_eq!(&deserialized, treemap);
+        assert_eq!(len, buffer.len());
+
+        // Truncation inside a nested bitmap is reported relative to the whole buffer
+        let err = Treemap::try_deserialize_exact::<S>(&buffer[..len - 1]).unwrap_err();
+        assert_eq!(
+            err,
+            DeserializeError::Truncated {
+                needed: len,
+                available: len - 1
+            }
+        );
+
+        let trailing = [&buffer[..], b"x"].concat();
+        let err = Treemap::try_deserialize_exact::<S>(&trailing).unwrap_err();
+        assert_eq!(err, DeserializeError::TrailingBytes { extra: 1 });
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_treemap_deserializer_default_reader() {
+    init();
+    // A format outside this crate, which only implements the required methods
+    struct Wrapped;
+
+    impl croaring::treemap::Deserializer for Wrapped {
+        fn try_deserialize(buffer: &[u8]) -> Option<(Treemap, usize)> {
+            <Portable as croaring::treemap::Deserializer>::try_deserialize(buffer)
+        }
+    }
+
+    let treemap = Treemap::of(&[1, 2, 3, 1 << 40]);
+    let buffer = treemap.serialize::<Portable>();
+    let read = Treemap::deserialize_from_reader::<Wrapped, _>(&buffer[..]).unwrap();
+    assert_eq!(read, treemap);
+}
+
 #[test]
 #[cfg(feature = "alloc")]
 fn test_treemap_deserialize_cpp() {
@@ -202,13 +607,110 @@ fn test_treemap_deserialize_cpp() {
                 assert!(treemap.contains(i));
             }
 
-            assert!(treemap.contains(std::u32::MAX as u64));
-            assert!(treemap.contains(std::u64::MAX));
+            assert!(treemap.contains(u32::MAX as u64));
+            assert!(treemap.contains(u64::MAX));
         }
         Err(err) => panic!("Cannot read test file {}", err),
     }
 }
 
+#[test]
+#[cfg(feature = "std")]
+fn test_clickhouse_fixtures() {
+    use croaring::{Bitmap64, ClickHouse};
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // groupBitmap states, as ClickHouse stores them for UInt32 and UInt64 columns
+    const CLICKHOUSE_SMALL_U32: &str = "00030100000002000000e8030000";
+    const CLICKHOUSE_LARGE_U32: &str = "01523a300000010000000000200010000000000002000400060008000a000c000e00100012001400160018001a001c001e00200022002400260028002a002c002e00300032003400360038003a003c003e004000";
+    const CLICKHOUSE_SMALL_U64: &str = "000301000000000000000000000001000000ffffffffffffffff";
+    const CLICKHOUSE_LARGE_U64: &str = "015e0100000000000000000000003a300000010000000000200010000000000002000400060008000a000c000e00100012001400160018001a001c001e00200022002400260028002a002c002e00300032003400360038003a003c003e004000";
+
+    init();
+    let even: Vec<u32> = (0..=64).step_by(2).collect();
+    let cases_32 = [
+        (CLICKHOUSE_SMALL_U32, Bitmap::of(&[1, 2, 1000])),
+        (CLICKHOUSE_LARGE_U32, Bitmap::of(&even)),
+    ];
+    for (hex, expected) in &cases_32 {
+        let data = from_hex(hex);
+        assert_eq!(
+            &Bitmap::try_deserialize_exact::<ClickHouse>(&data).unwrap(),
+            expected
+        );
+        assert_eq!(expected.serialize::<ClickHouse>(), data);
+        let mut reader = &data[..];
+        let read = Bitmap::deserialize_from_reader::<ClickHouse, _>(&mut reader).unwrap();
+        assert_eq!(&read, expected);
+        assert!(reader.is_empty());
+    }
+
+    let even: Vec<u64> = even.iter().copied().map(u64::from).collect();
+    let cases_64 = [
+        (CLICKHOUSE_SMALL_U64, vec![1, 1 << 32, u64::MAX]),
+        (CLICKHOUSE_LARGE_U64, even),
+    ];
+    for (hex, values) in &cases_64 {
+        let data = from_hex(hex);
+        let bitmap = Bitmap64::of(values);
+        assert_eq!(
+            Bitmap64::try_deserialize_exact::<ClickHouse>(&data).unwrap(),
+            bitmap
+        );
+        assert_eq!(bitmap.serialize::<ClickHouse>(), data);
+        let treemap = Treemap::of(values);
+        assert_eq!(
+            Treemap::try_deserialize_exact::<ClickHouse>(&data).unwrap(),
+            treemap
+        );
+        assert_eq!(treemap.serialize::<ClickHouse>(), data);
+        let mut reader = &data[..];
+        let read = Treemap::deserialize_from_reader::<ClickHouse, _>(&mut reader).unwrap();
+        assert_eq!(read, treemap);
+        assert!(reader.is_empty());
+    }
+
+    // Empty states are small sets
+    assert_eq!(Bitmap::new().serialize::<ClickHouse>(), [0, 0]);
+    assert_eq!(Bitmap64::new().serialize::<ClickHouse>(), [0, 0]);
+    assert_eq!(Treemap::new().serialize::<ClickHouse>(), [0, 0]);
+
+    let err = |hex: &str| Bitmap::try_deserialize_exact::<ClickHouse>(&from_hex(hex)).unwrap_err();
+    assert_eq!(err("02"), DeserializeError::UnknownCookie { cookie: 2 });
+    assert_eq!(
+        err("0021"),
+        DeserializeError::InvalidHeader {
+            reason: "too many values for a small set"
+        }
+    );
+    assert_eq!(
+        err("000201000000"),
+        DeserializeError::Truncated {
+            needed: 10,
+            available: 6
+        }
+    );
+    assert_eq!(
+        err("0180"),
+        DeserializeError::Truncated {
+            needed: 3,
+            available: 2
+        }
+    );
+    assert_eq!(
+        err("01ffffffffffffffffffff01"),
+        DeserializeError::InvalidHeader {
+            reason: "variable length integer is too long"
+        }
+    );
+}
+
 #[test]
 #[cfg(feature = "alloc")]
 fn test_treemap_deserialize_jvm() {
@@ -221,8 +723,8 @@ fn test_treemap_deserialize_jvm() {
                 assert!(treemap.contains(i));
             }
 
-            assert!(treemap.contains(std::u32::MAX as u64));
-            assert!(treemap.contains(std::u64::MAX));
+            assert!(treemap.contains(u32::MAX as u64));
+            assert!(treemap.contains(u64::MAX));
         }
         Err(err) => panic!("Cannot read test file {}", err),
     }
@@ -232,7 +734,7 @@ fn test_treemap_deserialize_jvm() {
 #[cfg(feature = "alloc")]
 fn test_treemap_max_andnot_empty() {
     init();
-    let single_max = Treemap::of(&[std::u64::MAX]);
+    let single_max = Treemap::of(&[u64::MAX]);
     let empty = Treemap::new();
     let diff = single_max.andnot(&empty);
     assert_eq!(diff, single_max);
@@ -386,6 +888,75 @@ fn serialize_into_existing_vec_native() {
     assert!(unsafe { data.as_ptr().offset_from(buffer.as_ptr()) } >= 13);
 }
 
+#[test]
+#[cfg(feature = "std")]
+fn cow_bitmap_mutate_clones_across_threads() {
+    use croaring::CowBitmap;
+    init();
+
+    let mut bitmap = Bitmap::from_range(0..1_000_000);
+    bitmap.add_many(&[2_000_000, 2_000_005, 3_000_000]);
+    let original = CowBitmap::from(bitmap.clone());
+
+    let handles: Vec<_> = (0..8u32)
+        .map(|i| {
+            let mut snapshot = original.clone();
+            std::thread::spawn(move || {
+                for j in 0..100 {
+                    // Each clone shares containers with its siblings until written
+                    let copy = snapshot.clone();
+                    snapshot.remove(i * 1000 + j);
+                    snapshot.add(4_000_000 + i);
+                    assert!(copy.contains(i * 1000 + j));
+                    drop(copy);
+                }
+                snapshot.remove_range(500_000..600_000);
+                snapshot
+            })
+        })
+        .collect();
+
+    // Reading the original while clones are modified on other threads
+    assert_eq!(original.cardinality(), bitmap.cardinality());
+    assert!(original.iter().eq(bitmap.iter()));
+
+    for (i, handle) in handles.into_iter().enumerate() {
+        let i = i as u32;
+        let snapshot = handle.join().unwrap();
+        let mut expected = bitmap.clone();
+        expected.remove_range(i * 1000..i * 1000 + 100);
+        expected.remove_range(500_000..600_000);
+        expected.add(4_000_000 + i);
+        assert_eq!(snapshot, expected);
+        assert_eq!(snapshot.into_bitmap(), expected);
+    }
+
+    assert_eq!(original, bitmap);
+    let mut original = original;
+    assert!(original.unshare());
+    assert!(!original.contains_shared());
+    assert_eq!(original, bitmap);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn cow_bitmap_stays_shared_after_overwriting_empty() {
+    use croaring::CowBitmap;
+    init();
+
+    let other = Bitmap::from_range(0..100_000);
+    // CRoaring overwrites an empty bitmap with a copy of the other side of an or/xor
+    for op in [CowBitmap::or_inplace, CowBitmap::xor_inplace] {
+        let mut cow = CowBitmap::new();
+        op(&mut cow, &other);
+        let clone = cow.clone();
+        assert!(cow.contains_shared());
+        assert!(clone.contains_shared());
+        assert_eq!(clone, other);
+        assert_eq!(cow.into_bitmap(), other);
+    }
+}
+
 proptest! {
     #[test]
     fn bitmap_cardinality_roundtrip(
@@ -456,6 +1027,722 @@ proptest! {
 
         prop_assert_eq!(original , deserialized);
     }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_bitmap64_jvm_serialization_roundtrip(
+        indices in prop::collection::vec(proptest::num::u64::ANY, 1..3000)
+    ) {
+        init();
+        let original = croaring::Bitmap64::of(&indices);
+
+        let buffer = original.serialize::<JvmLegacy>();
+        // Both store each group of values with the same high 32 bits as a 32-bit bitmap
+        prop_assert_eq!(&buffer, &Treemap::of(&indices).serialize::<JvmLegacy>());
+
+        let deserialized = croaring::Bitmap64::try_deserialize::<JvmLegacy>(&buffer).unwrap();
+
+        prop_assert_eq!(original , deserialized);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_clickhouse_serialization_roundtrip(
+        indices in prop::collection::vec(0..1u64 << 34, 0..100)
+    ) {
+        use croaring::ClickHouse;
+
+        init();
+        let bitmap = Bitmap::of(&indices.iter().map(|&v| v as u32).collect::<Vec<_>>());
+        let buffer = bitmap.serialize::<ClickHouse>();
+        prop_assert_eq!(buffer.len(), bitmap.get_serialized_size_in_bytes::<ClickHouse>());
+        prop_assert_eq!(Bitmap::try_deserialize_exact::<ClickHouse>(&buffer).unwrap(), bitmap);
+
+        let bitmap64 = croaring::Bitmap64::of(&indices);
+        let buffer = bitmap64.serialize::<ClickHouse>();
+        // Both store large states in the same portable format
+        prop_assert_eq!(&buffer, &Treemap::of(&indices).serialize::<ClickHouse>());
+        prop_assert_eq!(croaring::Bitmap64::try_deserialize_exact::<ClickHouse>(&buffer).unwrap(), bitmap64);
+        prop_assert_eq!(Treemap::try_deserialize_exact::<ClickHouse>(&buffer).unwrap(), Treemap::of(&indices));
+    }
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn treemap_ord_ignores_empty_bitmaps() {
+    init();
+    let treemap1 = Treemap::of(&[1, 2, 1 << 40]);
+    let mut treemap2 = treemap1.clone();
+    treemap2.map.insert(5, Bitmap::new());
+    assert_eq!(treemap1, treemap2);
+    assert_eq!(treemap1.cmp(&treemap2), std::cmp::Ordering::Equal);
+    assert!(treemap2 < Treemap::of(&[1, 3]));
+    assert!(Treemap::of(&[1, 2, 1 << 41]) > treemap2);
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn treemap_par_fast_or_skips_empty_bitmaps() {
+    init();
+    let treemap1 = Treemap::of(&[1, 1 << 40]);
+    let mut treemap2 = Treemap::of(&[2]);
+    treemap2.map.insert(5, Bitmap::new());
+    let union = Treemap::par_fast_or(&[&treemap1, &treemap2]);
+    assert_eq!(union.map.keys().copied().collect::<Vec<_>>(), [0, 1 << 8]);
+    assert_eq!(union.iter().collect::<Vec<_>>(), [1, 2, 1 << 40]);
+}
+
+proptest! {
+    #[test]
+    fn bitmap_ord_matches_btreeset(
+        a in prop::collection::btree_set(0..2000u32, 0..100),
+        b in prop::collection::btree_set(0..2000u32, 0..100),
+    ) {
+        init();
+        let bitmap_a: Bitmap = a.iter().copied().collect();
+        let bitmap_b: Bitmap = b.iter().copied().collect();
+        prop_assert_eq!(bitmap_a.cmp(&bitmap_b), a.cmp(&b));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn treemap_ord_matches_btreeset(
+        a in prop::collection::btree_set(prop::sample::select(vec![0, 1, 2, u64::from(u32::MAX), 1 << 32, u64::MAX]), 0..6),
+        b in prop::collection::btree_set(prop::sample::select(vec![0, 1, 2, u64::from(u32::MAX), 1 << 32, u64::MAX]), 0..6),
+    ) {
+        init();
+        let treemap_a: Treemap = a.iter().copied().collect();
+        let treemap_b: Treemap = b.iter().copied().collect();
+        prop_assert_eq!(treemap_a.cmp(&treemap_b), a.cmp(&b));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn bitmap_view_hash_matches_bitmap(
+        indices in prop::collection::vec(proptest::num::u32::ANY, 0..3000)
+    ) {
+        use std::hash::BuildHasher;
+        init();
+
+        let hasher = std::collections::hash_map::RandomState::new();
+        let original = Bitmap::of(&indices);
+        let mut optimized = original.clone();
+        optimized.run_optimize();
+        let serialized = optimized.serialize::<Portable>();
+        let view = unsafe { BitmapView::deserialize::<Portable>(&serialized) };
+        prop_assert_eq!(hasher.hash_one(&original), hasher.hash_one(&optimized));
+        prop_assert_eq!(hasher.hash_one(&original), hasher.hash_one(&view));
+    }
+}
+
+proptest! {
+    #[test]
+    fn rank_many_select_many_match_single(
+        indices in prop::collection::vec(0..300_000u32, 0..3000),
+        ranges in prop::collection::vec((0..300_000u32, 0..5000u32), 0..4),
+        mut queries in prop::collection::vec(0..400_000u32, 0..100),
+        sort in proptest::bool::ANY,
+    ) {
+        init();
+        let mut bitmap = Bitmap::of(&indices);
+        for (start, len) in ranges {
+            bitmap.add_range(start..start + len);
+        }
+        bitmap.run_optimize();
+        if sort {
+            queries.sort_unstable();
+        }
+
+        let mut ranks = vec![0; queries.len()];
+        bitmap.rank_many(&queries, &mut ranks);
+        let expected: Vec<u64> = queries.iter().map(|&q| bitmap.rank(q)).collect();
+        prop_assert_eq!(&ranks, &expected);
+
+        let mut selected = vec![0; queries.len()];
+        let n = bitmap.select_many(&queries, &mut selected);
+        let expected: Vec<u32> = queries.iter().map_while(|&q| bitmap.select(q)).collect();
+        prop_assert_eq!(&selected[..n], &expected[..]);
+
+        let bitmap64: croaring::Bitmap64 = bitmap.iter().map(|v| u64::from(v) << 12).collect();
+        let queries64: Vec<u64> = queries.iter().map(|&q| u64::from(q) << 12).collect();
+        let mut ranks = vec![0; queries.len()];
+        bitmap64.rank_many(&queries64, &mut ranks);
+        let expected: Vec<u64> = queries64.iter().map(|&q| bitmap64.rank(q)).collect();
+        prop_assert_eq!(&ranks, &expected);
+
+        let positions: Vec<u64> = queries.iter().map(|&q| u64::from(q)).collect();
+        let mut selected = vec![0; queries.len()];
+        let n = bitmap64.select_many(&positions, &mut selected);
+        let expected: Vec<u64> = positions.iter().map_while(|&p| bitmap64.select(p)).collect();
+        prop_assert_eq!(&selected[..n], &expected[..]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn treemap_rank_many_select_many_match_single(
+        indices in prop::collection::vec(0..300_000u64, 0..3000),
+        mut queries in prop::collection::vec(0..400_000u64, 0..100),
+        sort in proptest::bool::ANY,
+    ) {
+        init();
+        // Spread values across several 32-bit keys
+        let treemap: Treemap = indices.iter().map(|&v| v << 15).collect();
+        if sort {
+            queries.sort_unstable();
+        }
+
+        let values: Vec<u64> = queries.iter().map(|&q| q << 15).collect();
+        let mut ranks = vec![0; values.len()];
+        treemap.rank_many(&values, &mut ranks);
+        let expected: Vec<u64> = values.iter().map(|&v| treemap.rank(v)).collect();
+        prop_assert_eq!(&ranks, &expected);
+
+        let mut selected = vec![0; queries.len()];
+        let n = treemap.select_many(&queries, &mut selected);
+        let expected: Vec<u64> = queries.iter().map_while(|&q| treemap.select(q)).collect();
+        prop_assert_eq!(&selected[..n], &expected[..]);
+    }
+
+    #[test]
+    fn bulk_context_matches_single(
+        indices in prop::collection::vec(0..300_000u32, 0..3000),
+        adds in prop::collection::vec(0..300_000u32, 0..300),
+        queries in prop::collection::vec(0..400_000u32, 0..300),
+    ) {
+        init();
+        let mut bitmap = Bitmap::of(&indices);
+        let mut expected = bitmap.clone();
+        let mut context = bitmap.bulk_context_mut();
+        for (&add, &query) in adds.iter().zip(&queries) {
+            context.add(add);
+            expected.add(add);
+            prop_assert_eq!(context.contains(query), expected.contains(query));
+        }
+        prop_assert_eq!(&bitmap, &expected);
+
+        let mut bitmap64: croaring::Bitmap64 = indices.iter().map(|&v| u64::from(v) << 10).collect();
+        let mut expected64 = bitmap64.clone();
+        let mut context = bitmap64.bulk_context_mut();
+        for (&add, &query) in adds.iter().zip(&queries) {
+            context.add(u64::from(add) << 10);
+            expected64.add(u64::from(add) << 10);
+            prop_assert_eq!(context.contains(u64::from(query) << 10), expected64.contains(u64::from(query) << 10));
+        }
+        prop_assert_eq!(&bitmap64, &expected64);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn to_vec_range_matches_to_vec(
+        indices in prop::collection::vec(0..300_000u32, 0..3000),
+        offset in 0..3500u32,
+        limit in 0..3500usize,
+    ) {
+        init();
+        let bitmap = Bitmap::of(&indices);
+        let all = bitmap.to_vec();
+        let start = all.len().min(offset as usize);
+        let expected = &all[start..all.len().min(start + limit)];
+        prop_assert_eq!(&bitmap.to_vec_range(offset, limit)[..], expected);
+
+        let bitmap64: croaring::Bitmap64 = indices.iter().map(|&v| u64::from(v) << 20).collect();
+        let all64 = bitmap64.to_vec();
+        let expected64 = &all64[start..all64.len().min(start + limit)];
+        prop_assert_eq!(&bitmap64.to_vec_range(u64::from(offset), limit)[..], expected64);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn containers_match_values(
+        indices in prop::collection::vec(0..300_000u32, 0..3000),
+        ranges in prop::collection::vec((0..300_000u32, 0..70_000u32), 0..4),
+        optimize in proptest::bool::ANY,
+    ) {
+        use croaring::bitmap::ContainerType;
+        use std::collections::BTreeMap;
+        init();
+
+        let mut bitmap = Bitmap::of(&indices);
+        for (start, len) in ranges {
+            bitmap.add_range(start..start.saturating_add(len));
+        }
+        if optimize {
+            bitmap.run_optimize();
+        }
+
+        // (cardinality, runs) for each key, computed from the values
+        let mut expected = BTreeMap::<u16, (u32, u32)>::new();
+        let mut prev = None;
+        for value in bitmap.iter() {
+            let entry = expected.entry((value >> 16) as u16).or_default();
+            entry.0 += 1;
+            if prev.map(|prev| prev + 1) != Some(value) || value & 0xFFFF == 0 {
+                entry.1 += 1;
+            }
+            prev = Some(value);
+        }
+
+        let containers: Vec<_> = bitmap.containers().collect();
+        let actual: BTreeMap<u16, (u32, u32)> = containers
+            .iter()
+            .map(|c| (c.key, (c.cardinality, c.n_runs)))
+            .collect();
+        prop_assert_eq!(&actual, &expected);
+
+        let statistics = bitmap.statistics();
+        let count = |ty| containers.iter().filter(|c| c.container_type == ty).count() as u32;
+        let bytes = |ty| containers.iter().filter(|c| c.container_type == ty).map(|c| c.size_in_bytes).sum::<u32>();
+        prop_assert_eq!(count(ContainerType::Array), statistics.n_array_containers);
+        prop_assert_eq!(count(ContainerType::Bitset), statistics.n_bitset_containers);
+        prop_assert_eq!(count(ContainerType::Run), statistics.n_run_containers);
+        prop_assert_eq!(bytes(ContainerType::Array), statistics.n_bytes_array_containers);
+        prop_assert_eq!(bytes(ContainerType::Bitset), statistics.n_bytes_bitset_containers);
+        prop_assert_eq!(bytes(ContainerType::Run), statistics.n_bytes_run_containers);
+
+        let serialized = bitmap.serialize::<Portable>();
+        let view = unsafe { BitmapView::deserialize::<Portable>(&serialized) };
+        prop_assert_eq!(view.containers().collect::<Vec<_>>(), containers);
+
+        // Container types depend on how the bitmap was built, so compare after optimizing both
+        bitmap.run_optimize();
+        let mut bitmap64: croaring::Bitmap64 = bitmap.iter().map(|v| u64::from(v) | (1 << 40)).collect();
+        bitmap64.run_optimize();
+        let actual64: Vec<_> = bitmap64
+            .containers()
+            .map(|c| (c.key, c.container_type, c.cardinality, c.size_in_bytes, c.n_runs))
+            .collect();
+        let expected64: Vec<_> = bitmap
+            .containers()
+            .map(|c| ((1 << 24) | u64::from(c.key), c.container_type, c.cardinality, c.size_in_bytes, c.n_runs))
+            .collect();
+        prop_assert_eq!(actual64, expected64);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn fast_and_matches_fold(
+        sets in prop::collection::vec(prop::collection::vec(0..2000u64, 0..500), 0..5),
+    ) {
+        init();
+        // Spread the values over several containers
+        let bitmaps: Vec<Bitmap> = sets.iter().map(|set| set.iter().map(|&v| (v << 8) as u32).collect()).collect();
+        let refs: Vec<&Bitmap> = bitmaps.iter().collect();
+        let expected = bitmaps.iter().skip(1).fold(bitmaps.first().cloned().unwrap_or_default(), |acc, b| acc.and(b));
+        prop_assert_eq!(&Bitmap::fast_and(&refs), &expected);
+        prop_assert_eq!(Bitmap::fast_and_cardinality(&refs), expected.cardinality());
+
+        let serialized: Vec<Vec<u8>> = bitmaps.iter().map(|b| b.serialize::<Portable>()).collect();
+        let views: Vec<BitmapView> = serialized.iter().map(|data| unsafe { BitmapView::deserialize::<Portable>(data) }).collect();
+        let refs: Vec<&Bitmap> = views.iter().map(|view| &**view).collect();
+        prop_assert_eq!(&Bitmap::fast_and(&refs), &expected);
+        prop_assert_eq!(Bitmap::fast_and_cardinality(&refs), expected.cardinality());
+
+        let bitmaps: Vec<croaring::Bitmap64> = sets.iter().map(|set| set.iter().map(|&v| v << 20).collect()).collect();
+        let refs: Vec<&croaring::Bitmap64> = bitmaps.iter().collect();
+        let expected = bitmaps.iter().skip(1).fold(bitmaps.first().cloned().unwrap_or_default(), |acc, b| acc.and(b));
+        prop_assert_eq!(&croaring::Bitmap64::fast_and(&refs), &expected);
+        prop_assert_eq!(croaring::Bitmap64::fast_and_cardinality(&refs), expected.cardinality());
+
+        let treemaps: Vec<Treemap> = sets.iter().map(|set| set.iter().map(|&v| v << 22).collect()).collect();
+        let refs: Vec<&Treemap> = treemaps.iter().collect();
+        let expected = treemaps.iter().skip(1).fold(treemaps.first().cloned().unwrap_or_default(), |acc, t| acc.and(t));
+        prop_assert_eq!(&Treemap::fast_and(&refs), &expected);
+        prop_assert_eq!(Treemap::fast_and_cardinality(&refs), expected.cardinality());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn threshold_matches_counts(
+        sets in prop::collection::vec(prop::collection::btree_set(0..500u32, 0..300), 0..12),
+        k in 0..14usize,
+    ) {
+        use std::collections::BTreeMap;
+        init();
+        let mut counts = BTreeMap::<u32, usize>::new();
+        for &value in sets.iter().flatten() {
+            *counts.entry(value).or_default() += 1;
+        }
+        let expected: Vec<u32> = counts.iter().filter(|&(_, &count)| count >= k).map(|(&v, _)| v).collect();
+
+        let bitmaps: Vec<Bitmap> = sets.iter().map(|set| set.iter().copied().collect()).collect();
+        let refs: Vec<&Bitmap> = bitmaps.iter().collect();
+        prop_assert_eq!(Bitmap::threshold(&refs, k).to_vec(), expected.clone());
+
+        let bitmaps: Vec<croaring::Bitmap64> = sets.iter().map(|set| set.iter().map(|&v| u64::from(v) << 30).collect()).collect();
+        let refs: Vec<&croaring::Bitmap64> = bitmaps.iter().collect();
+        let expected64: Vec<u64> = expected.iter().map(|&v| u64::from(v) << 30).collect();
+        prop_assert_eq!(croaring::Bitmap64::threshold(&refs, k).to_vec(), expected64);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ranges_match_values(
+        runs in prop::collection::vec((0..200_000u32, 1..5000u32), 0..8),
+        at_end in any::<bool>(),
+        optimize in any::<bool>(),
+    ) {
+        init();
+        let base = if at_end { u32::MAX - 210_000 } else { 0 };
+        let mut bitmap = Bitmap::new();
+        for &(start, len) in &runs {
+            bitmap.add_range(base + start..base + start + len);
+        }
+        if at_end && !runs.is_empty() {
+            bitmap.add_range(u32::MAX - 100..=u32::MAX);
+        }
+        if optimize {
+            bitmap.run_optimize();
+        }
+
+        let mut expected: Vec<std::ops::RangeInclusive<u32>> = Vec::new();
+        for value in bitmap.iter() {
+            match expected.last_mut() {
+                Some(last) if last.end() + 1 == value => *last = *last.start()..=value,
+                _ => expected.push(value..=value),
+            }
+        }
+        prop_assert_eq!(bitmap.ranges().collect::<Vec<_>>(), expected.clone());
+        prop_assert_eq!(&Bitmap::from_ranges(expected.clone()), &bitmap);
+
+        let to64 = |v: u32| u64::from(v) + if at_end { u64::MAX - u64::from(u32::MAX) } else { 0xFFFF_0000 };
+        let expected64: Vec<std::ops::RangeInclusive<u64>> =
+            expected.iter().map(|r| to64(*r.start())..=to64(*r.end())).collect();
+        let bitmap64 = croaring::Bitmap64::from_ranges(expected64.clone());
+        prop_assert_eq!(bitmap64.cardinality(), bitmap.cardinality());
+        prop_assert_eq!(bitmap64.ranges().collect::<Vec<_>>(), expected64.clone());
+        let treemap = Treemap::from_ranges(expected64.clone());
+        prop_assert_eq!(treemap.cardinality(), bitmap.cardinality());
+        prop_assert_eq!(treemap.ranges().collect::<Vec<_>>(), expected64);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn double_ended_iter_matches_vec(
+        values in prop::collection::btree_set(proptest::num::u32::ANY, 0..500),
+        ops in prop::collection::vec((0..6u8, 0..20usize), 0..60),
+    ) {
+        use std::collections::VecDeque;
+        init();
+        let bitmap = Bitmap::from_iter(values.iter().copied());
+        let bitmap64 = croaring::Bitmap64::from_iter(values.iter().map(|&v| u64::from(v) << 20));
+        let treemap = Treemap::from_iter(values.iter().map(|&v| u64::from(v) << 20));
+
+        let mut expected: VecDeque<u64> = values.iter().map(|&v| u64::from(v) << 20).collect();
+        let mut iter = bitmap.iter();
+        let mut iter64 = bitmap64.iter();
+        let mut tree_iter = treemap.iter();
+        for (op, n) in ops {
+            let (actual, actual64, actual_tree, model) = match op {
+                0 => (
+                    iter.next().map(|v| u64::from(v) << 20),
+                    iter64.next(),
+                    tree_iter.next(),
+                    expected.pop_front(),
+                ),
+                1 => (
+                    iter.next_back().map(|v| u64::from(v) << 20),
+                    iter64.next_back(),
+                    tree_iter.next_back(),
+                    expected.pop_back(),
+                ),
+                2 => (
+                    iter.nth(n).map(|v| u64::from(v) << 20),
+                    iter64.nth(n),
+                    tree_iter.nth(n),
+                    {
+                        expected.drain(..n.min(expected.len()));
+                        expected.pop_front()
+                    },
+                ),
+                3 => (
+                    iter.nth_back(n).map(|v| u64::from(v) << 20),
+                    iter64.nth_back(n),
+                    tree_iter.nth_back(n),
+                    {
+                        expected.truncate(expected.len().saturating_sub(n));
+                        expected.pop_back()
+                    },
+                ),
+                4 => {
+                    let mut buf = vec![0; n];
+                    let read = iter.next_many(&mut buf);
+                    let mut buf64 = vec![0; n];
+                    prop_assert_eq!(iter64.next_many(&mut buf64), read);
+                    let model: Vec<u64> = expected.drain(..n.min(expected.len())).collect();
+                    prop_assert_eq!(buf[..read].iter().map(|&v| u64::from(v) << 20).collect::<Vec<_>>(), model.clone());
+                    prop_assert_eq!(&buf64[..read], &model[..]);
+                    let tree_model: Vec<u64> = tree_iter.by_ref().take(model.len()).collect();
+                    prop_assert_eq!(tree_model, model);
+                    continue;
+                }
+                _ => {
+                    prop_assert_eq!(iter.peek().map(|v| u64::from(v) << 20), expected.front().copied());
+                    prop_assert_eq!(iter64.peek(), expected.front().copied());
+                    continue;
+                }
+            };
+            prop_assert_eq!(actual, model);
+            prop_assert_eq!(actual64, model);
+            prop_assert_eq!(actual_tree, model);
+            prop_assert_eq!(iter.size_hint(), (expected.len(), Some(expected.len())));
+            prop_assert_eq!(iter64.size_hint(), (expected.len(), Some(expected.len())));
+            prop_assert_eq!(tree_iter.size_hint(), (expected.len(), Some(expected.len())));
+        }
+        prop_assert_eq!(iter.rev().map(|v| u64::from(v) << 20).collect::<Vec<_>>(), expected.iter().rev().copied().collect::<Vec<_>>());
+        prop_assert_eq!(iter64.collect::<Vec<_>>(), expected.iter().copied().collect::<Vec<_>>());
+        prop_assert_eq!(tree_iter.rev().collect::<Vec<_>>(), expected.iter().rev().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn iter_range_matches_filter(
+        values in prop::collection::btree_set(proptest::num::u32::ANY, 0..500),
+        start in proptest::num::u32::ANY,
+        end in proptest::num::u32::ANY,
+        start_bound in 0..3u8,
+        end_bound in 0..3u8,
+        batch in 1..50usize,
+    ) {
+        use std::ops::{Bound, RangeBounds};
+        init();
+        let bound = |kind: u8, value: u32| match kind {
+            0 => Bound::Included(value),
+            1 => Bound::Excluded(value),
+            _ => Bound::Unbounded,
+        };
+        let range = (bound(start_bound, start), bound(end_bound, end));
+        let expected: Vec<u32> = values.iter().copied().filter(|v| range.contains(v)).collect();
+        let to64 = |v: u32| u64::from(v) << 20;
+        let range64 = (range.0.map(to64), range.1.map(to64));
+        let expected64: Vec<u64> = expected.iter().map(|&v| to64(v)).collect();
+
+        let bitmap = Bitmap::from_iter(values.iter().copied());
+        prop_assert_eq!(bitmap.iter_range(range).size_hint(), (expected.len(), Some(expected.len())));
+        prop_assert_eq!(bitmap.iter_range(range).collect::<Vec<_>>(), expected.clone());
+        prop_assert_eq!(bitmap.iter_range(range).rev().collect::<Vec<_>>(), expected.iter().rev().copied().collect::<Vec<_>>());
+        let mut iter = bitmap.iter_range(range);
+        let mut buf = vec![0; batch];
+        let mut batched = Vec::new();
+        loop {
+            let n = iter.next_many(&mut buf);
+            batched.extend_from_slice(&buf[..n]);
+            if n < batch {
+                break;
+            }
+        }
+        prop_assert_eq!(batched, expected.clone());
+
+        let bitmap64 = croaring::Bitmap64::from_iter(values.iter().map(|&v| to64(v)));
+        prop_assert_eq!(bitmap64.iter_range(range64).size_hint(), (expected64.len(), Some(expected64.len())));
+        prop_assert_eq!(bitmap64.iter_range(range64).collect::<Vec<_>>(), expected64.clone());
+        prop_assert_eq!(bitmap64.iter_range(range64).rev().collect::<Vec<_>>(), expected64.iter().rev().copied().collect::<Vec<_>>());
+        let mut iter = bitmap64.iter_range(range64);
+        let mut buf = vec![0; batch];
+        let mut batched = Vec::new();
+        loop {
+            let n = iter.next_many(&mut buf);
+            batched.extend_from_slice(&buf[..n]);
+            if n < batch {
+                break;
+            }
+        }
+        prop_assert_eq!(batched, expected64.clone());
+
+        let treemap = Treemap::from_iter(values.iter().map(|&v| to64(v)));
+        prop_assert_eq!(treemap.iter_range(range64).size_hint(), (expected64.len(), Some(expected64.len())));
+        prop_assert_eq!(treemap.iter_range(range64).collect::<Vec<_>>(), expected64.clone());
+        prop_assert_eq!(treemap.iter_range(range64).rev().collect::<Vec<_>>(), expected64.iter().rev().copied().collect::<Vec<_>>());
+        let mut iter = treemap.iter_range(range64);
+        let mut buf = vec![0; batch];
+        let mut batched = Vec::new();
+        loop {
+            let n = iter.next_many(&mut buf);
+            batched.extend_from_slice(&buf[..n]);
+            if n < batch {
+                break;
+            }
+        }
+        prop_assert_eq!(batched, expected64);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn split_off_append_roundtrip(
+        values in prop::collection::btree_set(0..0x8_0000u32, 0..2000),
+        ranges in prop::collection::vec((0..0x8_0000u32, 0..0x4000u32), 0..3),
+        at in 0..0x9_0000u32,
+        retain in (0..0x9_0000u32, 0..0x9_0000u32),
+        append_to_head in any::<bool>(),
+    ) {
+        init();
+        let mut original = Bitmap::from_iter(values.iter().copied());
+        for &(start, len) in &ranges {
+            original.add_range(start..start.saturating_add(len));
+        }
+        original.run_optimize();
+        let mut bitmap = original.clone();
+        let mut tail = bitmap.split_off(at);
+        bitmap.internal_validate().unwrap();
+        tail.internal_validate().unwrap();
+        prop_assert_eq!(bitmap.to_vec(), original.iter().filter(|&v| v < at).collect::<Vec<_>>());
+        prop_assert_eq!(tail.to_vec(), original.iter().filter(|&v| v >= at).collect::<Vec<_>>());
+
+        if append_to_head {
+            bitmap.append(&mut tail);
+        } else {
+            tail.append(&mut bitmap);
+            std::mem::swap(&mut bitmap, &mut tail);
+        }
+        bitmap.internal_validate().unwrap();
+        prop_assert!(tail.is_empty());
+        prop_assert_eq!(&bitmap, &original);
+
+        let mut retained = original.clone();
+        retained.retain_range(retain.0..retain.1);
+        retained.internal_validate().unwrap();
+        let range = retain.0..retain.1;
+        prop_assert_eq!(retained.to_vec(), original.iter().filter(|v| range.contains(v)).collect::<Vec<_>>());
+
+        let to64 = |v: u32| u64::from(v) << 15;
+        let original64 = Treemap::from_iter(original.iter().map(to64));
+        let mut treemap = original64.clone();
+        let mut tail = treemap.split_off(to64(at));
+        prop_assert_eq!(treemap.iter().collect::<Vec<_>>(), original.iter().filter(|&v| v < at).map(to64).collect::<Vec<_>>());
+        prop_assert_eq!(tail.iter().collect::<Vec<_>>(), original.iter().filter(|&v| v >= at).map(to64).collect::<Vec<_>>());
+        treemap.append(&mut tail);
+        prop_assert!(tail.is_empty());
+        prop_assert_eq!(&treemap, &original64);
+        treemap.retain_range(to64(range.start)..to64(range.end));
+        prop_assert_eq!(treemap.iter().collect::<Vec<_>>(), retained.iter().map(to64).collect::<Vec<_>>());
+
+        let original64 = croaring::Bitmap64::from_iter(original.iter().map(to64));
+        let mut bitmap64 = original64.clone();
+        // Split inside containers, so both sides hold part of the container holding `at`
+        let at64 = to64(at) + u64::from(at % 7);
+        let mut tail = bitmap64.split_off(at64);
+        bitmap64.internal_validate().unwrap();
+        tail.internal_validate().unwrap();
+        prop_assert_eq!(bitmap64.iter().collect::<Vec<_>>(), original64.iter().filter(|&v| v < at64).collect::<Vec<_>>());
+        prop_assert_eq!(tail.iter().collect::<Vec<_>>(), original64.iter().filter(|&v| v >= at64).collect::<Vec<_>>());
+        bitmap64.append(&mut tail);
+        prop_assert_eq!(&bitmap64, &original64);
+        bitmap64.retain_range(to64(range.start)..to64(range.end));
+        prop_assert_eq!(bitmap64.iter().collect::<Vec<_>>(), retained.iter().map(to64).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn add_offset_matches_shifted_values(
+        values in prop::collection::btree_set(any::<u32>(), 0..500),
+        offset in -0x1_0000_0000i64..=0x1_0000_0000,
+        aligned in any::<bool>(),
+        high in prop::collection::vec(prop_oneof![0..4u32, u32::MAX - 3..=u32::MAX], 1..4),
+    ) {
+        init();
+        // Offsets which are a multiple of the container or bucket size take a faster path
+        let offset32 = if aligned { offset & !0xFFFF } else { offset };
+        let bitmap = Bitmap::from_iter(values.iter().copied());
+        let expected = values
+            .iter()
+            .filter_map(|&v| u32::try_from(i64::from(v) + offset32).ok())
+            .collect::<Vec<_>>();
+        let dropped = (values.len() - expected.len()) as u64;
+
+        let (shifted, shifted_dropped) = bitmap.add_offset_checked(offset32);
+        prop_assert_eq!(shifted.to_vec(), expected.clone());
+        prop_assert_eq!(shifted_dropped, dropped);
+        let mut inplace = bitmap.clone();
+        inplace.add_offset_inplace(offset32);
+        inplace.internal_validate().unwrap();
+        prop_assert_eq!(&inplace, &shifted);
+        match u32::try_from(offset32.unsigned_abs()) {
+            Ok(rhs) if offset32 >= 0 => prop_assert_eq!(&(&bitmap << rhs), &shifted),
+            Ok(rhs) => prop_assert_eq!(&(&bitmap >> rhs), &shifted),
+            Err(_) => {}
+        }
+
+        let values64 = high
+            .iter()
+            .flat_map(|&h| values.iter().map(move |&v| (u64::from(h) << 32) | u64::from(v)))
+            .collect::<std::collections::BTreeSet<_>>();
+        let offset64 = if aligned { i128::from(offset) << 32 } else { i128::from(offset) << 16 };
+        let expected = values64
+            .iter()
+            .filter_map(|&v| u64::try_from(i128::from(v) + offset64).ok())
+            .collect::<Vec<_>>();
+        let dropped = (values64.len() - expected.len()) as u64;
+
+        let treemap = Treemap::from_iter(values64.iter().copied());
+        let (shifted, shifted_dropped) = treemap.add_offset_checked(offset64);
+        prop_assert_eq!(shifted.iter().collect::<Vec<_>>(), expected.clone());
+        prop_assert_eq!(shifted_dropped, dropped);
+        prop_assert!(shifted.map.values().all(|bitmap| !bitmap.is_empty()));
+        let mut inplace = treemap.clone();
+        inplace.add_offset_inplace(offset64);
+        prop_assert_eq!(&inplace, &shifted);
+
+        let bitmap64 = croaring::Bitmap64::from_iter(values64.iter().copied());
+        let (shifted, shifted_dropped) = bitmap64.add_offset_checked(offset64);
+        prop_assert_eq!(shifted.iter().collect::<Vec<_>>(), expected);
+        prop_assert_eq!(shifted_dropped, dropped);
+        let mut inplace = bitmap64.clone();
+        inplace.add_offset_inplace(offset64);
+        prop_assert_eq!(&inplace, &shifted);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn par_ops_match_sequential(
+        sets in prop::collection::vec(
+            prop::collection::vec(prop_oneof![0..0x4_0000u32, u32::MAX - 0x1_0000..=u32::MAX], 0..100),
+            0..20,
+        ),
+    ) {
+        use rayon::prelude::*;
+
+        init();
+        let bitmaps = sets.iter().map(|values| Bitmap::of(values)).collect::<Vec<_>>();
+        let refs = bitmaps.iter().collect::<Vec<_>>();
+        prop_assert_eq!(Bitmap::par_fast_or(&refs), Bitmap::fast_or(&refs));
+        prop_assert_eq!(Bitmap::par_fast_and(&refs), Bitmap::fast_and(&refs));
+        prop_assert_eq!(Bitmap::par_fast_xor(&refs), Bitmap::fast_xor(&refs));
+
+        let union = Bitmap::fast_or(&refs);
+        prop_assert_eq!(union.par_iter().collect::<Vec<_>>(), union.to_vec());
+        let collected: Bitmap = sets.par_iter().flatten().copied().collect();
+        prop_assert_eq!(&collected, &union);
+
+        let to64 = |v: u32| u64::from(v) << 20;
+        let expected = union.iter().map(to64).collect::<Vec<_>>();
+        let bitmaps64 = sets
+            .iter()
+            .map(|values| croaring::Bitmap64::from_iter(values.iter().copied().map(to64)))
+            .collect::<Vec<_>>();
+        let refs64 = bitmaps64.iter().collect::<Vec<_>>();
+        let union64 = croaring::Bitmap64::par_fast_or(&refs64);
+        prop_assert_eq!(union64.iter().collect::<Vec<_>>(), expected.clone());
+        prop_assert_eq!(union64.par_iter().collect::<Vec<_>>(), expected.clone());
+        let collected64: croaring::Bitmap64 = union.par_iter().map(to64).collect();
+        prop_assert_eq!(&collected64, &union64);
+        let and64 = croaring::Bitmap64::par_fast_and(&refs64);
+        prop_assert_eq!(and64.iter().collect::<Vec<_>>(), Bitmap::fast_and(&refs).iter().map(to64).collect::<Vec<_>>());
+        let xor64 = croaring::Bitmap64::par_fast_xor(&refs64);
+        prop_assert_eq!(xor64.iter().collect::<Vec<_>>(), Bitmap::fast_xor(&refs).iter().map(to64).collect::<Vec<_>>());
+
+        let treemaps = sets
+            .iter()
+            .map(|values| Treemap::from_iter(values.iter().copied().map(to64)))
+            .collect::<Vec<_>>();
+        let treemap_refs = treemaps.iter().collect::<Vec<_>>();
+        let treemap_union = Treemap::par_fast_or(&treemap_refs);
+        prop_assert_eq!(treemap_union.iter().collect::<Vec<_>>(), expected.clone());
+        prop_assert_eq!(treemap_union.par_iter().collect::<Vec<_>>(), expected);
+        let collected: Treemap = union.par_iter().map(to64).collect();
+        prop_assert_eq!(&collected, &treemap_union);
+        prop_assert_eq!(Treemap::par_fast_and(&treemap_refs), Treemap::fast_and(&treemap_refs));
+        let treemap_xor = Treemap::par_fast_xor(&treemap_refs);
+        prop_assert_eq!(treemap_xor.iter().collect::<Vec<_>>(), Bitmap::fast_xor(&refs).iter().map(to64).collect::<Vec<_>>());
+    }
 }
 
 proptest! {
@@ -489,6 +1776,125 @@ proptest! {
         assert!(original.iter().eq(deserialized.iter()));
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn writer_reader_roundtrip(
+        indices in prop::collection::vec(proptest::num::u32::ANY, 0..3000),
+        ranges in prop::collection::vec((0..0x8_0000u32, 0..0x1_8000u32), 0..3),
+        run_optimize in any::<bool>(),
+    ) {
+        init();
+
+        let mut original = Bitmap::of(&indices);
+        for &(start, len) in &ranges {
+            original.add_range(start..start + len);
+        }
+        if run_optimize {
+            original.run_optimize();
+        }
+
+        let mut data = Vec::new();
+        let portable_len = original.serialize_into_writer::<Portable, _>(&mut data).unwrap();
+        prop_assert_eq!(&data[..], &original.serialize::<Portable>()[..]);
+        let native_len = original.serialize_into_writer::<Native, _>(&mut data).unwrap();
+        prop_assert_eq!(&data[portable_len..], &original.serialize::<Native>()[..]);
+        prop_assert_eq!(data.len(), portable_len + native_len);
+
+        let original64 = croaring::Bitmap64::from_iter(original.iter().map(|v| u64::from(v) << 4));
+        original64.serialize_into_writer::<Portable, _>(&mut data).unwrap();
+        original64.serialize_into_writer::<Native, _>(&mut data).unwrap();
+        let treemap = Treemap::from_iter(original.iter().map(|v| u64::from(v) << 4));
+        treemap.serialize_into_writer::<Native, _>(&mut data).unwrap();
+        treemap.serialize_into_writer::<JvmLegacy, _>(&mut data).unwrap();
+
+        let mut reader = &data[..];
+        prop_assert_eq!(&Bitmap::deserialize_from_reader::<Portable, _>(&mut reader).unwrap(), &original);
+        prop_assert_eq!(&Bitmap::deserialize_from_reader::<Native, _>(&mut reader).unwrap(), &original);
+        prop_assert_eq!(&croaring::Bitmap64::deserialize_from_reader::<Portable, _>(&mut reader).unwrap(), &original64);
+        prop_assert_eq!(&croaring::Bitmap64::deserialize_from_reader::<Native, _>(&mut reader).unwrap(), &original64);
+        prop_assert_eq!(&Treemap::deserialize_from_reader::<Native, _>(&mut reader).unwrap(), &treemap);
+        prop_assert_eq!(&Treemap::deserialize_from_reader::<JvmLegacy, _>(&mut reader).unwrap(), &treemap);
+        prop_assert!(reader.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn try_deserialize_view_rejects_corruption(
+        indices in prop::collection::vec(0..0x4_0000u32, 0..3000),
+        run_optimize in any::<bool>(),
+        corruption in prop::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..4),
+    ) {
+        use croaring::BitmapView;
+        init();
+
+        let mut original = Bitmap::of(&indices);
+        if run_optimize {
+            original.run_optimize();
+        }
+        let mut portable = original.serialize::<Portable>();
+        // Portable data with run containers may not be aligned for viewing in place
+        match BitmapView::try_deserialize::<Portable>(&portable) {
+            Ok(view) => prop_assert_eq!(&view, &original),
+            Err(err) => prop_assert!(
+                matches!(err, croaring::DeserializeError::Misaligned { .. }),
+                "{}",
+                err
+            ),
+        }
+        let mut buf = Vec::new();
+        let frozen = original.serialize_into_vec::<Frozen>(&mut buf);
+        prop_assert_eq!(&BitmapView::try_deserialize::<Frozen>(frozen).unwrap(), &original);
+
+        // Any view handed out for corrupted data must be a valid bitmap
+        for &(index, byte) in &corruption {
+            let i = index.index(portable.len());
+            portable[i] = byte;
+            let i = index.index(frozen.len());
+            frozen[i] = byte;
+        }
+        let views = [
+            BitmapView::try_deserialize::<Portable>(&portable),
+            BitmapView::try_deserialize::<Frozen>(frozen),
+        ];
+        for view in views.iter().flatten() {
+            prop_assert!(view.internal_validate().is_ok());
+            prop_assert_eq!(view.iter().count() as u64, view.cardinality());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn try_deserialize_view64_rejects_corruption(
+        indices in prop::collection::vec((0..4u64, 0..0x300_0000u64), 0..3000),
+        run_optimize in any::<bool>(),
+        corruption in prop::collection::vec((any::<prop::sample::Index>(), any::<bool>(), any::<u8>()), 1..4),
+    ) {
+        use croaring::{Bitmap64, Bitmap64View};
+        init();
+
+        let mut original: Bitmap64 = indices.iter().map(|&(high, low)| high << 40 | low).collect();
+        if run_optimize {
+            original.run_optimize();
+        }
+        original.shrink_to_fit();
+        let mut buf = Vec::new();
+        let frozen = original.serialize_into_vec::<Frozen>(&mut buf);
+        prop_assert_eq!(&Bitmap64View::try_deserialize::<Frozen>(frozen).unwrap(), &original);
+        let range = frozen.as_ptr() as usize - buf.as_ptr() as usize..buf.len();
+        let frozen = &mut buf[range];
+
+        // Any view handed out for corrupted data must be a valid bitmap. The headers and tree
+        // come before the containers, so are corrupted more often
+        for &(index, in_header, byte) in &corruption {
+            let len = if in_header { frozen.len().min(1024) } else { frozen.len() };
+            frozen[index.index(len)] = byte;
+        }
+        if let Ok(view) = Bitmap64View::try_deserialize::<Frozen>(frozen) {
+            prop_assert!(view.internal_validate().is_ok());
+            prop_assert_eq!(view.iter().count() as u64, view.cardinality());
+        }
+    }
+
     #[test]
     #[cfg(feature = "alloc")]
     fn frozen_bitmap_roundtrip(
@@ -9,6 +9,7 @@ fn init() {
     }
 }
 
+#[allow(clippy::identity_op)]
 fn expected_serialized_bitmap() -> Bitmap64 {
     let mut bitmap = Bitmap64::new();
 
@@ -22,7 +23,7 @@ fn expected_serialized_bitmap() -> Bitmap64 {
         bitmap.add(base | 0x2_0005);
         // Bitmap container
         for j in (0..0x1_0000).step_by(2) {
-            bitmap.add(base | 0x80000 + j);
+            bitmap.add(base | (0x80000 + j));
         }
     }
     bitmap
@@ -38,6 +39,159 @@ fn test_portable_deserialize() {
     assert!(bitmap.iter().eq(expected.iter()))
 }
 
+#[test]
+#[cfg(feature = "std")]
+fn test_portable_deserialize_from_reader() {
+    init();
+    let mut file = fs::File::open("tests/data/portable_bitmap64.bin").unwrap();
+    let bitmap = Bitmap64::deserialize_from_reader::<Portable, _>(&mut file).unwrap();
+    assert_eq!(bitmap, expected_serialized_bitmap());
+    // The whole file was consumed, and nothing more
+    assert_eq!(std::io::Read::read(&mut file, &mut [0]).unwrap(), 0);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_portable_serialize_into_writer() {
+    init();
+    // Holds a run container which is larger than its array form
+    let unoptimized = expected_serialized_bitmap();
+    let mut optimized = unoptimized.clone();
+    optimized.run_optimize();
+    let mut edges = Bitmap64::of(&[0, u64::from(u32::MAX), u64::MAX - 1, u64::MAX]);
+    edges.add_range(5 << 32..6 << 32);
+
+    for bitmap in [Bitmap64::new(), unoptimized, optimized, edges] {
+        let mut written = Vec::new();
+        let len = bitmap
+            .serialize_into_writer::<Portable, _>(&mut written)
+            .unwrap();
+        assert_eq!(len, written.len());
+        assert_eq!(written, bitmap.serialize::<Portable>());
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_jvm_legacy_roundtrip() {
+    use croaring::bitmap64::Deserializer;
+    use croaring::{JvmLegacy, Treemap};
+
+    init();
+    // Written by Roaring64NavigableMap in the Java implementation
+    let buffer = fs::read("tests/data/testjvm.bin").unwrap();
+    let bitmap = Bitmap64::try_deserialize_exact::<JvmLegacy>(&buffer).unwrap();
+    let mut expected = Bitmap64::from_range(100..1000);
+    expected.add(u64::from(u32::MAX));
+    expected.add(u64::MAX);
+    assert_eq!(bitmap, expected);
+    let treemap = Treemap::try_deserialize::<JvmLegacy>(&buffer).unwrap();
+    assert!(bitmap.iter().eq(treemap.iter()));
+
+    assert_eq!(
+        bitmap.get_serialized_size_in_bytes::<JvmLegacy>(),
+        buffer.len()
+    );
+    assert_eq!(bitmap.serialize::<JvmLegacy>(), buffer);
+    let mut written = Vec::new();
+    bitmap
+        .serialize_into_writer::<JvmLegacy, _>(&mut written)
+        .unwrap();
+    assert_eq!(written, buffer);
+    let mut reader = &buffer[..];
+    let read = Bitmap64::deserialize_from_reader::<JvmLegacy, _>(&mut reader).unwrap();
+    assert_eq!(read, bitmap);
+    assert!(reader.is_empty());
+    assert_eq!(
+        JvmLegacy::find_end(&buffer).map(|end| end.get()),
+        Some(buffer.len())
+    );
+
+    let empty = Bitmap64::new();
+    let serialized = empty.serialize::<JvmLegacy>();
+    assert_eq!(serialized, [0, 0, 0, 0, 0]);
+    assert_eq!(
+        Bitmap64::try_deserialize_exact::<JvmLegacy>(&serialized).unwrap(),
+        empty
+    );
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_jvm_legacy_signed() {
+    use croaring::{Bitmap, DeserializeError, JvmLegacy};
+
+    init();
+    // With signed longs, Java orders the bucket for the high bits 0xFFFF_FFFF (-1) first
+    let buffer = fs::read("tests/data/testjvm.bin").unwrap();
+    let (header, rest) = buffer.split_at(5);
+    let second_len = 4 + Bitmap::of(&[u32::MAX]).get_serialized_size_in_bytes::<Portable>();
+    let (first, second) = rest.split_at(rest.len() - second_len);
+    let mut signed = vec![1];
+    signed.extend_from_slice(&header[1..]);
+    signed.extend_from_slice(second);
+    signed.extend_from_slice(first);
+    assert_eq!(
+        Bitmap64::try_deserialize_exact::<JvmLegacy>(&signed).unwrap(),
+        Bitmap64::try_deserialize_exact::<JvmLegacy>(&buffer).unwrap()
+    );
+
+    // Buckets must not be repeated
+    let mut repeated = header.to_vec();
+    repeated.extend_from_slice(second);
+    repeated.extend_from_slice(second);
+    assert_eq!(
+        Bitmap64::try_deserialize_exact::<JvmLegacy>(&repeated),
+        Err(DeserializeError::InvalidHeader {
+            reason: "duplicate bucket key"
+        })
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_native_roundtrip() {
+    use croaring::bitmap64::Deserializer;
+    use croaring::Native;
+
+    init();
+    // Sparse bitmaps are stored as an array of values, dense bitmaps in the portable format
+    let sparse = Bitmap64::of(&[1, 1 << 20, 1 << 40, u64::MAX]);
+    let dense = expected_serialized_bitmap();
+    for (bitmap, kind) in [(&sparse, 1), (&dense, 2)] {
+        let serialized = bitmap.serialize::<Native>();
+        assert_eq!(serialized[0], kind);
+        assert_eq!(
+            serialized.len(),
+            bitmap.get_serialized_size_in_bytes::<Native>()
+        );
+        assert!(serialized.len() <= 1 + bitmap.get_serialized_size_in_bytes::<Portable>());
+        assert_eq!(
+            &Bitmap64::try_deserialize_exact::<Native>(&serialized).unwrap(),
+            bitmap
+        );
+
+        // The end of the bitmap can be found when it is followed by other data
+        let mut stream = serialized.clone();
+        stream.extend_from_slice(&[0xFF; 3]);
+        assert_eq!(
+            Native::find_end(&stream).map(|end| end.get()),
+            Some(serialized.len())
+        );
+        let mut reader = &stream[..];
+        let read = Bitmap64::deserialize_from_reader::<Native, _>(&mut reader).unwrap();
+        assert_eq!(&read, bitmap);
+        assert_eq!(reader, [0xFF; 3]);
+    }
+    assert_eq!(
+        sparse.get_serialized_size_in_bytes::<Native>(),
+        1 + 8 + 4 * 8
+    );
+
+    assert_eq!(Native::find_end(&[1, 1, 0, 0, 0, 0, 0, 0, 0]), None);
+    assert_eq!(Native::find_end(&[3]), None);
+}
+
 #[test]
 fn test_r64_contains_max() {
     init();
@@ -101,3 +255,35 @@ fn copy_from_view() {
     assert!(!view.contains(10));
     assert!(mutable_bitmap.contains(10));
 }
+
+#[test]
+#[cfg(feature = "mmap")]
+fn test_mmap_view() {
+    use croaring::MmapBitmap64View;
+    use std::io::ErrorKind;
+
+    init();
+    let mut expected = expected_serialized_bitmap();
+    expected.shrink_to_fit();
+    let mut buf = Vec::new();
+    let frozen = expected.serialize_into_vec::<Frozen>(&mut buf);
+    let mut data = vec![0; 64];
+    data.extend_from_slice(frozen);
+    let path = std::env::temp_dir().join(format!("croaring-test-mmap64-{}", std::process::id()));
+    fs::write(&path, &data).unwrap();
+    let file = fs::File::open(&path).unwrap();
+
+    let view = unsafe { MmapBitmap64View::map_range::<Frozen>(&file, 64, frozen.len()) };
+    assert_eq!(view.unwrap(), expected);
+    let misaligned = unsafe { MmapBitmap64View::map_range::<Frozen>(&file, 32, frozen.len()) };
+    assert_eq!(misaligned.unwrap_err().kind(), ErrorKind::InvalidData);
+    let past_end = unsafe { MmapBitmap64View::map_range::<Frozen>(&file, 65, frozen.len()) };
+    assert_eq!(past_end.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+
+    fs::write(&path, frozen).unwrap();
+    let view = unsafe { MmapBitmap64View::open::<Frozen>(&path) };
+    assert_eq!(view.unwrap(), expected);
+
+    drop(file);
+    fs::remove_file(&path).unwrap();
+}
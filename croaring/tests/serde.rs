@@ -0,0 +1,187 @@
+#![cfg(feature = "serde")]
+
+use croaring::{Bitmap, Bitmap64, Bitset, Portable, Treemap};
+use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Configure, Token};
+
+// serde_test requires tokens to borrow static data
+fn leak(bytes: Vec<u8>) -> &'static [u8] {
+    Box::leak(bytes.into_boxed_slice())
+}
+
+fn init() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| unsafe { croaring::configure_rust_alloc() });
+}
+
+#[test]
+fn bitmap_json_roundtrip() {
+    init();
+    let bitmap = Bitmap::of(&[1, 2, 3, 100, u32::MAX]);
+    let json = serde_json::to_string(&bitmap).unwrap();
+    assert_eq!(json, "[1,2,3,100,4294967295]");
+    assert_eq!(serde_json::from_str::<Bitmap>(&json).unwrap(), bitmap);
+    assert_eq!(
+        serde_json::from_str::<Bitmap>("[3,1,2,1]").unwrap(),
+        Bitmap::of(&[1, 2, 3])
+    );
+    assert!(serde_json::from_str::<Bitmap>("[-1]").is_err());
+}
+
+#[test]
+fn bitmap_binary_roundtrip() {
+    init();
+    let mut bitmap = Bitmap::from_range(0..100_000);
+    bitmap.add_many(&[200_000, 300_000]);
+    bitmap.run_optimize();
+    let bytes = leak(bitmap.serialize::<Portable>());
+    assert_tokens(&bitmap.compact(), &[Token::Bytes(bytes)]);
+}
+
+#[test]
+fn bitmap_binary_rejects_invalid() {
+    init();
+    assert_de_tokens_error::<serde_test::Compact<Bitmap>>(
+        &[Token::Bytes(&[1, 2, 3])],
+        "invalid value: input truncated: needed at least 4 bytes, but only 3 available, \
+         expected a bitmap serialized in the portable format",
+    );
+
+    let mut bytes = Bitmap::of(&[1, 2, 3]).serialize::<Portable>();
+    bytes.push(0);
+    let bytes = leak(bytes);
+    assert_de_tokens_error::<serde_test::Compact<Bitmap>>(
+        &[Token::Bytes(bytes)],
+        "invalid value: 1 unexpected bytes after the serialized data, \
+         expected a bitmap serialized in the portable format",
+    );
+}
+
+// [1, 2, 3] in the portable format with a run container cookie, but no run containers: valid,
+// though CRoaring would serialize it without the run flags, and with an offset header
+const PORTABLE_RUN_COOKIE_NO_RUNS: &[u8] = &[
+    0x3B, 0x30, 0x00, 0x00, // cookie, 1 container
+    0x00, // run flags
+    0x00, 0x00, 0x02, 0x00, // key 0, cardinality 3
+    0x01, 0x00, 0x02, 0x00, 0x03, 0x00, // values
+];
+
+#[test]
+fn bitmap_binary_accepts_other_encodings() {
+    init();
+    let bitmap = Bitmap::of(&[1, 2, 3]);
+    assert_ne!(
+        bitmap.get_serialized_size_in_bytes::<Portable>(),
+        PORTABLE_RUN_COOKIE_NO_RUNS.len()
+    );
+    assert_de_tokens(
+        &bitmap.compact(),
+        &[Token::Bytes(PORTABLE_RUN_COOKIE_NO_RUNS)],
+    );
+
+    let bytes = [
+        &1u64.to_ne_bytes()[..],
+        &0u32.to_ne_bytes(),
+        PORTABLE_RUN_COOKIE_NO_RUNS,
+    ]
+    .concat();
+    assert_de_tokens(
+        &Bitmap64::of(&[1, 2, 3]).compact(),
+        &[Token::Bytes(leak(bytes))],
+    );
+}
+
+#[test]
+fn bitmap64_roundtrip() {
+    init();
+    let bitmap = Bitmap64::of(&[1, 2, 3, u64::from(u32::MAX) + 10, u64::MAX]);
+    let json = serde_json::to_string(&bitmap).unwrap();
+    assert_eq!(
+        json,
+        format!("[1,2,3,{},{}]", u64::from(u32::MAX) + 10, u64::MAX)
+    );
+    assert_eq!(serde_json::from_str::<Bitmap64>(&json).unwrap(), bitmap);
+
+    let bytes = leak(bitmap.serialize::<Portable>());
+    assert_tokens(&bitmap.compact(), &[Token::Bytes(bytes)]);
+    assert_de_tokens_error::<serde_test::Compact<Bitmap64>>(
+        &[Token::Bytes(&bytes[..bytes.len() - 1])],
+        &format!(
+            "invalid value: input truncated: needed at least {} bytes, but only {} available, \
+             expected a bitmap serialized in the portable format",
+            bytes.len(),
+            bytes.len() - 1
+        ),
+    );
+}
+
+#[test]
+fn treemap_roundtrip() {
+    init();
+    let treemap = Treemap::of(&[1, 2, 3, u64::from(u32::MAX) + 10, u64::MAX]);
+    let json = serde_json::to_string(&treemap).unwrap();
+    assert_eq!(
+        json,
+        format!("[1,2,3,{},{}]", u64::from(u32::MAX) + 10, u64::MAX)
+    );
+    assert_eq!(serde_json::from_str::<Treemap>(&json).unwrap(), treemap);
+
+    let bytes = leak(treemap.serialize::<Portable>());
+    assert_tokens(&treemap.compact(), &[Token::Bytes(bytes)]);
+    assert_de_tokens_error::<serde_test::Compact<Treemap>>(
+        &[Token::Bytes(&bytes[..bytes.len() - 1])],
+        &format!(
+            "invalid value: input truncated: needed at least {} bytes, but only {} available, \
+             expected a treemap serialized in the portable format",
+            bytes.len(),
+            bytes.len() - 1
+        ),
+    );
+}
+
+#[test]
+fn bitset_roundtrip() {
+    init();
+    let bitset: Bitset = [1, 2, 3, 64, 200].into_iter().collect();
+    let json = serde_json::to_string(&bitset).unwrap();
+    assert_eq!(json, "[1,2,3,64,200]");
+    let deserialized = serde_json::from_str::<Bitset>(&json).unwrap();
+    assert_eq!(deserialized.iter().collect::<Vec<_>>(), [1, 2, 3, 64, 200]);
+
+    let words = bitset.as_slice();
+    let mut tokens = vec![Token::Seq {
+        len: Some(words.len()),
+    }];
+    tokens.extend(words.iter().map(|&w| Token::U64(w)));
+    tokens.push(Token::SeqEnd);
+    assert_tokens(&Words(bitset).compact(), &tokens);
+}
+
+#[test]
+fn bitset_index_too_large() {
+    init();
+    assert!(serde_json::from_str::<Bitset>("[1, 18446744073709551615]").is_err());
+    let too_large = u64::from(u32::MAX) + 1;
+    assert!(serde_json::from_str::<Bitset>(&format!("[{too_large}]")).is_err());
+}
+
+/// Bitset doesn't implement `PartialEq`, compare by underlying words
+#[derive(Debug)]
+struct Words(Bitset);
+
+impl PartialEq for Words {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_slice() == other.0.as_slice()
+    }
+}
+
+impl serde::Serialize for Words {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Words {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <Bitset as serde::Deserialize>::deserialize(deserializer).map(Words)
+    }
+}
@@ -1,6 +1,6 @@
 #![no_main]
 
-use croaring::{Bitmap, Bitmap64, Native, Portable};
+use croaring::{Bitmap, Bitmap64, Bitmap64View, Frozen, Native, Portable};
 use libfuzzer_sys::fuzz_target;
 use libfuzzer_sys::arbitrary::{self, Arbitrary};
 
@@ -64,11 +64,29 @@ fn check_bitmap64<D: croaring::bitmap64::Deserializer>(input: &[u8]) {
     }
 }
 
+fn check_view64(input: &[u8]) {
+    // Frozen views must be aligned to 64 bytes, so copy into an aligned offset
+    let mut buf = vec![0u8; input.len() + 63];
+    let offset = buf.as_ptr().align_offset(64);
+    let data = &mut buf[offset..offset + input.len()];
+    data.copy_from_slice(input);
+    let data = &*data;
+
+    if let Ok(view) = Bitmap64View::try_deserialize::<Frozen>(data) {
+        view.internal_validate().unwrap();
+        assert_eq!(view.iter().count() as u64, view.cardinality());
+        let owned = view.to_bitmap64();
+        owned.internal_validate().unwrap();
+        assert_eq!(owned, *view);
+    }
+}
+
 #[derive(Arbitrary, Debug)]
 enum BitmapType {
     Portable32,
     Native32,
     Portable64,
+    Frozen64View,
 }
 
 fuzz_target!(|input: (BitmapType, &[u8])| {
@@ -77,5 +95,6 @@ fuzz_target!(|input: (BitmapType, &[u8])| {
         BitmapType::Portable32 => check_bitmap::<Portable>(input),
         BitmapType::Native32 => check_bitmap::<Native>(input),
         BitmapType::Portable64 => check_bitmap64::<Portable>(input),
+        BitmapType::Frozen64View => check_view64(input),
     }
 });